@@ -12,7 +12,7 @@ use std::{
 };
 #[cfg(not(target_os = "macos"))]
 use tracing::info;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 #[serde_as]
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -75,6 +75,29 @@ impl Map {
         }
     }
 
+    /// Map for 6DoF devices such as 3Dconnexion SpaceMouse, which are recognized as joysticks
+    /// with six axes.
+    pub fn new_space_mouse() -> Self {
+        let mut axis_map = HashMap::new();
+        axis_map.insert(gilrs::Axis::LeftStickX, Axis::LinearY);
+        axis_map.insert(gilrs::Axis::LeftStickY, Axis::LinearX);
+        axis_map.insert(gilrs::Axis::LeftZ, Axis::LinearZ);
+        axis_map.insert(gilrs::Axis::RightStickX, Axis::AngularY);
+        axis_map.insert(gilrs::Axis::RightStickY, Axis::AngularX);
+        axis_map.insert(gilrs::Axis::RightZ, Axis::AngularZ);
+        let mut axis_value_map = HashMap::new();
+        axis_value_map.insert(Axis::LinearY, -1.0);
+        axis_value_map.insert(Axis::LinearZ, -1.0);
+        axis_value_map.insert(Axis::AngularY, -1.0);
+        axis_value_map.insert(Axis::AngularZ, -1.0);
+
+        Self {
+            button_map: HashMap::new(),
+            axis_map,
+            axis_value_map,
+        }
+    }
+
     fn convert_button(&self, b: gilrs::Button) -> Button {
         if let Some(e) = self.button_map.get(&b) {
             debug!("convert_button {:?} -> {:?}", b, e);
@@ -107,6 +130,8 @@ impl Map {
                 let (axis, value) = self.convert_axis(a, v);
                 Some(GamepadEvent::AxisChanged(axis, value))
             }
+            gilrs::EventType::Connected => Some(GamepadEvent::Connected),
+            gilrs::EventType::Disconnected => Some(GamepadEvent::Disconnected),
             _ => None,
         }
     }
@@ -177,6 +202,16 @@ mod test {
         assert!((v - -0.1).abs() < 0.00001);
     }
     #[test]
+    fn test_space_mouse_map() {
+        let m = Map::new_space_mouse();
+        let (a, v) = m.convert_axis(gilrs::Axis::LeftStickY, 0.1);
+        assert_eq!(a, arci::gamepad::Axis::LinearX);
+        assert!((v - 0.1).abs() < 0.00001);
+        let (a, v) = m.convert_axis(gilrs::Axis::RightZ, 0.1);
+        assert_eq!(a, arci::gamepad::Axis::AngularZ);
+        assert!((v - -0.1).abs() < 0.00001);
+    }
+    #[test]
     fn test_default_map() {
         let m = Map::default();
         assert_eq!(
@@ -207,10 +242,25 @@ mod test {
         assert_eq!(a, arci::gamepad::Axis::Unknown);
         assert!((v - 0.0).abs() < 0.00001);
     }
+
+    #[test]
+    fn test_convert_connection_event() {
+        let m = Map::default();
+        assert_eq!(
+            m.convert_event(gilrs::EventType::Connected),
+            Some(GamepadEvent::Connected)
+        );
+        assert_eq!(
+            m.convert_event(gilrs::EventType::Disconnected),
+            Some(GamepadEvent::Disconnected)
+        );
+        assert_eq!(m.convert_event(gilrs::EventType::Dropped), None);
+    }
 }
 
 pub struct GilGamepad {
     rx: crossbeam_channel::Receiver<GamepadEvent>,
+    rumble_tx: crossbeam_channel::Sender<(f64, Duration)>,
     _handle: std::thread::JoinHandle<()>,
     is_running: Arc<AtomicBool>,
 }
@@ -218,10 +268,23 @@ pub struct GilGamepad {
 impl GilGamepad {
     pub fn new(id: usize, map: Map) -> Self {
         let (tx, rx) = crossbeam_channel::unbounded();
+        let (rumble_tx, rumble_rx) = crossbeam_channel::unbounded();
         let is_running = Arc::new(AtomicBool::new(true));
         let is_running_cloned = is_running.clone();
         let _handle = std::thread::spawn(move || {
-            let mut gil = gilrs::Gilrs::new().unwrap();
+            let mut gil = match gilrs::Gilrs::new() {
+                Ok(gil) => gil,
+                Err(gilrs::Error::NotImplemented(gil)) => {
+                    warn!("gilrs is not supported on this platform");
+                    gil
+                }
+                Err(e) => {
+                    error!("Failed to initialize gilrs: {}", e);
+                    // No gamepad can be used, so tell the receiver to stop.
+                    let _ = tx.send(GamepadEvent::Unknown);
+                    return;
+                }
+            };
             // TODO: On MacOS `gamepads()` does not works.
             #[cfg(not(target_os = "macos"))]
             {
@@ -233,10 +296,22 @@ impl GilGamepad {
                     }
                 }
                 if !is_found {
-                    panic!("No Gamepad id={} is found", id);
+                    warn!(
+                        "No Gamepad id={} is found, waiting for it to be connected",
+                        id
+                    );
+                    let _ = tx.send(GamepadEvent::Disconnected);
                 }
             }
+            // The effect stops when it is dropped.
+            let mut _playing_effect = None;
             while is_running_cloned.load(Ordering::Relaxed) {
+                for (intensity, duration) in rumble_rx.try_iter() {
+                    match play_rumble(&mut gil, id, intensity, duration) {
+                        Ok(effect) => _playing_effect = effect,
+                        Err(e) => debug!("Failed to rumble: {}", e),
+                    }
+                }
                 // gil.next_event is no block. We have to polling it.
                 match gil.next_event() {
                     Some(gilrs::Event {
@@ -244,7 +319,9 @@ impl GilGamepad {
                     }) => {
                         if id == recv_id.into() {
                             if let Some(e) = map.convert_event(event) {
-                                tx.send(e).unwrap();
+                                if tx.send(e).is_err() {
+                                    break;
+                                }
                             }
                         }
                     }
@@ -257,6 +334,7 @@ impl GilGamepad {
 
         Self {
             rx,
+            rumble_tx,
             _handle,
             is_running,
         }
@@ -280,6 +358,44 @@ impl Gamepad for GilGamepad {
     fn stop(&self) {
         self.is_running.store(false, Ordering::Relaxed);
     }
+    fn rumble(&self, intensity: f64, duration: Duration) {
+        let _ = self.rumble_tx.send((intensity, duration));
+    }
+}
+
+/// Plays the force feedback on the gamepad `id`. Returns `None` if the gamepad doesn't support it.
+fn play_rumble(
+    gil: &mut gilrs::Gilrs,
+    id: usize,
+    intensity: f64,
+    duration: Duration,
+) -> Result<Option<gilrs::ff::Effect>, gilrs::ff::Error> {
+    use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Repeat, Replay, Ticks};
+    let gamepad_ids: Vec<_> = gil
+        .gamepads()
+        .filter(|(gamepad_id, gamepad)| id == (*gamepad_id).into() && gamepad.is_ff_supported())
+        .map(|(gamepad_id, _)| gamepad_id)
+        .collect();
+    if gamepad_ids.is_empty() {
+        return Ok(None);
+    }
+    let play_for = Ticks::from_ms(duration.as_millis() as u32);
+    let effect = EffectBuilder::new()
+        .add_effect(BaseEffect {
+            kind: BaseEffectType::Strong {
+                magnitude: (intensity.clamp(0.0, 1.0) * u16::MAX as f64) as u16,
+            },
+            scheduling: Replay {
+                play_for,
+                ..Default::default()
+            },
+            envelope: Default::default(),
+        })
+        .repeat(Repeat::For(play_for))
+        .gamepads(&gamepad_ids)
+        .finish(gil)?;
+    effect.play()?;
+    Ok(Some(effect))
 }
 
 /* We need gamepad to test actually
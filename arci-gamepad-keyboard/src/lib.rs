@@ -0,0 +1,450 @@
+//! [`arci::Gamepad`] implementation using the keyboard of the terminal, e.g. over SSH.
+//!
+//! Terminals report only key presses (and their auto-repeats), so a button or an axis is
+//! released if the key is not repeated within `release_timeout_secs`. Buttons in
+//! `toggle_buttons` (the deadman button by default) are pressed and released by turns
+//! instead, because only one key can be repeated at a time. Esc releases everything.
+
+use arci::gamepad::*;
+use arci::*;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{error, warn};
+
+const TICK: Duration = Duration::from_millis(10);
+
+/// Axis and the value when the key is pressed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AxisKey {
+    pub axis: Axis,
+    pub value: f64,
+}
+
+/// Key bindings of `KeyboardGamepad`.
+///
+/// Keys are the characters (case insensitive) or the names of the special keys:
+/// `space`, `enter`, `tab`, `backspace`, `up`, `down`, `left`, `right`, `f1`...
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyboardGamepadConfig {
+    #[serde(default = "default_button_map")]
+    pub button_map: HashMap<String, Button>,
+    #[serde(default = "default_axis_map")]
+    pub axis_map: HashMap<String, AxisKey>,
+    /// Buttons which are pressed and released by turns.
+    #[serde(default = "default_toggle_buttons")]
+    pub toggle_buttons: Vec<Button>,
+    /// Must be longer than the key repeat delay of the terminal.
+    #[serde(default = "default_release_timeout_secs")]
+    pub release_timeout_secs: f64,
+    /// Time for an axis to change from 0 to 1.
+    #[serde(default = "default_axis_ramp_secs")]
+    pub axis_ramp_secs: f64,
+}
+
+impl Default for KeyboardGamepadConfig {
+    fn default() -> Self {
+        Self {
+            button_map: default_button_map(),
+            axis_map: default_axis_map(),
+            toggle_buttons: default_toggle_buttons(),
+            release_timeout_secs: default_release_timeout_secs(),
+            axis_ramp_secs: default_axis_ramp_secs(),
+        }
+    }
+}
+
+fn default_button_map() -> HashMap<String, Button> {
+    let mut button_map = HashMap::new();
+    button_map.insert("space".to_owned(), Button::RightTrigger2);
+    button_map.insert("tab".to_owned(), Button::LeftTrigger2);
+    button_map.insert("m".to_owned(), Button::North);
+    button_map.insert("n".to_owned(), Button::East);
+    button_map.insert("r".to_owned(), Button::West);
+    button_map.insert("f".to_owned(), Button::South);
+    button_map.insert("up".to_owned(), Button::DPadUp);
+    button_map.insert("down".to_owned(), Button::DPadDown);
+    button_map.insert("left".to_owned(), Button::DPadLeft);
+    button_map.insert("right".to_owned(), Button::DPadRight);
+    button_map
+}
+
+fn default_axis_map() -> HashMap<String, AxisKey> {
+    let mut axis_map = HashMap::new();
+    let mut insert = |key: &str, axis, value| {
+        axis_map.insert(key.to_owned(), AxisKey { axis, value });
+    };
+    insert("w", Axis::LeftStickY, 1.0);
+    insert("s", Axis::LeftStickY, -1.0);
+    insert("a", Axis::LeftStickX, 1.0);
+    insert("d", Axis::LeftStickX, -1.0);
+    insert("i", Axis::RightStickY, 1.0);
+    insert("k", Axis::RightStickY, -1.0);
+    insert("j", Axis::RightStickX, 1.0);
+    insert("l", Axis::RightStickX, -1.0);
+    axis_map
+}
+
+fn default_toggle_buttons() -> Vec<Button> {
+    vec![Button::RightTrigger2, Button::LeftTrigger2]
+}
+
+fn default_release_timeout_secs() -> f64 {
+    0.7
+}
+
+fn default_axis_ramp_secs() -> f64 {
+    0.5
+}
+
+/// Converts the key to the name used in `KeyboardGamepadConfig`.
+fn key_name(key: &KeyEvent) -> Option<String> {
+    Some(match key.code {
+        KeyCode::Char(' ') => "space".to_owned(),
+        KeyCode::Char(c) => c.to_lowercase().to_string(),
+        KeyCode::Enter => "enter".to_owned(),
+        KeyCode::Tab => "tab".to_owned(),
+        KeyCode::Backspace => "backspace".to_owned(),
+        KeyCode::Up => "up".to_owned(),
+        KeyCode::Down => "down".to_owned(),
+        KeyCode::Left => "left".to_owned(),
+        KeyCode::Right => "right".to_owned(),
+        KeyCode::F(n) => format!("f{}", n),
+        _ => return None,
+    })
+}
+
+/// Converts the keys to the gamepad events.
+struct KeyboardState {
+    config: KeyboardGamepadConfig,
+    /// Held buttons and the time when the key is pressed last.
+    pressed_buttons: HashMap<Button, Instant>,
+    toggled_buttons: HashSet<Button>,
+    /// Target values of the axes and the time when the key is pressed last.
+    axis_targets: HashMap<Axis, (f64, Instant)>,
+    axis_values: HashMap<Axis, f64>,
+    last_update: Option<Instant>,
+}
+
+impl KeyboardState {
+    fn new(config: KeyboardGamepadConfig) -> Self {
+        Self {
+            config,
+            pressed_buttons: HashMap::new(),
+            toggled_buttons: HashSet::new(),
+            axis_targets: HashMap::new(),
+            axis_values: HashMap::new(),
+            last_update: None,
+        }
+    }
+
+    fn release_timeout(&self) -> Duration {
+        Duration::from_secs_f64(self.config.release_timeout_secs)
+    }
+
+    fn on_key(&mut self, key: &str, now: Instant) -> Vec<GamepadEvent> {
+        let mut events = vec![];
+        if let Some(&button) = self.config.button_map.get(key) {
+            if self.config.toggle_buttons.contains(&button) {
+                if self.toggled_buttons.remove(&button) {
+                    events.push(GamepadEvent::ButtonReleased(button));
+                } else {
+                    self.toggled_buttons.insert(button);
+                    events.push(GamepadEvent::ButtonPressed(button));
+                }
+            } else if self.pressed_buttons.insert(button, now).is_none() {
+                events.push(GamepadEvent::ButtonPressed(button));
+            }
+        } else if let Some(axis_key) = self.config.axis_map.get(key) {
+            self.axis_targets
+                .insert(axis_key.axis, (axis_key.value, now));
+        }
+        events
+    }
+
+    /// Releases all buttons and axes immediately.
+    fn release_all(&mut self) -> Vec<GamepadEvent> {
+        let mut events: Vec<_> = self
+            .toggled_buttons
+            .drain()
+            .chain(self.pressed_buttons.drain().map(|(button, _)| button))
+            .map(GamepadEvent::ButtonReleased)
+            .collect();
+        self.axis_targets.clear();
+        events.extend(
+            self.axis_values
+                .drain()
+                .map(|(axis, _)| GamepadEvent::AxisChanged(axis, 0.0)),
+        );
+        events
+    }
+
+    /// Releases the keys which are not repeated and ramps the axes.
+    fn update(&mut self, now: Instant) -> Vec<GamepadEvent> {
+        let mut events = vec![];
+        let timeout = self.release_timeout();
+        let released: Vec<_> = self
+            .pressed_buttons
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) > timeout)
+            .map(|(button, _)| *button)
+            .collect();
+        for button in released {
+            self.pressed_buttons.remove(&button);
+            events.push(GamepadEvent::ButtonReleased(button));
+        }
+        self.axis_targets
+            .retain(|_, (_, last)| now.duration_since(*last) <= timeout);
+
+        let dt = self
+            .last_update
+            .map_or(0.0, |last| now.duration_since(last).as_secs_f64());
+        self.last_update = Some(now);
+        let step = if self.config.axis_ramp_secs > 0.0 {
+            dt / self.config.axis_ramp_secs
+        } else {
+            f64::INFINITY
+        };
+        let axes: HashSet<_> = self
+            .axis_targets
+            .keys()
+            .chain(self.axis_values.keys())
+            .copied()
+            .collect();
+        for axis in axes {
+            let target = self.axis_targets.get(&axis).map_or(0.0, |(v, _)| *v);
+            let current = self.axis_values.get(&axis).copied().unwrap_or(0.0);
+            if (target - current).abs() < f64::EPSILON {
+                continue;
+            }
+            let value = if target > current {
+                (current + step).min(target)
+            } else {
+                (current - step).max(target)
+            };
+            if value == 0.0 {
+                self.axis_values.remove(&axis);
+            } else {
+                self.axis_values.insert(axis, value);
+            }
+            events.push(GamepadEvent::AxisChanged(axis, value));
+        }
+        events
+    }
+}
+
+/// `Gamepad` using the keyboard of the terminal.
+///
+/// The terminal is in the raw mode until `stop` is called. Ctrl-C sends `GamepadEvent::Unknown`.
+pub struct KeyboardGamepad {
+    rx: crossbeam_channel::Receiver<GamepadEvent>,
+    _handle: std::thread::JoinHandle<()>,
+    is_running: Arc<AtomicBool>,
+}
+
+impl KeyboardGamepad {
+    pub fn new_from_config(config: KeyboardGamepadConfig) -> Self {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let is_running = Arc::new(AtomicBool::new(true));
+        let is_running_cloned = is_running.clone();
+        let _handle = std::thread::spawn(move || {
+            if let Err(e) = crossterm::terminal::enable_raw_mode() {
+                error!("Failed to enable raw mode: {}", e);
+            }
+            let mut state = KeyboardState::new(config);
+            while is_running_cloned.load(Ordering::Relaxed) {
+                let mut events = vec![];
+                match event::poll(TICK) {
+                    Ok(true) => match event::read() {
+                        Ok(Event::Key(KeyEvent {
+                            code: KeyCode::Char('c'),
+                            modifiers,
+                        })) if modifiers.contains(KeyModifiers::CONTROL) => {
+                            events.extend(state.release_all());
+                            events.push(GamepadEvent::Unknown);
+                            is_running_cloned.store(false, Ordering::Relaxed);
+                        }
+                        Ok(Event::Key(KeyEvent {
+                            code: KeyCode::Esc, ..
+                        })) => events.extend(state.release_all()),
+                        Ok(Event::Key(key)) => {
+                            if let Some(name) = key_name(&key) {
+                                events.extend(state.on_key(&name, Instant::now()));
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("Failed to read the terminal event: {}", e),
+                    },
+                    Ok(false) => {}
+                    Err(e) => warn!("Failed to poll the terminal event: {}", e),
+                }
+                events.extend(state.update(Instant::now()));
+                for e in events {
+                    if tx.send(e).is_err() {
+                        is_running_cloned.store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+            if let Err(e) = crossterm::terminal::disable_raw_mode() {
+                error!("Failed to disable raw mode: {}", e);
+            }
+        });
+        Self {
+            rx,
+            _handle,
+            is_running,
+        }
+    }
+}
+
+impl Default for KeyboardGamepad {
+    fn default() -> Self {
+        Self::new_from_config(KeyboardGamepadConfig::default())
+    }
+}
+
+#[async_trait]
+impl Gamepad for KeyboardGamepad {
+    async fn next_event(&self) -> GamepadEvent {
+        match self.rx.recv() {
+            Ok(e) => e,
+            Err(e) => {
+                error!("recv error: {:?}", e);
+                GamepadEvent::Unknown
+            }
+        }
+    }
+    fn stop(&self) {
+        self.is_running.store(false, Ordering::Relaxed);
+        // Restore the terminal without waiting for the thread, as the process may exit soon.
+        if let Err(e) = crossterm::terminal::disable_raw_mode() {
+            error!("Failed to disable raw mode: {}", e);
+        }
+    }
+}
+
+impl Drop for KeyboardGamepad {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_axis(events: &[GamepadEvent], axis: Axis, value: f64) {
+        assert!(
+            events.iter().any(|e| matches!(
+                e,
+                GamepadEvent::AxisChanged(a, v) if *a == axis && (v - value).abs() < 1e-6
+            )),
+            "{:?}",
+            events
+        );
+    }
+
+    #[test]
+    fn test_button() {
+        let mut state = KeyboardState::new(KeyboardGamepadConfig::default());
+        let start = Instant::now();
+        let events = state.on_key("f", start);
+        assert!(matches!(
+            events[..],
+            [GamepadEvent::ButtonPressed(Button::South)]
+        ));
+        // Repeated keys are ignored.
+        assert!(state
+            .on_key("f", start + Duration::from_millis(500))
+            .is_empty());
+        assert!(state.update(start + Duration::from_millis(1000)).is_empty());
+        let events = state.update(start + Duration::from_millis(1300));
+        assert!(matches!(
+            events[..],
+            [GamepadEvent::ButtonReleased(Button::South)]
+        ));
+    }
+
+    #[test]
+    fn test_toggle_button() {
+        let mut state = KeyboardState::new(KeyboardGamepadConfig::default());
+        let start = Instant::now();
+        assert!(matches!(
+            state.on_key("space", start)[..],
+            [GamepadEvent::ButtonPressed(Button::RightTrigger2)]
+        ));
+        // Toggled buttons are not released by the timeout.
+        assert!(state.update(start + Duration::from_secs(10)).is_empty());
+        assert!(matches!(
+            state.on_key("space", start + Duration::from_secs(10))[..],
+            [GamepadEvent::ButtonReleased(Button::RightTrigger2)]
+        ));
+    }
+
+    #[test]
+    fn test_axis_ramp() {
+        let mut state = KeyboardState::new(KeyboardGamepadConfig::default());
+        let start = Instant::now();
+        state.update(start);
+        assert!(state.on_key("w", start).is_empty());
+        let events = state.update(start + Duration::from_millis(250));
+        assert_axis(&events, Axis::LeftStickY, 0.5);
+        state.on_key("w", start + Duration::from_millis(500));
+        let events = state.update(start + Duration::from_millis(1000));
+        assert_axis(&events, Axis::LeftStickY, 1.0);
+        // Key released, ramps down to zero.
+        let events = state.update(start + Duration::from_millis(1250));
+        assert_axis(&events, Axis::LeftStickY, 0.5);
+        let events = state.update(start + Duration::from_millis(2000));
+        assert_axis(&events, Axis::LeftStickY, 0.0);
+        assert!(state.update(start + Duration::from_millis(3000)).is_empty());
+    }
+
+    #[test]
+    fn test_release_all() {
+        let mut state = KeyboardState::new(KeyboardGamepadConfig::default());
+        let start = Instant::now();
+        state.update(start);
+        state.on_key("space", start);
+        state.on_key("a", start);
+        state.update(start + Duration::from_millis(100));
+        let events = state.release_all();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, GamepadEvent::ButtonReleased(Button::RightTrigger2))));
+        assert_axis(&events, Axis::LeftStickX, 0.0);
+        assert!(state.update(start + Duration::from_millis(200)).is_empty());
+    }
+
+    #[test]
+    fn test_config() {
+        let config: KeyboardGamepadConfig = toml::from_str(
+            r#"
+toggle_buttons = []
+[button_map]
+x = "South"
+[axis_map]
+w = { axis = "RightStickY", value = 0.5 }
+"#,
+        )
+        .unwrap();
+        let mut state = KeyboardState::new(config);
+        let start = Instant::now();
+        assert!(state.on_key("f", start).is_empty());
+        assert!(matches!(
+            state.on_key("x", start)[..],
+            [GamepadEvent::ButtonPressed(Button::South)]
+        ));
+        state.update(start);
+        state.on_key("w", start);
+        let events = state.update(start + Duration::from_millis(500));
+        assert_axis(&events, Axis::RightStickY, 0.5);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::message::*;
+use arci::{
+    async_trait, BaseVelocity, BatteryState, ControllerMode, Error, Fault, JointTrajectoryClient,
+    Localization, MoveBase, Navigation, NavigationFeedback, NavigationStatus, RobotStatus, Speaker,
+    TrajectoryPoint, Waypoint,
+};
+use nalgebra as na;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tracing::error;
+use url::Url;
+
+fn connection_error(e: impl std::fmt::Display) -> Error {
+    Error::Connection {
+        message: format!("{}", e),
+    }
+}
+
+fn into_result<T>(result: RemoteResult<T>) -> Result<T, Error> {
+    result.map_err(|message| anyhow::anyhow!("remote: {}", message).into())
+}
+
+fn join(base_url: &Url, path: &str) -> Result<Url, Error> {
+    base_url.join(path).map_err(connection_error)
+}
+
+fn get<T: DeserializeOwned>(url: Url) -> Result<T, Error> {
+    ureq::get(url.as_str())
+        .call()
+        .map_err(connection_error)?
+        .into_json::<RemoteResult<T>>()
+        .map_err(connection_error)
+        .and_then(into_result)
+}
+
+fn post<B: Serialize, T: DeserializeOwned>(url: Url, body: &B) -> Result<T, Error> {
+    ureq::post(url.as_str())
+        .send_json(serde_json::to_value(body).map_err(|e| anyhow::anyhow!(e))?)
+        .map_err(connection_error)?
+        .into_json::<RemoteResult<T>>()
+        .map_err(connection_error)
+        .and_then(into_result)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemoteRobotClientConfig {
+    /// URL of `RemoteRobotServer`, e.g. `http://192.168.0.10:8888`.
+    pub url: String,
+}
+
+/// JointTrajectoryClient exposed by `RemoteRobotServer`.
+pub struct RemoteJointTrajectoryClient {
+    base_url: Url,
+    joint_names: Vec<String>,
+}
+
+impl RemoteJointTrajectoryClient {
+    pub fn try_new(base_url: &Url, name: &str) -> Result<Self, Error> {
+        let base_url = join(base_url, &format!("joint_trajectory_clients/{}/", name))?;
+        let joint_names = get(join(&base_url, "joint_names")?)?;
+        Ok(Self {
+            base_url,
+            joint_names,
+        })
+    }
+}
+
+#[async_trait]
+impl JointTrajectoryClient for RemoteJointTrajectoryClient {
+    fn joint_names(&self) -> &[String] {
+        &self.joint_names
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        get(join(&self.base_url, "current_joint_positions")?)
+    }
+
+    async fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: Duration,
+    ) -> Result<(), Error> {
+        post(
+            join(&self.base_url, "send_joint_positions")?,
+            &SendJointPositionsRequest {
+                positions,
+                duration_sec: duration.as_secs_f64(),
+            },
+        )
+    }
+
+    async fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<(), Error> {
+        post(
+            join(&self.base_url, "send_joint_trajectory")?,
+            &SendJointTrajectoryRequest {
+                trajectory: trajectory.into_iter().map(Into::into).collect(),
+            },
+        )
+    }
+}
+
+/// Speaker exposed by `RemoteRobotServer`.
+pub struct RemoteSpeaker {
+    url: Url,
+}
+
+impl RemoteSpeaker {
+    pub fn try_new(base_url: &Url, name: &str) -> Result<Self, Error> {
+        Ok(Self {
+            url: join(base_url, &format!("speakers/{}/speak", name))?,
+        })
+    }
+}
+
+impl Speaker for RemoteSpeaker {
+    fn speak(&self, message: &str) {
+        if let Err(e) = post::<_, ()>(
+            self.url.clone(),
+            &SpeakRequest {
+                message: message.to_owned(),
+            },
+        ) {
+            error!("Failed to speak {:?} on the remote server: {}", message, e);
+        }
+    }
+}
+
+/// Client of `RemoteRobotServer`.
+///
+/// This implements the traits of the base (`Localization`, `MoveBase`, `Navigation`
+/// and `RobotStatus`) and creates the joint trajectory clients and the speakers of
+/// the remote robot.
+pub struct RemoteRobotClient {
+    base_url: Url,
+}
+
+impl RemoteRobotClient {
+    pub fn new(mut base_url: Url) -> Self {
+        // Url::join replaces the last segment if the path does not end with '/'.
+        if !base_url.path().ends_with('/') {
+            let path = format!("{}/", base_url.path());
+            base_url.set_path(&path);
+        }
+        Self { base_url }
+    }
+
+    pub fn new_from_config(config: RemoteRobotClientConfig) -> Result<Self, Error> {
+        Ok(Self::new(
+            Url::parse(&config.url).map_err(connection_error)?,
+        ))
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Returns the components provided by the remote server.
+    pub fn info(&self) -> Result<RemoteRobotInfo, Error> {
+        ureq::get(join(&self.base_url, "info")?.as_str())
+            .call()
+            .map_err(connection_error)?
+            .into_json()
+            .map_err(connection_error)
+    }
+
+    pub fn create_joint_trajectory_clients(
+        &self,
+        info: &RemoteRobotInfo,
+    ) -> Result<HashMap<String, Arc<dyn JointTrajectoryClient>>, Error> {
+        let mut clients = HashMap::new();
+        for name in &info.joint_trajectory_clients {
+            let client = RemoteJointTrajectoryClient::try_new(&self.base_url, name)?;
+            clients.insert(
+                name.to_owned(),
+                Arc::new(client) as Arc<dyn JointTrajectoryClient>,
+            );
+        }
+        Ok(clients)
+    }
+
+    pub fn create_speakers(
+        &self,
+        info: &RemoteRobotInfo,
+    ) -> Result<HashMap<String, Arc<dyn Speaker>>, Error> {
+        let mut speakers = HashMap::new();
+        for name in &info.speakers {
+            let speaker = RemoteSpeaker::try_new(&self.base_url, name)?;
+            speakers.insert(name.to_owned(), Arc::new(speaker) as Arc<dyn Speaker>);
+        }
+        Ok(speakers)
+    }
+}
+
+impl Localization for RemoteRobotClient {
+    fn current_pose(&self, frame_id: &str) -> Result<na::Isometry2<f64>, Error> {
+        let mut url = join(&self.base_url, "localization/current_pose")?;
+        url.query_pairs_mut().append_pair("frame_id", frame_id);
+        Ok(get::<Pose2D>(url)?.into())
+    }
+}
+
+impl MoveBase for RemoteRobotClient {
+    fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), Error> {
+        post(
+            join(&self.base_url, "move_base/send_velocity")?,
+            &Velocity::from(*velocity),
+        )
+    }
+
+    fn current_velocity(&self) -> Result<BaseVelocity, Error> {
+        Ok(get::<Velocity>(join(&self.base_url, "move_base/current_velocity")?)?.into())
+    }
+}
+
+#[async_trait]
+impl Navigation for RemoteRobotClient {
+    async fn move_to(
+        &self,
+        goal: na::Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        post(
+            join(&self.base_url, "navigation/move_to")?,
+            &MoveToRequest {
+                goal: goal.into(),
+                frame_id: frame_id.to_owned(),
+                timeout_sec: timeout.as_secs_f64(),
+            },
+        )
+    }
+
+    fn cancel(&self) -> Result<(), Error> {
+        post(join(&self.base_url, "navigation/cancel")?, &())
+    }
+
+    fn status(&self) -> Result<NavigationStatus, Error> {
+        get(join(&self.base_url, "navigation/status")?)
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, Error> {
+        Ok(
+            get::<Option<NavigationFeedbackMessage>>(join(&self.base_url, "navigation/feedback")?)?
+                .map(Into::into),
+        )
+    }
+
+    fn plan_path(
+        &self,
+        goal: na::Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Vec<na::Isometry2<f64>>, Error> {
+        let path: Vec<Pose2D> = post(
+            join(&self.base_url, "navigation/plan_path")?,
+            &PlanPathRequest {
+                goal: goal.into(),
+                frame_id: frame_id.to_owned(),
+            },
+        )?;
+        Ok(path.into_iter().map(Into::into).collect())
+    }
+
+    async fn follow_waypoints(
+        &self,
+        waypoints: &[Waypoint],
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        post(
+            join(&self.base_url, "navigation/follow_waypoints")?,
+            &FollowWaypointsRequest {
+                waypoints: waypoints.iter().map(Into::into).collect(),
+                frame_id: frame_id.to_owned(),
+                timeout_sec: timeout.as_secs_f64(),
+            },
+        )
+    }
+}
+
+impl RobotStatus for RemoteRobotClient {
+    fn battery_state(&self) -> Result<BatteryState, Error> {
+        get(join(&self.base_url, "robot_status/battery_state")?)
+    }
+
+    fn is_emergency_stopped(&self) -> Result<bool, Error> {
+        get(join(&self.base_url, "robot_status/is_emergency_stopped")?)
+    }
+
+    fn faults(&self) -> Result<Vec<Fault>, Error> {
+        get(join(&self.base_url, "robot_status/faults")?)
+    }
+
+    fn controller_mode(&self) -> Result<ControllerMode, Error> {
+        get(join(&self.base_url, "robot_status/controller_mode")?)
+    }
+}
@@ -0,0 +1,12 @@
+//! arci implementation to use the clients of a robot from another process or computer.
+//!
+//! `RemoteRobotServer` exposes arci clients over JSON-over-HTTP, and
+//! `RemoteRobotClient` and friends implement the arci traits using the server.
+
+mod client;
+mod message;
+mod server;
+
+pub use client::*;
+pub use message::RemoteRobotInfo;
+pub use server::*;
@@ -0,0 +1,215 @@
+//! Messages exchanged between `RemoteRobotServer` and the remote clients as JSON.
+
+use arci::{BaseVelocity, NavigationFeedback, TrajectoryPoint, Waypoint, WaypointTolerance};
+use nalgebra as na;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Every response of the server is `Ok(T)` or `Err(message)`.
+pub(crate) type RemoteResult<T> = Result<T, String>;
+
+/// Components provided by the remote server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RemoteRobotInfo {
+    pub joint_trajectory_clients: Vec<String>,
+    pub speakers: Vec<String>,
+    pub has_localization: bool,
+    pub has_move_base: bool,
+    pub has_navigation: bool,
+    pub has_robot_status: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Pose2D {
+    pub x: f64,
+    pub y: f64,
+    pub theta: f64,
+}
+
+impl From<na::Isometry2<f64>> for Pose2D {
+    fn from(pose: na::Isometry2<f64>) -> Self {
+        Self {
+            x: pose.translation.x,
+            y: pose.translation.y,
+            theta: pose.rotation.angle(),
+        }
+    }
+}
+
+impl From<Pose2D> for na::Isometry2<f64> {
+    fn from(pose: Pose2D) -> Self {
+        na::Isometry2::new(na::Vector2::new(pose.x, pose.y), pose.theta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Velocity {
+    pub x: f64,
+    pub y: f64,
+    pub theta: f64,
+}
+
+impl From<BaseVelocity> for Velocity {
+    fn from(velocity: BaseVelocity) -> Self {
+        Self {
+            x: velocity.x,
+            y: velocity.y,
+            theta: velocity.theta,
+        }
+    }
+}
+
+impl From<Velocity> for BaseVelocity {
+    fn from(velocity: Velocity) -> Self {
+        BaseVelocity::new(velocity.x, velocity.y, velocity.theta)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SendJointPositionsRequest {
+    pub positions: Vec<f64>,
+    pub duration_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct TrajectoryPointMessage {
+    pub positions: Vec<f64>,
+    pub velocities: Option<Vec<f64>>,
+    pub time_from_start_sec: f64,
+}
+
+impl From<TrajectoryPoint> for TrajectoryPointMessage {
+    fn from(point: TrajectoryPoint) -> Self {
+        Self {
+            positions: point.positions,
+            velocities: point.velocities,
+            time_from_start_sec: point.time_from_start.as_secs_f64(),
+        }
+    }
+}
+
+impl From<TrajectoryPointMessage> for TrajectoryPoint {
+    fn from(point: TrajectoryPointMessage) -> Self {
+        Self {
+            positions: point.positions,
+            velocities: point.velocities,
+            time_from_start: Duration::from_secs_f64(point.time_from_start_sec),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SendJointTrajectoryRequest {
+    pub trajectory: Vec<TrajectoryPointMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SpeakRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MoveToRequest {
+    pub goal: Pose2D,
+    pub frame_id: String,
+    pub timeout_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct WaypointMessage {
+    pub pose: Pose2D,
+    pub tolerance: Option<WaypointTolerance>,
+}
+
+impl From<&Waypoint> for WaypointMessage {
+    fn from(waypoint: &Waypoint) -> Self {
+        Self {
+            pose: waypoint.pose.into(),
+            tolerance: waypoint.tolerance,
+        }
+    }
+}
+
+impl From<WaypointMessage> for Waypoint {
+    fn from(waypoint: WaypointMessage) -> Self {
+        Self {
+            pose: waypoint.pose.into(),
+            tolerance: waypoint.tolerance,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct FollowWaypointsRequest {
+    pub waypoints: Vec<WaypointMessage>,
+    pub frame_id: String,
+    pub timeout_sec: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct PlanPathRequest {
+    pub goal: Pose2D,
+    pub frame_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct NavigationFeedbackMessage {
+    pub current_pose: Pose2D,
+    pub distance_remaining: f64,
+}
+
+impl From<NavigationFeedback> for NavigationFeedbackMessage {
+    fn from(feedback: NavigationFeedback) -> Self {
+        Self {
+            current_pose: feedback.current_pose.into(),
+            distance_remaining: feedback.distance_remaining,
+        }
+    }
+}
+
+impl From<NavigationFeedbackMessage> for NavigationFeedback {
+    fn from(feedback: NavigationFeedbackMessage) -> Self {
+        Self {
+            current_pose: feedback.current_pose.into(),
+            distance_remaining: feedback.distance_remaining,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_pose_2d() {
+        let pose = na::Isometry2::new(na::Vector2::new(1.0, -2.0), 0.5);
+        let json = serde_json::to_string(&Pose2D::from(pose)).unwrap();
+        let converted: na::Isometry2<f64> = serde_json::from_str::<Pose2D>(&json).unwrap().into();
+        assert_approx_eq!(converted.translation.x, 1.0);
+        assert_approx_eq!(converted.translation.y, -2.0);
+        assert_approx_eq!(converted.rotation.angle(), 0.5);
+    }
+
+    #[test]
+    fn test_trajectory_point_message() {
+        let mut point = TrajectoryPoint::new(vec![1.0, -1.0], Duration::from_millis(1500));
+        point.velocities = Some(vec![0.5, 0.0]);
+        let json = serde_json::to_string(&TrajectoryPointMessage::from(point)).unwrap();
+        let converted: TrajectoryPoint = serde_json::from_str::<TrajectoryPointMessage>(&json)
+            .unwrap()
+            .into();
+        assert_eq!(converted.positions, vec![1.0, -1.0]);
+        assert_eq!(converted.velocities, Some(vec![0.5, 0.0]));
+        assert_eq!(converted.time_from_start, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn test_remote_result() {
+        let ok: RemoteResult<Vec<f64>> = Ok(vec![1.0]);
+        let json = serde_json::to_string(&ok).unwrap();
+        assert_eq!(json, r#"{"Ok":[1.0]}"#);
+        let err: RemoteResult<Vec<f64>> = serde_json::from_str(r#"{"Err":"failed"}"#).unwrap();
+        assert_eq!(err, Err("failed".to_owned()));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::message::*;
+use arci::{
+    Error, JointTrajectoryClient, Localization, MoveBase, Navigation, RobotStatus, Speaker,
+};
+use rouille::{router, try_or_400, Request, Response};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::{SocketAddr, ToSocketAddrs},
+    sync::{mpsc::Sender, Arc},
+    thread::JoinHandle,
+    time::Duration,
+};
+use tracing::{debug, info};
+
+fn json_response<T: Serialize>(result: Result<T, Error>) -> Response {
+    let result: RemoteResult<T> = result.map_err(|e| e.to_string());
+    Response::json(&result)
+}
+
+fn not_available(name: &str) -> Error {
+    Error::Uninitialized {
+        message: format!("{} is not available on the remote server", name),
+    }
+}
+
+/// Server which exposes arci clients over JSON-over-HTTP.
+///
+/// The clients are used from another process by the clients of this crate, e.g.
+/// `RemoteJointTrajectoryClient` and `RemoteRobotClient`.
+pub struct RemoteRobotServer {
+    joint_trajectory_clients: HashMap<String, Arc<dyn JointTrajectoryClient>>,
+    speakers: HashMap<String, Arc<dyn Speaker>>,
+    localization: Option<Arc<dyn Localization>>,
+    move_base: Option<Arc<dyn MoveBase>>,
+    navigation: Option<Arc<dyn Navigation>>,
+    robot_status: Option<Arc<dyn RobotStatus>>,
+    runtime: Option<tokio::runtime::Handle>,
+}
+
+impl RemoteRobotServer {
+    /// Creates a new server.
+    ///
+    /// If this is called in the context of a tokio runtime, the async methods of
+    /// the clients are run in that context.
+    pub fn new(
+        joint_trajectory_clients: HashMap<String, Arc<dyn JointTrajectoryClient>>,
+        speakers: HashMap<String, Arc<dyn Speaker>>,
+        localization: Option<Arc<dyn Localization>>,
+        move_base: Option<Arc<dyn MoveBase>>,
+        navigation: Option<Arc<dyn Navigation>>,
+        robot_status: Option<Arc<dyn RobotStatus>>,
+    ) -> Self {
+        Self {
+            joint_trajectory_clients,
+            speakers,
+            localization,
+            move_base,
+            navigation,
+            robot_status,
+            runtime: tokio::runtime::Handle::try_current().ok(),
+        }
+    }
+
+    pub fn info(&self) -> RemoteRobotInfo {
+        let mut joint_trajectory_clients: Vec<_> =
+            self.joint_trajectory_clients.keys().cloned().collect();
+        joint_trajectory_clients.sort();
+        let mut speakers: Vec<_> = self.speakers.keys().cloned().collect();
+        speakers.sort();
+        RemoteRobotInfo {
+            joint_trajectory_clients,
+            speakers,
+            has_localization: self.localization.is_some(),
+            has_move_base: self.move_base.is_some(),
+            has_navigation: self.navigation.is_some(),
+            has_robot_status: self.robot_status.is_some(),
+        }
+    }
+
+    /// Starts the server in a background thread.
+    ///
+    /// The server stops when the returned handle is dropped.
+    pub fn spawn<A: ToSocketAddrs>(self, addr: A) -> Result<RemoteRobotServerHandle, Error> {
+        let server =
+            rouille::Server::new(addr, move |request| self.handle(request)).map_err(|e| {
+                Error::Connection {
+                    message: format!("Failed to start the remote robot server: {}", e),
+                }
+            })?;
+        let addr = server.server_addr();
+        info!("Remote robot server is listening on {}", addr);
+        let (thread, stop) = server.stoppable();
+        Ok(RemoteRobotServerHandle {
+            addr,
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _guard = self.runtime.as_ref().map(|runtime| runtime.enter());
+        futures::executor::block_on(future)
+    }
+
+    fn joint_trajectory_client(
+        &self,
+        name: &str,
+    ) -> Result<&Arc<dyn JointTrajectoryClient>, Error> {
+        self.joint_trajectory_clients
+            .get(name)
+            .ok_or_else(|| not_available(&format!("joint_trajectory_client {}", name)))
+    }
+
+    fn speaker(&self, name: &str) -> Result<&Arc<dyn Speaker>, Error> {
+        self.speakers
+            .get(name)
+            .ok_or_else(|| not_available(&format!("speaker {}", name)))
+    }
+
+    fn localization(&self) -> Result<&Arc<dyn Localization>, Error> {
+        self.localization
+            .as_ref()
+            .ok_or_else(|| not_available("localization"))
+    }
+
+    fn move_base(&self) -> Result<&Arc<dyn MoveBase>, Error> {
+        self.move_base
+            .as_ref()
+            .ok_or_else(|| not_available("move_base"))
+    }
+
+    fn navigation(&self) -> Result<&Arc<dyn Navigation>, Error> {
+        self.navigation
+            .as_ref()
+            .ok_or_else(|| not_available("navigation"))
+    }
+
+    fn robot_status(&self) -> Result<&Arc<dyn RobotStatus>, Error> {
+        self.robot_status
+            .as_ref()
+            .ok_or_else(|| not_available("robot_status"))
+    }
+
+    fn handle(&self, request: &Request) -> Response {
+        debug!("{} {}", request.method(), request.url());
+        router!(request,
+            (GET) (/info) => {
+                Response::json(&self.info())
+            },
+            (GET) (/joint_trajectory_clients/{name: String}/joint_names) => {
+                json_response(
+                    self.joint_trajectory_client(&name)
+                        .map(|client| client.joint_names().to_vec()),
+                )
+            },
+            (GET) (/joint_trajectory_clients/{name: String}/current_joint_positions) => {
+                json_response(
+                    self.joint_trajectory_client(&name)
+                        .and_then(|client| client.current_joint_positions()),
+                )
+            },
+            (POST) (/joint_trajectory_clients/{name: String}/send_joint_positions) => {
+                let req: SendJointPositionsRequest = try_or_400!(rouille::input::json_input(request));
+                json_response(self.joint_trajectory_client(&name).and_then(|client| {
+                    self.block_on(client.send_joint_positions(
+                        req.positions,
+                        Duration::from_secs_f64(req.duration_sec),
+                    ))
+                }))
+            },
+            (POST) (/joint_trajectory_clients/{name: String}/send_joint_trajectory) => {
+                let req: SendJointTrajectoryRequest = try_or_400!(rouille::input::json_input(request));
+                json_response(self.joint_trajectory_client(&name).and_then(|client| {
+                    self.block_on(client.send_joint_trajectory(
+                        req.trajectory.into_iter().map(Into::into).collect(),
+                    ))
+                }))
+            },
+            (POST) (/speakers/{name: String}/speak) => {
+                let req: SpeakRequest = try_or_400!(rouille::input::json_input(request));
+                json_response(self.speaker(&name).map(|speaker| speaker.speak(&req.message)))
+            },
+            (GET) (/localization/current_pose) => {
+                let frame_id = request.get_param("frame_id").unwrap_or_default();
+                json_response(
+                    self.localization()
+                        .and_then(|localization| localization.current_pose(&frame_id))
+                        .map(Pose2D::from),
+                )
+            },
+            (POST) (/move_base/send_velocity) => {
+                let req: Velocity = try_or_400!(rouille::input::json_input(request));
+                json_response(
+                    self.move_base()
+                        .and_then(|move_base| move_base.send_velocity(&req.into())),
+                )
+            },
+            (GET) (/move_base/current_velocity) => {
+                json_response(
+                    self.move_base()
+                        .and_then(|move_base| move_base.current_velocity())
+                        .map(Velocity::from),
+                )
+            },
+            (POST) (/navigation/move_to) => {
+                let req: MoveToRequest = try_or_400!(rouille::input::json_input(request));
+                json_response(self.navigation().and_then(|navigation| {
+                    self.block_on(navigation.move_to(
+                        req.goal.into(),
+                        &req.frame_id,
+                        Duration::from_secs_f64(req.timeout_sec),
+                    ))
+                }))
+            },
+            (POST) (/navigation/follow_waypoints) => {
+                let FollowWaypointsRequest {
+                    waypoints,
+                    frame_id,
+                    timeout_sec,
+                } = try_or_400!(rouille::input::json_input(request));
+                let waypoints: Vec<_> = waypoints.into_iter().map(Into::into).collect();
+                json_response(self.navigation().and_then(|navigation| {
+                    self.block_on(navigation.follow_waypoints(
+                        &waypoints,
+                        &frame_id,
+                        Duration::from_secs_f64(timeout_sec),
+                    ))
+                }))
+            },
+            (POST) (/navigation/cancel) => {
+                json_response(self.navigation().and_then(|navigation| navigation.cancel()))
+            },
+            (GET) (/navigation/status) => {
+                json_response(self.navigation().and_then(|navigation| navigation.status()))
+            },
+            (GET) (/navigation/feedback) => {
+                json_response(
+                    self.navigation()
+                        .and_then(|navigation| navigation.feedback())
+                        .map(|feedback| feedback.map(NavigationFeedbackMessage::from)),
+                )
+            },
+            (POST) (/navigation/plan_path) => {
+                let req: PlanPathRequest = try_or_400!(rouille::input::json_input(request));
+                json_response(
+                    self.navigation()
+                        .and_then(|navigation| navigation.plan_path(req.goal.into(), &req.frame_id))
+                        .map(|path| path.into_iter().map(Pose2D::from).collect::<Vec<_>>()),
+                )
+            },
+            (GET) (/robot_status/battery_state) => {
+                json_response(self.robot_status().and_then(|status| status.battery_state()))
+            },
+            (GET) (/robot_status/is_emergency_stopped) => {
+                json_response(self.robot_status().and_then(|status| status.is_emergency_stopped()))
+            },
+            (GET) (/robot_status/faults) => {
+                json_response(self.robot_status().and_then(|status| status.faults()))
+            },
+            (GET) (/robot_status/controller_mode) => {
+                json_response(self.robot_status().and_then(|status| status.controller_mode()))
+            },
+            _ => Response::empty_404()
+        )
+    }
+}
+
+/// Handle of the server started by `RemoteRobotServer::spawn`.
+pub struct RemoteRobotServerHandle {
+    addr: SocketAddr,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RemoteRobotServerHandle {
+    /// The address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Stops the server and waits for the server thread to finish.
+    pub fn stop(self) {
+        drop(self)
+    }
+}
+
+impl Drop for RemoteRobotServerHandle {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use arci::{
+    BaseVelocity, DummyJointTrajectoryClient, DummyLocalization, DummyMoveBase, DummyNavigation,
+    DummyRobotStatus, Isometry2, JointTrajectoryClient, Localization, MoveBase, Navigation,
+    NavigationStatus, RobotStatus, TrajectoryPoint,
+};
+use arci_remote::{RemoteRobotClient, RemoteRobotInfo, RemoteRobotServer};
+use assert_approx_eq::assert_approx_eq;
+use nalgebra::Vector2;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use url::Url;
+
+fn url(server: &arci_remote::RemoteRobotServerHandle) -> Url {
+    Url::parse(&format!("http://{}", server.addr())).unwrap()
+}
+
+#[test]
+fn test_joint_trajectory_client() {
+    let dummy = Arc::new(DummyJointTrajectoryClient::new(vec![
+        "j1".to_owned(),
+        "j2".to_owned(),
+    ]));
+    let mut clients = HashMap::new();
+    clients.insert(
+        "arm".to_owned(),
+        dummy.clone() as Arc<dyn JointTrajectoryClient>,
+    );
+    let server = RemoteRobotServer::new(clients, HashMap::new(), None, None, None, None)
+        .spawn("127.0.0.1:0")
+        .unwrap();
+
+    let remote = RemoteRobotClient::new(url(&server));
+    let info = remote.info().unwrap();
+    assert_eq!(
+        info,
+        RemoteRobotInfo {
+            joint_trajectory_clients: vec!["arm".to_owned()],
+            ..Default::default()
+        }
+    );
+    let clients = remote.create_joint_trajectory_clients(&info).unwrap();
+    let client = &clients["arm"];
+    assert_eq!(client.joint_names(), &["j1".to_owned(), "j2".to_owned()]);
+
+    tokio_test::block_on(client.send_joint_positions(vec![1.0, -1.0], Duration::from_secs(1)))
+        .unwrap();
+    let positions = client.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 1.0);
+    assert_approx_eq!(positions[1], -1.0);
+
+    tokio_test::block_on(client.send_joint_trajectory(vec![
+        TrajectoryPoint::new(vec![0.5, 0.5], Duration::from_secs(1)),
+        TrajectoryPoint::new(vec![2.0, 3.0], Duration::from_secs(2)),
+    ]))
+    .unwrap();
+    let positions = client.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 2.0);
+    assert_approx_eq!(positions[1], 3.0);
+    let last_trajectory = dummy.last_trajectory.lock().unwrap();
+    assert_eq!(last_trajectory.len(), 2);
+    assert_eq!(last_trajectory[1].time_from_start, Duration::from_secs(2));
+}
+
+#[test]
+fn test_base() {
+    let server = RemoteRobotServer::new(
+        HashMap::new(),
+        HashMap::new(),
+        Some(Arc::new(DummyLocalization::new())),
+        Some(Arc::new(DummyMoveBase::new())),
+        Some(Arc::new(DummyNavigation::new())),
+        Some(Arc::new(DummyRobotStatus::new())),
+    )
+    .spawn("127.0.0.1:0")
+    .unwrap();
+
+    let remote = RemoteRobotClient::new(url(&server));
+    let info = remote.info().unwrap();
+    assert!(info.has_localization);
+    assert!(info.has_move_base);
+    assert!(info.has_navigation);
+    assert!(info.has_robot_status);
+
+    remote
+        .send_velocity(&BaseVelocity::new(0.1, 0.2, -0.3))
+        .unwrap();
+    let velocity = remote.current_velocity().unwrap();
+    assert_approx_eq!(velocity.x, 0.1);
+    assert_approx_eq!(velocity.y, 0.2);
+    assert_approx_eq!(velocity.theta, -0.3);
+
+    let pose = remote.current_pose("map").unwrap();
+    assert_approx_eq!(pose.translation.x, 0.0);
+
+    assert_eq!(remote.status().unwrap(), NavigationStatus::Idle);
+    tokio_test::block_on(remote.move_to(
+        Isometry2::new(Vector2::new(1.0, 2.0), 0.5),
+        "map",
+        Duration::from_secs(1),
+    ))
+    .unwrap();
+    assert_eq!(remote.status().unwrap(), NavigationStatus::Succeeded);
+    assert!(remote.feedback().unwrap().is_none());
+    let path = remote
+        .plan_path(Isometry2::new(Vector2::new(3.0, 2.0), 0.0), "map")
+        .unwrap();
+    assert_eq!(path.len(), 2);
+    assert_approx_eq!(path[0].translation.x, 1.0);
+    assert_approx_eq!(path[0].rotation.angle(), 0.5);
+    assert_approx_eq!(path[1].translation.x, 3.0);
+    remote.cancel().unwrap();
+
+    assert!(!remote.is_emergency_stopped().unwrap());
+    assert!(remote.faults().unwrap().is_empty());
+    remote.battery_state().unwrap();
+    remote.controller_mode().unwrap();
+}
+
+#[test]
+fn test_not_available() {
+    let server = RemoteRobotServer::new(HashMap::new(), HashMap::new(), None, None, None, None)
+        .spawn("127.0.0.1:0")
+        .unwrap();
+    let remote = RemoteRobotClient::new(url(&server));
+    let err = remote.current_velocity().unwrap_err();
+    assert!(err.to_string().contains("move_base"), "{}", err);
+    assert!(remote.current_pose("map").is_err());
+    assert!(remote.status().is_err());
+    assert!(remote.faults().is_err());
+    assert!(arci_remote::RemoteJointTrajectoryClient::try_new(remote.base_url(), "arm").is_err());
+}
+
+#[test]
+fn test_connection_error() {
+    let server = RemoteRobotServer::new(HashMap::new(), HashMap::new(), None, None, None, None)
+        .spawn("127.0.0.1:0")
+        .unwrap();
+    let url = url(&server);
+    server.stop();
+    let remote = RemoteRobotClient::new(url);
+    assert!(matches!(remote.info(), Err(arci::Error::Connection { .. })));
+}
@@ -7,6 +7,7 @@ mod ros_control_client;
 mod ros_localization_client;
 mod ros_nav_client;
 mod ros_robot_client;
+mod ros_robot_status_client;
 mod ros_speak_client;
 pub mod ros_transform_resolver;
 pub mod rosrust_utils;
@@ -19,6 +20,7 @@ pub use ros_control_client::*;
 pub use ros_localization_client::*;
 pub use ros_nav_client::*;
 pub use ros_robot_client::*;
+pub use ros_robot_status_client::*;
 pub use ros_speak_client::*;
 pub use ros_transform_resolver::*;
 pub use rosrust::{init, is_ok, rate};
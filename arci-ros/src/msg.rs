@@ -1,17 +1,23 @@
 ros_nalgebra::rosmsg_include!(
     actionlib_msgs / GoalID,
     actionlib_msgs / GoalStatus,
+    actionlib_msgs / GoalStatusArray,
     control_msgs / JointTrajectoryControllerState,
     control_msgs / FollowJointTrajectoryActionGoal,
     control_msgs / FollowJointTrajectoryActionResult,
     control_msgs / FollowJointTrajectoryGoal,
     control_msgs / FollowJointTrajectoryResult,
+    diagnostic_msgs / DiagnosticArray,
+    diagnostic_msgs / DiagnosticStatus,
     geometry_msgs / Pose,
     geometry_msgs / PoseStamped,
     geometry_msgs / PoseWithCovarianceStamped,
     geometry_msgs / Twist,
+    move_base_msgs / MoveBaseActionFeedback,
     move_base_msgs / MoveBaseActionGoal,
     move_base_msgs / MoveBaseActionResult,
+    nav_msgs / GetPlan,
+    sensor_msgs / BatteryState,
     sensor_msgs / JointState,
     sensor_msgs / Joy,
     std_msgs / Header,
@@ -1,8 +1,9 @@
 use crate::msg;
+use crate::rosrust_utils::*;
 use arci::*;
 use nalgebra as na;
 use serde::{Deserialize, Serialize};
-use std::time;
+use std::{sync::Mutex, time};
 
 use crate::define_action_client_internal;
 define_action_client_internal!(SimpleActionClient, msg::move_base_msgs, MoveBase);
@@ -21,9 +22,32 @@ impl From<na::Isometry2<f64>> for msg::geometry_msgs::Pose {
     }
 }
 
+fn pose_to_isometry2(pose: msg::geometry_msgs::Pose) -> na::Isometry2<f64> {
+    let pose: na::Isometry3<f64> = pose.into();
+    na::Isometry2::new(
+        na::Vector2::new(pose.translation.vector[0], pose.translation.vector[1]),
+        pose.rotation.euler_angles().2,
+    )
+}
+
+fn goal_status_to_navigation_status(status: u8) -> NavigationStatus {
+    use msg::actionlib_msgs::GoalStatus;
+    match status {
+        GoalStatus::PENDING | GoalStatus::RECALLING => NavigationStatus::Pending,
+        GoalStatus::ACTIVE | GoalStatus::PREEMPTING => NavigationStatus::Active,
+        GoalStatus::SUCCEEDED => NavigationStatus::Succeeded,
+        GoalStatus::PREEMPTED | GoalStatus::RECALLED => NavigationStatus::Canceled,
+        _ => NavigationStatus::Aborted,
+    }
+}
+
 const NO_MOTION_UPDATE_SERVICE: &str = "request_nomotion_update";
 const MOVE_BASE_ACTION: &str = "/move_base";
+const MOVE_BASE_STATUS_TOPIC: &str = "/move_base/status";
+const MOVE_BASE_FEEDBACK_TOPIC: &str = "/move_base/feedback";
 const CLEAR_COSTMAP_SERVICE: &str = "/move_base/clear_costmaps";
+const MAKE_PLAN_SERVICE: &str = "/move_base/make_plan";
+const WAYPOINT_MONITORING_RATE: f64 = 10.0;
 
 /// Build RosNavClient interactively.
 ///
@@ -111,6 +135,13 @@ pub struct RosNavClient {
     pub clear_costmap_before_start: bool,
     action_client: SimpleActionClient,
     nomotion_update_client: Option<rosrust::Client<std_srvs::Empty>>,
+    status_subscriber: SubscriberHandler<msg::actionlib_msgs::GoalStatusArray>,
+    feedback_subscriber: SubscriberHandler<msg::move_base_msgs::MoveBaseActionFeedback>,
+    /// Goal ID and goal pose of the last sent goal.
+    current_goal: Mutex<Option<(String, na::Isometry2<f64>)>>,
+    /// Goal ID and the last terminal status of it. move_base drops finished goals from the
+    /// status topic after a while, so they are reported from here.
+    last_terminal_status: Mutex<Option<(String, NavigationStatus)>>,
 }
 
 impl RosNavClient {
@@ -131,6 +162,10 @@ impl RosNavClient {
             clear_costmap_before_start: false,
             action_client,
             nomotion_update_client,
+            status_subscriber: SubscriberHandler::new(MOVE_BASE_STATUS_TOPIC, 1),
+            feedback_subscriber: SubscriberHandler::new(MOVE_BASE_FEEDBACK_TOPIC, 1),
+            current_goal: Mutex::new(None),
+            last_terminal_status: Mutex::new(None),
         }
     }
     pub fn new_from_config(config: RosNavClientConfig) -> Self {
@@ -149,6 +184,55 @@ impl RosNavClient {
         Ok(())
     }
 
+    fn send_goal(&self, goal: na::Isometry2<f64>, frame_id: &str) -> Result<String, Error> {
+        let mut target_pose = msg::geometry_msgs::PoseStamped {
+            pose: goal.into(),
+            ..Default::default()
+        };
+        target_pose.header.frame_id = frame_id.to_owned();
+        target_pose.header.stamp = rosrust::now();
+        let goal_id = self
+            .action_client
+            .send_goal(msg::move_base_msgs::MoveBaseGoal { target_pose })
+            .map_err(|e| anyhow::anyhow!("Failed to send_goal : {}", e.to_string()))?;
+        *self.current_goal.lock().unwrap() = Some((goal_id.clone(), goal));
+        Ok(goal_id)
+    }
+
+    fn current_goal_id(&self) -> Option<String> {
+        self.current_goal
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|(goal_id, _)| goal_id.clone())
+    }
+
+    /// Wait until the robot enters the tolerance of the waypoint or the goal succeeds.
+    fn wait_until_waypoint_reached(
+        &self,
+        waypoint: &Waypoint,
+        timeout: std::time::Duration,
+    ) -> Result<(), Error> {
+        let rate = rosrust::rate(WAYPOINT_MONITORING_RATE);
+        let start_time = std::time::Instant::now();
+        while start_time.elapsed() < timeout {
+            match self.status()? {
+                NavigationStatus::Succeeded => return Ok(()),
+                status @ NavigationStatus::Aborted | status @ NavigationStatus::Canceled => {
+                    return Err(anyhow::anyhow!("Waypoint is not reached : {:?}", status).into());
+                }
+                _ => {}
+            }
+            if let Some(feedback) = self.feedback()? {
+                if waypoint.is_reached(&feedback.current_pose) {
+                    return Ok(());
+                }
+            }
+            rate.sleep();
+        }
+        Err(anyhow::anyhow!("Timeout to reach waypoint {:?}", waypoint.pose).into())
+    }
+
     fn wait_until_reach(&self, goal_id: &str, timeout: std::time::Duration) {
         match self.action_client.wait_for_result(goal_id, timeout) {
             Ok(_) => {
@@ -183,16 +267,7 @@ impl Navigation for RosNavClient {
         if self.clear_costmap_before_start {
             self.clear_costmap()?;
         }
-        let mut target_pose = msg::geometry_msgs::PoseStamped {
-            pose: goal.into(),
-            ..Default::default()
-        };
-        target_pose.header.frame_id = frame_id.to_owned();
-        target_pose.header.stamp = rosrust::now();
-        let goal_id = self
-            .action_client
-            .send_goal(msg::move_base_msgs::MoveBaseGoal { target_pose })
-            .map_err(|e| anyhow::anyhow!("Failed to send_goal_and_wait : {}", e.to_string()))?;
+        let goal_id = self.send_goal(goal, frame_id)?;
         self.wait_until_reach(&goal_id, timeout);
         Ok(())
     }
@@ -203,6 +278,110 @@ impl Navigation for RosNavClient {
             .map_err(|e| anyhow::anyhow!("Failed to cancel_all_goal : {}", e.to_string()))?;
         Ok(())
     }
+
+    fn status(&self) -> Result<NavigationStatus, Error> {
+        let goal_id = match self.current_goal_id() {
+            Some(goal_id) => goal_id,
+            None => return Ok(NavigationStatus::Idle),
+        };
+        let status = self
+            .status_subscriber
+            .get()?
+            .and_then(|status_array| {
+                status_array
+                    .status_list
+                    .into_iter()
+                    .find(|status| status.goal_id.id == goal_id)
+            })
+            .map(|status| goal_status_to_navigation_status(status.status));
+        let mut last_terminal_status = self.last_terminal_status.lock().unwrap();
+        match status {
+            Some(status) => {
+                if status.is_done() {
+                    *last_terminal_status = Some((goal_id, status));
+                }
+                Ok(status)
+            }
+            None => Ok(match &*last_terminal_status {
+                Some((id, status)) if *id == goal_id => *status,
+                _ => NavigationStatus::Pending,
+            }),
+        }
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, Error> {
+        let (goal_id, goal) = match self.current_goal.lock().unwrap().clone() {
+            Some(current_goal) => current_goal,
+            None => return Ok(None),
+        };
+        Ok(self
+            .feedback_subscriber
+            .get()?
+            .filter(|feedback| feedback.status.goal_id.id == goal_id)
+            .map(|feedback| {
+                let current_pose = pose_to_isometry2(feedback.feedback.base_position.pose);
+                NavigationFeedback {
+                    distance_remaining: (goal.translation.vector - current_pose.translation.vector)
+                        .norm(),
+                    current_pose,
+                }
+            }))
+    }
+
+    fn plan_path(
+        &self,
+        goal: na::Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Vec<na::Isometry2<f64>>, Error> {
+        rosrust::wait_for_service(MAKE_PLAN_SERVICE, Some(time::Duration::from_secs(10)))
+            .map_err(|e| anyhow::anyhow!("Failed to wait {} : {}", MAKE_PLAN_SERVICE, e))?;
+        let client = rosrust::client::<msg::nav_msgs::GetPlan>(MAKE_PLAN_SERVICE).map_err(|e| {
+            anyhow::anyhow!("Failed to create client {} : {}", MAKE_PLAN_SERVICE, e)
+        })?;
+        let mut goal_pose = msg::geometry_msgs::PoseStamped {
+            pose: goal.into(),
+            ..Default::default()
+        };
+        goal_pose.header.frame_id = frame_id.to_owned();
+        goal_pose.header.stamp = rosrust::now();
+        // move_base uses the current robot pose if the frame_id of start is empty.
+        let res = client
+            .req(&msg::nav_msgs::GetPlanReq {
+                start: Default::default(),
+                goal: goal_pose,
+                tolerance: 0.0,
+            })
+            .map_err(|e| anyhow::anyhow!("Failed to call {} : {}", MAKE_PLAN_SERVICE, e))?
+            .map_err(|e| anyhow::anyhow!("{} returns error : {}", MAKE_PLAN_SERVICE, e))?;
+        Ok(res
+            .plan
+            .poses
+            .into_iter()
+            .map(|pose| pose_to_isometry2(pose.pose))
+            .collect())
+    }
+
+    async fn follow_waypoints(
+        &self,
+        waypoints: &[Waypoint],
+        frame_id: &str,
+        timeout: std::time::Duration,
+    ) -> Result<(), Error> {
+        if self.clear_costmap_before_start {
+            self.clear_costmap()?;
+        }
+        for waypoint in waypoints {
+            let goal_id = self.send_goal(waypoint.pose, frame_id)?;
+            if waypoint.tolerance.is_some() {
+                self.wait_until_waypoint_reached(waypoint, timeout)?;
+            } else {
+                self.action_client
+                    .wait_for_result(&goal_id, timeout)
+                    .map_err(|e| anyhow::anyhow!("Waypoint is not reached : {:?}", e))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
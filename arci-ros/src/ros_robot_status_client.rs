@@ -0,0 +1,196 @@
+use crate::msg;
+use crate::rosrust_utils::*;
+use arci::*;
+use serde::{Deserialize, Serialize};
+
+use msg::diagnostic_msgs::{DiagnosticArray, DiagnosticStatus};
+use msg::sensor_msgs::BatteryState as BatteryStateMessage;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RosRobotStatusClientConfig {
+    #[serde(default = "default_battery_state_topic_name")]
+    pub battery_state_topic_name: String,
+    #[serde(default = "default_diagnostics_topic_name")]
+    pub diagnostics_topic_name: String,
+    /// Name of the DiagnosticStatus which reports the emergency stop.
+    /// The robot is regarded as emergency stopped if its level is ERROR.
+    #[serde(default = "default_emergency_stop_status_name")]
+    pub emergency_stop_status_name: String,
+    /// Name of the DiagnosticStatus which reports the controller mode.
+    #[serde(default = "default_controller_mode_status_name")]
+    pub controller_mode_status_name: String,
+    /// Key of the value which contains the controller mode in the DiagnosticStatus.
+    #[serde(default = "default_controller_mode_key")]
+    pub controller_mode_key: String,
+}
+
+fn default_battery_state_topic_name() -> String {
+    "/battery_state".to_owned()
+}
+
+fn default_diagnostics_topic_name() -> String {
+    "/diagnostics_agg".to_owned()
+}
+
+fn default_emergency_stop_status_name() -> String {
+    "emergency_stop".to_owned()
+}
+
+fn default_controller_mode_status_name() -> String {
+    "controller".to_owned()
+}
+
+fn default_controller_mode_key() -> String {
+    "mode".to_owned()
+}
+
+impl Default for RosRobotStatusClientConfig {
+    fn default() -> Self {
+        Self {
+            battery_state_topic_name: default_battery_state_topic_name(),
+            diagnostics_topic_name: default_diagnostics_topic_name(),
+            emergency_stop_status_name: default_emergency_stop_status_name(),
+            controller_mode_status_name: default_controller_mode_status_name(),
+            controller_mode_key: default_controller_mode_key(),
+        }
+    }
+}
+
+/// RobotStatus implementation using `sensor_msgs/BatteryState` and `diagnostic_msgs/DiagnosticArray`.
+pub struct RosRobotStatusClient {
+    config: RosRobotStatusClientConfig,
+    battery_state_subscriber: SubscriberHandler<BatteryStateMessage>,
+    diagnostics_subscriber: SubscriberHandler<DiagnosticArray>,
+}
+
+impl RosRobotStatusClient {
+    pub fn new(config: RosRobotStatusClientConfig) -> Self {
+        let battery_state_subscriber = SubscriberHandler::new(&config.battery_state_topic_name, 1);
+        let diagnostics_subscriber = SubscriberHandler::new(&config.diagnostics_topic_name, 1);
+        Self {
+            config,
+            battery_state_subscriber,
+            diagnostics_subscriber,
+        }
+    }
+
+    fn diagnostic_statuses(&self) -> Result<Vec<DiagnosticStatus>, Error> {
+        Ok(self
+            .diagnostics_subscriber
+            .get()?
+            .ok_or_else(|| Error::Connection {
+                message: format!(
+                    "Failed to get diagnostics from {}",
+                    self.config.diagnostics_topic_name
+                ),
+            })?
+            .status)
+    }
+
+    fn find_diagnostic_status(&self, name: &str) -> Result<Option<DiagnosticStatus>, Error> {
+        Ok(self
+            .diagnostic_statuses()?
+            .into_iter()
+            .find(|status| diagnostic_status_name_matches(&status.name, name)))
+    }
+}
+
+/// `diagnostic_aggregator` prefixes the name with the analyzer path (e.g. `/Safety/emergency_stop`).
+fn diagnostic_status_name_matches(status_name: &str, name: &str) -> bool {
+    status_name == name || status_name.rsplit('/').next() == Some(name)
+}
+
+fn f32_to_option(value: f32) -> Option<f64> {
+    if value.is_nan() {
+        None
+    } else {
+        Some(value as f64)
+    }
+}
+
+impl RobotStatus for RosRobotStatusClient {
+    fn battery_state(&self) -> Result<BatteryState, Error> {
+        let message = self
+            .battery_state_subscriber
+            .get()?
+            .ok_or_else(|| Error::Connection {
+                message: format!(
+                    "Failed to get battery state from {}",
+                    self.config.battery_state_topic_name
+                ),
+            })?;
+        Ok(BatteryState {
+            percentage: f32_to_option(message.percentage),
+            voltage: f32_to_option(message.voltage),
+            is_charging: message.power_supply_status
+                == BatteryStateMessage::POWER_SUPPLY_STATUS_CHARGING,
+        })
+    }
+
+    fn is_emergency_stopped(&self) -> Result<bool, Error> {
+        Ok(self
+            .find_diagnostic_status(&self.config.emergency_stop_status_name)?
+            .map(|status| status.level == DiagnosticStatus::ERROR)
+            .unwrap_or(false))
+    }
+
+    fn faults(&self) -> Result<Vec<Fault>, Error> {
+        Ok(self
+            .diagnostic_statuses()?
+            .into_iter()
+            .filter_map(|status| {
+                let level = match status.level {
+                    DiagnosticStatus::WARN => FaultLevel::Warn,
+                    DiagnosticStatus::ERROR => FaultLevel::Error,
+                    DiagnosticStatus::STALE => FaultLevel::Stale,
+                    _ => return None,
+                };
+                Some(Fault {
+                    name: status.name,
+                    level,
+                    message: status.message,
+                })
+            })
+            .collect())
+    }
+
+    fn controller_mode(&self) -> Result<ControllerMode, Error> {
+        let status = self.find_diagnostic_status(&self.config.controller_mode_status_name)?;
+        Ok(status
+            .and_then(|status| {
+                status
+                    .values
+                    .into_iter()
+                    .find(|key_value| key_value.key == self.config.controller_mode_key)
+            })
+            .map(|key_value| key_value.value.parse().unwrap())
+            .unwrap_or(ControllerMode::Unknown))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_status_name_matches() {
+        assert!(diagnostic_status_name_matches(
+            "emergency_stop",
+            "emergency_stop"
+        ));
+        assert!(diagnostic_status_name_matches(
+            "/Safety/emergency_stop",
+            "emergency_stop"
+        ));
+        assert!(!diagnostic_status_name_matches(
+            "/Safety/emergency_stop_button",
+            "emergency_stop"
+        ));
+    }
+
+    #[test]
+    fn test_f32_to_option() {
+        assert_eq!(f32_to_option(f32::NAN), None);
+        assert_eq!(f32_to_option(0.5), Some(0.5));
+    }
+}
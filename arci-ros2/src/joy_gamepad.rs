@@ -0,0 +1,128 @@
+use crate::utils::r2r_error;
+use crate::{node, Node};
+use arci::gamepad::*;
+use arci::*;
+use futures::StreamExt;
+use r2r::sensor_msgs::msg::Joy;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use std::collections::HashMap;
+use std::thread;
+use tracing::error;
+
+const JOY_TOPIC: &str = "/joy";
+
+/// The indices of `buttons` and `axes` in `sensor_msgs/msg/Joy` depend on the joy driver and
+/// the device.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2JoyGamepadConfig {
+    #[serde(default = "default_topic_name")]
+    pub topic_name: String,
+    /// Pairs of the index of `buttons` and the button.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub button_mapping: HashMap<usize, Button>,
+    /// Pairs of the index of `axes` and the axis.
+    #[serde_as(as = "Vec<(_, _)>")]
+    pub axis_mapping: HashMap<usize, Axis>,
+}
+
+fn default_topic_name() -> String {
+    JOY_TOPIC.to_owned()
+}
+
+pub struct Ros2JoyGamepad {
+    rx: crossbeam_channel::Receiver<GamepadEvent>,
+}
+
+impl Ros2JoyGamepad {
+    pub fn new(
+        topic_name: &str,
+        button_mapping: HashMap<usize, Button>,
+        axis_mapping: HashMap<usize, Axis>,
+    ) -> Result<Self, Error> {
+        Self::new_with_node(&node()?, topic_name, button_mapping, axis_mapping)
+    }
+
+    pub fn new_from_config(config: Ros2JoyGamepadConfig) -> Result<Self, Error> {
+        Self::new(
+            &config.topic_name,
+            config.button_mapping,
+            config.axis_mapping,
+        )
+    }
+
+    pub fn new_with_node(
+        node: &Node,
+        topic_name: &str,
+        button_mapping: HashMap<usize, Button>,
+        axis_mapping: HashMap<usize, Axis>,
+    ) -> Result<Self, Error> {
+        const DEAD_ZONE: f32 = 0.00001;
+        let mut stream = node
+            .r2r()
+            .subscribe::<Joy>(topic_name, r2r::QosProfile::default())
+            .map_err(r2r_error)?;
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let tx_for_stop = tx.clone();
+        // spawn for stop by Ctrl-C
+        tokio::spawn(async move {
+            tokio::signal::ctrl_c().await.unwrap();
+            tx_for_stop.send(GamepadEvent::Unknown).unwrap();
+        });
+
+        thread::spawn(move || {
+            futures::executor::block_on(async move {
+                let mut last_joy = Joy::default();
+                while let Some(joy_msg) = stream.next().await {
+                    // initialize last_joy
+                    if last_joy.buttons.len() < joy_msg.buttons.len() {
+                        last_joy.buttons.resize(joy_msg.buttons.len(), 0);
+                    }
+                    if last_joy.axes.len() < joy_msg.axes.len() {
+                        last_joy.axes.resize(joy_msg.axes.len(), 0.0);
+                    }
+                    for (&idx, button) in button_mapping.iter() {
+                        if joy_msg.buttons.len() <= idx {
+                            error!(
+                                "buttons index is out of range, ignored: input={}, size={}",
+                                idx,
+                                joy_msg.buttons.len()
+                            );
+                        } else if last_joy.buttons[idx] == 0 && joy_msg.buttons[idx] == 1 {
+                            let _ = tx.send(GamepadEvent::ButtonPressed(*button));
+                        } else if last_joy.buttons[idx] == 1 && joy_msg.buttons[idx] == 0 {
+                            let _ = tx.send(GamepadEvent::ButtonReleased(*button));
+                        }
+                    }
+                    for (&idx, axis) in axis_mapping.iter() {
+                        if joy_msg.axes.len() <= idx {
+                            error!(
+                                "axes index is out of range, ignored: input={}, size={}",
+                                idx,
+                                joy_msg.axes.len()
+                            );
+                        } else if (last_joy.axes[idx] - joy_msg.axes[idx]).abs() > DEAD_ZONE {
+                            let _ =
+                                tx.send(GamepadEvent::AxisChanged(*axis, joy_msg.axes[idx] as f64));
+                        }
+                    }
+                    last_joy = joy_msg;
+                }
+            })
+        });
+        Ok(Self { rx })
+    }
+}
+
+#[async_trait]
+impl Gamepad for Ros2JoyGamepad {
+    async fn next_event(&self) -> GamepadEvent {
+        if let Ok(ev) = self.rx.recv() {
+            ev
+        } else {
+            GamepadEvent::Unknown
+        }
+    }
+    fn stop(&self) {}
+}
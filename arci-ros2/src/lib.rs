@@ -0,0 +1,17 @@
+mod joy_gamepad;
+mod node;
+mod ros2_cmd_vel_move_base;
+mod ros2_control_client;
+mod ros2_localization_client;
+mod ros2_nav_client;
+mod ros2_speak_client;
+pub mod utils;
+
+pub use joy_gamepad::*;
+pub use node::*;
+pub use r2r;
+pub use ros2_cmd_vel_move_base::*;
+pub use ros2_control_client::*;
+pub use ros2_localization_client::*;
+pub use ros2_nav_client::*;
+pub use ros2_speak_client::*;
@@ -0,0 +1,56 @@
+use crate::utils::r2r_error;
+use once_cell::sync::OnceCell;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+const SPIN_TIMEOUT: Duration = Duration::from_millis(10);
+
+static NODE: OnceCell<Node> = OnceCell::new();
+
+/// ROS2 node which is spun in a background thread.
+#[derive(Clone)]
+pub struct Node {
+    inner: Arc<Mutex<r2r::Node>>,
+}
+
+impl Node {
+    pub fn new(name: &str, namespace: &str) -> Result<Self, arci::Error> {
+        let ctx = r2r::Context::create().map_err(r2r_error)?;
+        let node = r2r::Node::create(ctx, name, namespace).map_err(r2r_error)?;
+        let inner = Arc::new(Mutex::new(node));
+        let weak = Arc::downgrade(&inner);
+        thread::spawn(move || {
+            while let Some(node) = weak.upgrade() {
+                node.lock().unwrap().spin_once(SPIN_TIMEOUT);
+                drop(node);
+                // Give other threads a chance to create publishers and subscribers.
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        Ok(Self { inner })
+    }
+
+    pub fn r2r(&self) -> MutexGuard<'_, r2r::Node> {
+        self.inner.lock().unwrap()
+    }
+}
+
+/// Initialize the node shared by the clients of this crate.
+///
+/// This must be called before creating clients. Calling this twice has no effect.
+pub fn init(name: &str) -> Result<(), arci::Error> {
+    NODE.get_or_try_init(|| Node::new(name, ""))?;
+    Ok(())
+}
+
+/// Returns the node initialized by `init`.
+pub fn node() -> Result<Node, arci::Error> {
+    NODE.get()
+        .cloned()
+        .ok_or_else(|| arci::Error::Uninitialized {
+            message: "arci_ros2::init must be called before creating clients".to_owned(),
+        })
+}
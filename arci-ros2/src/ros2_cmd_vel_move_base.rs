@@ -0,0 +1,41 @@
+use crate::utils::r2r_error;
+use crate::{node, Node};
+use arci::*;
+use r2r::geometry_msgs::msg::Twist;
+use serde::{Deserialize, Serialize};
+
+pub struct Ros2CmdVelMoveBase {
+    vel_publisher: r2r::Publisher<Twist>,
+}
+
+impl Ros2CmdVelMoveBase {
+    pub fn new(cmd_topic_name: &str) -> Result<Self, Error> {
+        Self::new_with_node(&node()?, cmd_topic_name)
+    }
+
+    pub fn new_with_node(node: &Node, cmd_topic_name: &str) -> Result<Self, Error> {
+        let vel_publisher = node
+            .r2r()
+            .create_publisher(cmd_topic_name, r2r::QosProfile::default())
+            .map_err(r2r_error)?;
+        Ok(Self { vel_publisher })
+    }
+}
+
+impl MoveBase for Ros2CmdVelMoveBase {
+    fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), Error> {
+        let mut twist_msg = Twist::default();
+        twist_msg.linear.x = velocity.x;
+        twist_msg.linear.y = velocity.y;
+        twist_msg.angular.z = velocity.theta;
+        self.vel_publisher.publish(&twist_msg).map_err(r2r_error)
+    }
+    fn current_velocity(&self) -> Result<BaseVelocity, Error> {
+        Err(anyhow::anyhow!("Ros2CmdVelMoveBase does not support current_velocity").into())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2CmdVelMoveBaseConfig {
+    pub topic: String,
+}
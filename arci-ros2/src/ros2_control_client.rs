@@ -0,0 +1,217 @@
+use crate::utils::{duration_to_msg, r2r_error, SubscriberHandler};
+use crate::{node, Node};
+use arci::{Error, JointTrajectoryClient, TrajectoryPoint};
+use async_trait::async_trait;
+use r2r::{
+    control_msgs::action::FollowJointTrajectory,
+    sensor_msgs::msg::JointState,
+    trajectory_msgs::msg::{JointTrajectory, JointTrajectoryPoint},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+const JOINT_STATES_TOPIC: &str = "/joint_states";
+/// `control_msgs/action/FollowJointTrajectory` `Result::SUCCESSFUL`
+const FOLLOW_JOINT_TRAJECTORY_SUCCESSFUL: i32 = 0;
+/// Time to wait for the controller and the result in addition to the duration of the trajectory.
+const SEND_TRAJECTORY_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2ControlClientConfig {
+    pub name: String,
+    pub joint_names: Vec<String>,
+    /// e.g. `/arm_controller`. The action `<controller_name>/follow_joint_trajectory` is used.
+    pub controller_name: String,
+    #[serde(default = "default_joint_states_topic_name")]
+    pub joint_states_topic_name: String,
+}
+
+fn default_joint_states_topic_name() -> String {
+    JOINT_STATES_TOPIC.to_owned()
+}
+
+pub fn create_joint_trajectory_clients(
+    configs: Vec<Ros2ControlClientConfig>,
+) -> Result<HashMap<String, Arc<dyn JointTrajectoryClient>>, Error> {
+    let node = node()?;
+    let mut clients = HashMap::new();
+    let mut topic_name_to_subscriber: HashMap<String, Arc<SubscriberHandler<JointState>>> =
+        HashMap::new();
+    for config in configs {
+        let joint_state_subscriber =
+            match topic_name_to_subscriber.get(&config.joint_states_topic_name) {
+                Some(subscriber) => subscriber.clone(),
+                None => {
+                    let subscriber = Arc::new(SubscriberHandler::new(
+                        &node,
+                        &config.joint_states_topic_name,
+                    )?);
+                    topic_name_to_subscriber
+                        .insert(config.joint_states_topic_name.clone(), subscriber.clone());
+                    subscriber
+                }
+            };
+        let client = Ros2ControlClient::new_with_joint_state_subscriber(
+            &node,
+            config.joint_names,
+            &config.controller_name,
+            joint_state_subscriber,
+        )?;
+        clients.insert(
+            config.name,
+            Arc::new(client) as Arc<dyn JointTrajectoryClient>,
+        );
+    }
+    Ok(clients)
+}
+
+/// JointTrajectoryClient using `FollowJointTrajectory` action of ros2_control and `joint_states`.
+pub struct Ros2ControlClient {
+    joint_names: Vec<String>,
+    joint_state_subscriber: Arc<SubscriberHandler<JointState>>,
+    action_client: r2r::ActionClient<FollowJointTrajectory::Action>,
+}
+
+impl Ros2ControlClient {
+    pub fn new(joint_names: Vec<String>, controller_name: &str) -> Result<Self, Error> {
+        let node = node()?;
+        let joint_state_subscriber = Arc::new(SubscriberHandler::new(&node, JOINT_STATES_TOPIC)?);
+        Self::new_with_joint_state_subscriber(
+            &node,
+            joint_names,
+            controller_name,
+            joint_state_subscriber,
+        )
+    }
+
+    pub fn new_with_joint_state_subscriber(
+        node: &Node,
+        joint_names: Vec<String>,
+        controller_name: &str,
+        joint_state_subscriber: Arc<SubscriberHandler<JointState>>,
+    ) -> Result<Self, Error> {
+        let action_client = node
+            .r2r()
+            .create_action_client::<FollowJointTrajectory::Action>(&format!(
+                "{}/follow_joint_trajectory",
+                controller_name
+            ))
+            .map_err(r2r_error)?;
+        joint_state_subscriber.wait_message(Duration::from_secs(1));
+        Ok(Self {
+            joint_names,
+            joint_state_subscriber,
+            action_client,
+        })
+    }
+
+    /// Sends the trajectory and waits for the result within `timeout`, including waiting for
+    /// the controller, which may not be running.
+    async fn send_trajectory(
+        &self,
+        trajectory: JointTrajectory,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let goal = FollowJointTrajectory::Goal {
+            trajectory,
+            ..Default::default()
+        };
+        let mut client_goal = None;
+        let result = tokio::time::timeout(timeout, async {
+            r2r::Node::is_available(&self.action_client)
+                .map_err(r2r_error)?
+                .await
+                .map_err(r2r_error)?;
+            let (goal, result, _feedback) = self
+                .action_client
+                .send_goal_request(goal)
+                .map_err(r2r_error)?
+                .await
+                .map_err(r2r_error)?;
+            client_goal = Some(goal);
+            result.await.map_err(r2r_error)
+        })
+        .await;
+        let (status, result) = match result {
+            Ok(result) => result?,
+            Err(_) => {
+                if let Some(goal) = client_goal {
+                    goal.cancel().map_err(r2r_error)?.await.map_err(r2r_error)?;
+                }
+                return Err(
+                    anyhow::anyhow!("FollowJointTrajectory timeout ({:?})", timeout).into(),
+                );
+            }
+        };
+        if status != r2r::GoalStatus::Succeeded
+            || result.error_code != FOLLOW_JOINT_TRAJECTORY_SUCCESSFUL
+        {
+            return Err(anyhow::anyhow!(
+                "FollowJointTrajectory failed: status={:?}, error_code={}, error_string={:?}",
+                status,
+                result.error_code,
+                result.error_string
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JointTrajectoryClient for Ros2ControlClient {
+    fn joint_names(&self) -> &[String] {
+        &self.joint_names
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, Error> {
+        let joint_state = self.joint_state_subscriber.get()?;
+        self.joint_names
+            .iter()
+            .map(|joint_name| {
+                joint_state
+                    .name
+                    .iter()
+                    .position(|name| name == joint_name)
+                    .and_then(|index| joint_state.position.get(index).copied())
+                    .ok_or_else(|| {
+                        Error::Other(anyhow::anyhow!(
+                            "{} is not found in {}",
+                            joint_name,
+                            self.joint_state_subscriber.topic()
+                        ))
+                    })
+            })
+            .collect()
+    }
+
+    async fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: Duration,
+    ) -> Result<(), Error> {
+        self.send_joint_trajectory(vec![TrajectoryPoint::new(positions, duration)])
+            .await
+    }
+
+    async fn send_joint_trajectory(&self, trajectory: Vec<TrajectoryPoint>) -> Result<(), Error> {
+        let timeout = trajectory
+            .last()
+            .map_or(Duration::default(), |point| point.time_from_start)
+            + SEND_TRAJECTORY_TIMEOUT_MARGIN;
+        let trajectory = JointTrajectory {
+            joint_names: self.joint_names.clone(),
+            points: trajectory
+                .into_iter()
+                .map(|point| JointTrajectoryPoint {
+                    positions: point.positions,
+                    velocities: point.velocities.unwrap_or_default(),
+                    time_from_start: duration_to_msg(point.time_from_start),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        self.send_trajectory(trajectory, timeout).await
+    }
+}
@@ -0,0 +1,57 @@
+use crate::utils::{pose_to_isometry2, SubscriberHandler};
+use crate::{node, Node};
+use arci::*;
+use nalgebra as na;
+use r2r::geometry_msgs::msg::PoseWithCovarianceStamped;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const AMCL_POSE_TOPIC: &str = "/amcl_pose";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2LocalizationClientConfig {
+    #[serde(default = "default_pose_topic")]
+    pub pose_topic: String,
+}
+
+fn default_pose_topic() -> String {
+    AMCL_POSE_TOPIC.to_owned()
+}
+
+impl Default for Ros2LocalizationClientConfig {
+    fn default() -> Self {
+        Self {
+            pose_topic: default_pose_topic(),
+        }
+    }
+}
+
+/// Localization using the pose published by Nav2 (amcl).
+pub struct Ros2LocalizationClient {
+    pose_subscriber: SubscriberHandler<PoseWithCovarianceStamped>,
+}
+
+impl Ros2LocalizationClient {
+    pub fn new(pose_topic: &str) -> Result<Self, Error> {
+        Self::new_with_node(&node()?, pose_topic)
+    }
+
+    pub fn new_with_node(node: &Node, pose_topic: &str) -> Result<Self, Error> {
+        Ok(Self {
+            pose_subscriber: SubscriberHandler::new(node, pose_topic)?,
+        })
+    }
+
+    pub fn new_from_config(config: Ros2LocalizationClientConfig) -> Result<Self, Error> {
+        Self::new(&config.pose_topic)
+    }
+}
+
+impl Localization for Ros2LocalizationClient {
+    fn current_pose(&self, _frame_id: &str) -> Result<na::Isometry2<f64>, Error> {
+        self.pose_subscriber
+            .wait_message(Duration::from_millis(100));
+        let pose_with_cov_stamped = self.pose_subscriber.get()?;
+        Ok(pose_to_isometry2(&pose_with_cov_stamped.pose.pose))
+    }
+}
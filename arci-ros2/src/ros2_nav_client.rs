@@ -0,0 +1,186 @@
+use crate::utils::{isometry2_to_pose, pose_to_isometry2, r2r_error};
+use crate::{node, Node};
+use arci::*;
+use futures::StreamExt;
+use nalgebra as na;
+use r2r::{geometry_msgs::msg::PoseStamped, nav2_msgs::action::NavigateToPose};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+const NAVIGATE_TO_POSE_ACTION: &str = "/navigate_to_pose";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2NavClientConfig {
+    #[serde(default = "default_action_name")]
+    pub action_name: String,
+    /// Behavior tree used by bt_navigator. The default one is used if empty.
+    #[serde(default)]
+    pub behavior_tree: String,
+}
+
+fn default_action_name() -> String {
+    NAVIGATE_TO_POSE_ACTION.to_owned()
+}
+
+impl Default for Ros2NavClientConfig {
+    fn default() -> Self {
+        Self {
+            action_name: default_action_name(),
+            behavior_tree: String::new(),
+        }
+    }
+}
+
+fn goal_status_to_navigation_status(status: r2r::GoalStatus) -> NavigationStatus {
+    match status {
+        r2r::GoalStatus::Accepted => NavigationStatus::Pending,
+        r2r::GoalStatus::Executing | r2r::GoalStatus::Canceling => NavigationStatus::Active,
+        r2r::GoalStatus::Succeeded => NavigationStatus::Succeeded,
+        r2r::GoalStatus::Canceled => NavigationStatus::Canceled,
+        _ => NavigationStatus::Aborted,
+    }
+}
+
+/// Navigation using `NavigateToPose` action of Nav2.
+pub struct Ros2NavClient {
+    action_client: r2r::ActionClient<NavigateToPose::Action>,
+    behavior_tree: String,
+    current_goal: Mutex<Option<r2r::ClientGoal<NavigateToPose::Action>>>,
+    status: Arc<Mutex<NavigationStatus>>,
+    feedback: Arc<Mutex<Option<NavigationFeedback>>>,
+}
+
+impl Ros2NavClient {
+    pub fn new(action_name: &str) -> Result<Self, Error> {
+        Self::new_with_node(&node()?, action_name)
+    }
+
+    pub fn new_with_node(node: &Node, action_name: &str) -> Result<Self, Error> {
+        let action_client = node
+            .r2r()
+            .create_action_client::<NavigateToPose::Action>(action_name)
+            .map_err(r2r_error)?;
+        Ok(Self {
+            action_client,
+            behavior_tree: String::new(),
+            current_goal: Mutex::new(None),
+            status: Arc::new(Mutex::new(NavigationStatus::Idle)),
+            feedback: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub fn new_from_config(config: Ros2NavClientConfig) -> Result<Self, Error> {
+        let mut client = Self::new(&config.action_name)?;
+        client.behavior_tree = config.behavior_tree;
+        Ok(client)
+    }
+
+    fn cancel_current_goal(&self) -> Result<(), Error> {
+        if let Some(goal) = self.current_goal.lock().unwrap().take() {
+            futures::executor::block_on(goal.cancel().map_err(r2r_error)?).map_err(r2r_error)?;
+            info!("NavigateToPose goal is canceled");
+        }
+        Ok(())
+    }
+
+    /// Sends the goal when the action server is available and waits for the result.
+    async fn send_goal_and_wait(
+        &self,
+        goal_msg: NavigateToPose::Goal,
+    ) -> Result<r2r::GoalStatus, Error> {
+        r2r::Node::is_available(&self.action_client)
+            .map_err(r2r_error)?
+            .await
+            .map_err(r2r_error)?;
+        let (client_goal, result, mut feedback_stream) = self
+            .action_client
+            .send_goal_request(goal_msg)
+            .map_err(r2r_error)?
+            .await
+            .map_err(r2r_error)?;
+        *self.current_goal.lock().unwrap() = Some(client_goal);
+        *self.status.lock().unwrap() = NavigationStatus::Active;
+
+        let feedback = self.feedback.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = feedback_stream.next().await {
+                *feedback.lock().unwrap() = Some(NavigationFeedback {
+                    current_pose: pose_to_isometry2(&msg.current_pose.pose),
+                    distance_remaining: msg.distance_remaining as f64,
+                });
+            }
+        });
+
+        let (status, _) = result.await.map_err(r2r_error)?;
+        Ok(status)
+    }
+}
+
+#[async_trait]
+impl Navigation for Ros2NavClient {
+    async fn move_to(
+        &self,
+        goal: na::Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let mut pose = PoseStamped {
+            pose: isometry2_to_pose(&goal),
+            ..Default::default()
+        };
+        pose.header.frame_id = frame_id.to_owned();
+        let goal_msg = NavigateToPose::Goal {
+            pose,
+            behavior_tree: self.behavior_tree.clone(),
+        };
+        *self.status.lock().unwrap() = NavigationStatus::Pending;
+        *self.feedback.lock().unwrap() = None;
+
+        // The timeout includes waiting for the action server, which may not be running.
+        match tokio::time::timeout(timeout, self.send_goal_and_wait(goal_msg)).await {
+            Ok(result) => {
+                let status = result?;
+                self.current_goal.lock().unwrap().take();
+                let status = goal_status_to_navigation_status(status);
+                *self.status.lock().unwrap() = status;
+                if status == NavigationStatus::Succeeded {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("NavigateToPose does not succeed : {:?}", status).into())
+                }
+            }
+            Err(_) => {
+                warn!("NavigateToPose timeout");
+                self.cancel_current_goal()?;
+                *self.status.lock().unwrap() = NavigationStatus::Aborted;
+                Err(anyhow::anyhow!("Timeout to move to {:?}", goal).into())
+            }
+        }
+    }
+
+    fn cancel(&self) -> Result<(), Error> {
+        self.cancel_current_goal()?;
+        *self.status.lock().unwrap() = NavigationStatus::Canceled;
+        Ok(())
+    }
+
+    fn status(&self) -> Result<NavigationStatus, Error> {
+        Ok(*self.status.lock().unwrap())
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, Error> {
+        Ok(self.feedback.lock().unwrap().clone())
+    }
+
+    fn plan_path(
+        &self,
+        _goal: na::Isometry2<f64>,
+        _frame_id: &str,
+    ) -> Result<Vec<na::Isometry2<f64>>, Error> {
+        Err(anyhow::anyhow!("Ros2NavClient does not support plan_path").into())
+    }
+}
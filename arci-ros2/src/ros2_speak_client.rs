@@ -0,0 +1,41 @@
+use crate::utils::r2r_error;
+use crate::{node, Node};
+use r2r::std_msgs::msg::String as StringMsg;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+/// Publish the message to the topic (e.g. the input of a text-to-speech node).
+pub struct Ros2SpeakClient {
+    publisher: r2r::Publisher<StringMsg>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ros2SpeakClientConfig {
+    pub topic: String,
+}
+
+impl Ros2SpeakClient {
+    pub fn new(topic: &str) -> Result<Self, arci::Error> {
+        Self::new_with_node(&node()?, topic)
+    }
+
+    pub fn new_with_node(node: &Node, topic: &str) -> Result<Self, arci::Error> {
+        Ok(Self {
+            publisher: node
+                .r2r()
+                .create_publisher(topic, r2r::QosProfile::default())
+                .map_err(r2r_error)?,
+        })
+    }
+}
+
+impl arci::Speaker for Ros2SpeakClient {
+    fn speak(&self, message: &str) {
+        let ros_msg = StringMsg {
+            data: message.to_string(),
+        };
+        if let Err(e) = self.publisher.publish(&ros_msg) {
+            error!("Failed to publish {:?}: {:?}", message, e);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::Node;
+use futures::StreamExt;
+use nalgebra as na;
+use r2r::{builtin_interfaces, geometry_msgs};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+pub(crate) fn r2r_error(e: r2r::Error) -> arci::Error {
+    arci::Error::Connection {
+        message: format!("r2r error: {:?}", e),
+    }
+}
+
+/// Keeps the latest message of the topic.
+pub struct SubscriberHandler<T> {
+    topic: String,
+    latest: Arc<Mutex<Option<T>>>,
+}
+
+impl<T> SubscriberHandler<T>
+where
+    T: r2r::WrappedTypesupport + Send + 'static,
+{
+    pub fn new(node: &Node, topic: &str) -> Result<Self, arci::Error> {
+        let mut stream = node
+            .r2r()
+            .subscribe::<T>(topic, r2r::QosProfile::default())
+            .map_err(r2r_error)?;
+        let latest = Arc::new(Mutex::new(None));
+        let latest_cloned = latest.clone();
+        thread::spawn(move || {
+            futures::executor::block_on(async move {
+                while let Some(msg) = stream.next().await {
+                    *latest_cloned.lock().unwrap() = Some(msg);
+                }
+            })
+        });
+        Ok(Self {
+            topic: topic.to_owned(),
+            latest,
+        })
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Wait until the first message arrives. Returns false on timeout.
+    pub fn wait_message(&self, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if self.latest.lock().unwrap().is_some() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+}
+
+impl<T> SubscriberHandler<T>
+where
+    T: Clone,
+{
+    pub fn get(&self) -> Result<T, arci::Error> {
+        self.latest
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| arci::Error::Connection {
+                message: format!("No message is received from {}", self.topic),
+            })
+    }
+}
+
+pub fn duration_to_msg(duration: Duration) -> builtin_interfaces::msg::Duration {
+    builtin_interfaces::msg::Duration {
+        sec: duration.as_secs() as i32,
+        nanosec: duration.subsec_nanos(),
+    }
+}
+
+pub fn isometry2_to_pose(pose: &na::Isometry2<f64>) -> geometry_msgs::msg::Pose {
+    let rotation = na::UnitQuaternion::from_euler_angles(0.0, 0.0, pose.rotation.angle());
+    geometry_msgs::msg::Pose {
+        position: geometry_msgs::msg::Point {
+            x: pose.translation.x,
+            y: pose.translation.y,
+            z: 0.0,
+        },
+        orientation: geometry_msgs::msg::Quaternion {
+            x: rotation.i,
+            y: rotation.j,
+            z: rotation.k,
+            w: rotation.w,
+        },
+    }
+}
+
+pub fn pose_to_isometry2(pose: &geometry_msgs::msg::Pose) -> na::Isometry2<f64> {
+    let rotation = na::UnitQuaternion::from_quaternion(na::Quaternion::new(
+        pose.orientation.w,
+        pose.orientation.x,
+        pose.orientation.y,
+        pose.orientation.z,
+    ));
+    na::Isometry2::new(
+        na::Vector2::new(pose.position.x, pose.position.y),
+        rotation.euler_angles().2,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pose_conversion() {
+        let pose = na::Isometry2::new(na::Vector2::new(1.0, -2.0), 0.5);
+        let msg = isometry2_to_pose(&pose);
+        assert!((msg.position.x - 1.0).abs() < 1e-9);
+        assert!((msg.position.y + 2.0).abs() < 1e-9);
+        let converted = pose_to_isometry2(&msg);
+        assert!((converted.translation.vector - pose.translation.vector).norm() < 1e-9);
+        assert!((converted.rotation.angle() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_duration_to_msg() {
+        let msg = duration_to_msg(Duration::from_millis(1500));
+        assert_eq!(msg.sec, 1);
+        assert_eq!(msg.nanosec, 500_000_000);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::utils::*;
 use arci::{
     BaseVelocity, CompleteCondition, JointTrajectoryClient, JointVelocityLimiter, Localization,
-    MoveBase, Navigation, SetCompleteCondition, TotalJointDiffCondition,
+    MoveBase, Navigation, NavigationFeedback, NavigationStatus, SetCompleteCondition,
+    TotalJointDiffCondition,
 };
 use async_trait::async_trait;
 use nalgebra as na;
@@ -84,6 +85,7 @@ pub struct UrdfVizWebClient {
     complete_condition: Box<dyn CompleteCondition>,
     send_joint_positions_thread: Option<JoinHandle<()>>,
     is_dropping: Arc<AtomicBool>,
+    navigation_status: Mutex<NavigationStatus>,
 }
 
 impl UrdfVizWebClient {
@@ -99,6 +101,7 @@ impl UrdfVizWebClient {
             complete_condition: Box::new(TotalJointDiffCondition::default()),
             send_joint_positions_thread: None,
             is_dropping: Arc::new(AtomicBool::new(false)),
+            navigation_status: Mutex::new(NavigationStatus::Idle),
         })
     }
     pub fn run_thread(&self) {
@@ -285,14 +288,34 @@ impl Navigation for UrdfVizWebClient {
             }
         })?;
         if !re.is_ok {
+            *self.navigation_status.lock().unwrap() = NavigationStatus::Aborted;
             return Err(arci::Error::Connection { message: re.reason });
         }
+        *self.navigation_status.lock().unwrap() = NavigationStatus::Succeeded;
 
         Ok(())
     }
 
     fn cancel(&self) -> Result<(), arci::Error> {
-        todo!()
+        // move_to finishes immediately, so there is nothing to cancel.
+        Ok(())
+    }
+
+    fn status(&self) -> Result<NavigationStatus, arci::Error> {
+        Ok(*self.navigation_status.lock().unwrap())
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, arci::Error> {
+        // The goal is never active because move_to jumps to the goal.
+        Ok(None)
+    }
+
+    fn plan_path(
+        &self,
+        goal: na::Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Vec<na::Isometry2<f64>>, arci::Error> {
+        Ok(vec![self.current_pose(frame_id)?, goal])
     }
 }
 
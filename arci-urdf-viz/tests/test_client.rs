@@ -4,7 +4,10 @@ use assert_approx_eq::assert_approx_eq;
 use std::sync::{Arc, Mutex};
 use url::Url;
 
-use arci::{JointTrajectoryClient, SetCompleteCondition, TotalJointDiffCondition, TrajectoryPoint};
+use arci::{
+    JointTrajectoryClient, Navigation, NavigationStatus, SetCompleteCondition,
+    TotalJointDiffCondition, TrajectoryPoint,
+};
 use arci_urdf_viz::{UrdfVizWebClient, UrdfVizWebClientConfig};
 use web_server::*;
 
@@ -159,3 +162,39 @@ async fn test_send_joint_trajectory() {
     let result = client.send_joint_trajectory(trajectory).await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+async fn test_navigation() {
+    const PORT: u16 = 7782;
+    let mut web_server = WebServer::new(PORT);
+    web_server.current_robot_origin = Arc::new(Mutex::new(RobotOrigin {
+        position: [1.0, 2.0, 0.0],
+        quaternion: [1.0, 0.0, 0.0, 0.0],
+    }));
+    let target_robot_origin = web_server.target_robot_origin.clone();
+    std::thread::spawn(move || web_server.start());
+    std::thread::sleep(std::time::Duration::from_secs(1)); // Wait for web server to start.
+    let client =
+        UrdfVizWebClient::try_new(Url::parse(&format!("http://127.0.0.1:{}", PORT)).unwrap())
+            .unwrap();
+    assert_eq!(client.status().unwrap(), NavigationStatus::Idle);
+
+    let goal = arci::Isometry2::new(nalgebra::Vector2::new(3.0, 4.0), 0.5);
+    let path = client.plan_path(goal, "").unwrap();
+    assert_eq!(path.len(), 2);
+    assert_approx_eq!(path[0].translation.x, 1.0);
+    assert_approx_eq!(path[0].translation.y, 2.0);
+    assert_approx_eq!(path[1].translation.x, 3.0);
+    assert_approx_eq!(path[1].translation.y, 4.0);
+
+    client
+        .move_to(goal, "", std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(client.status().unwrap(), NavigationStatus::Succeeded);
+    assert!(client.feedback().unwrap().is_none());
+    let target_robot_origin = target_robot_origin.lock().unwrap();
+    assert!(target_robot_origin.requested);
+    assert_approx_eq!(target_robot_origin.origin.position[0], 3.0);
+    assert_approx_eq!(target_robot_origin.origin.position[1], 4.0);
+}
@@ -1,15 +1,27 @@
 mod dummy_localization;
 mod dummy_move_base;
 mod dummy_navigation;
+mod dummy_robot_status;
+mod dummy_speaker;
 mod dummy_trajectory_client;
 mod joint_trajectory_clients_container;
 mod joint_velocity_limiter;
+mod merged_gamepad;
 mod partial_joint_trajectory_client;
+mod safe_move_base;
+mod scripted_gamepad;
+mod transform_buffer;
 
 pub use dummy_localization::*;
 pub use dummy_move_base::*;
 pub use dummy_navigation::*;
+pub use dummy_robot_status::*;
+pub use dummy_speaker::*;
 pub use dummy_trajectory_client::*;
 pub use joint_trajectory_clients_container::*;
 pub use joint_velocity_limiter::*;
+pub use merged_gamepad::*;
 pub use partial_joint_trajectory_client::*;
+pub use safe_move_base::*;
+pub use scripted_gamepad::*;
+pub use transform_buffer::*;
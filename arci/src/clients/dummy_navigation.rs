@@ -1,5 +1,5 @@
 use crate::error::Error;
-use crate::traits::Navigation;
+use crate::traits::{Navigation, NavigationFeedback, NavigationStatus};
 use async_trait::async_trait;
 use nalgebra::{Isometry2, Vector2};
 use std::sync::Mutex;
@@ -7,12 +7,14 @@ use std::sync::Mutex;
 #[derive(Debug)]
 pub struct DummyNavigation {
     pub goal_pose: Mutex<Isometry2<f64>>,
+    pub status: Mutex<NavigationStatus>,
 }
 
 impl DummyNavigation {
     pub fn new() -> Self {
         Self {
             goal_pose: Mutex::new(Isometry2::new(Vector2::new(0.0, 0.0), 0.0)),
+            status: Mutex::new(NavigationStatus::Idle),
         }
     }
 
@@ -36,17 +38,46 @@ impl Navigation for DummyNavigation {
         _timeout: std::time::Duration,
     ) -> Result<(), Error> {
         *self.goal_pose.lock().unwrap() = goal;
+        *self.status.lock().unwrap() = NavigationStatus::Succeeded;
         Ok(())
     }
 
     fn cancel(&self) -> Result<(), Error> {
+        let mut status = self.status.lock().unwrap();
+        if !status.is_done() && *status != NavigationStatus::Idle {
+            *status = NavigationStatus::Canceled;
+        }
         Ok(())
     }
+
+    fn status(&self) -> Result<NavigationStatus, Error> {
+        Ok(*self.status.lock().unwrap())
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, Error> {
+        if *self.status.lock().unwrap() == NavigationStatus::Active {
+            Ok(Some(NavigationFeedback {
+                current_pose: self.current_goal_pose()?,
+                distance_remaining: 0.0,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn plan_path(
+        &self,
+        goal: Isometry2<f64>,
+        _frame_id: &str,
+    ) -> Result<Vec<Isometry2<f64>>, Error> {
+        Ok(vec![self.current_goal_pose()?, goal])
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::traits::Waypoint;
     #[test]
     fn test_set() {
         let nav = DummyNavigation::new();
@@ -61,5 +92,34 @@ mod tests {
         assert_eq!(current_goal_pose.translation.x, 1.0);
         assert_eq!(current_goal_pose.translation.y, 2.0);
         assert_eq!(current_goal_pose.rotation.angle(), 3.0);
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Succeeded);
+        assert!(nav.feedback().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_follow_waypoints() {
+        let nav = DummyNavigation::new();
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Idle);
+        assert!(tokio_test::block_on(nav.follow_waypoints(
+            &[
+                Waypoint::with_tolerance(Isometry2::new(Vector2::new(1.0, 0.0), 0.0), 0.1, 0.1),
+                Waypoint::new(Isometry2::new(Vector2::new(1.0, 2.0), 1.0)),
+            ],
+            "",
+            std::time::Duration::default(),
+        ))
+        .is_ok());
+
+        let current_goal_pose = nav.current_goal_pose().unwrap();
+        assert_eq!(current_goal_pose.translation.x, 1.0);
+        assert_eq!(current_goal_pose.translation.y, 2.0);
+        assert_eq!(current_goal_pose.rotation.angle(), 1.0);
+
+        let path = nav
+            .plan_path(Isometry2::new(Vector2::new(3.0, 2.0), 0.0), "")
+            .unwrap();
+        assert_eq!(path.len(), 2);
+        assert_eq!(path[0].translation.x, 1.0);
+        assert_eq!(path[1].translation.x, 3.0);
     }
 }
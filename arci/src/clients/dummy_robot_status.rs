@@ -0,0 +1,80 @@
+use crate::error::Error;
+use crate::traits::{BatteryState, ControllerMode, Fault, RobotStatus};
+use std::sync::Mutex;
+
+/// Dummy RobotStatus for Debug or Tests
+#[derive(Debug)]
+pub struct DummyRobotStatus {
+    pub battery_state: Mutex<BatteryState>,
+    pub is_emergency_stopped: Mutex<bool>,
+    pub faults: Mutex<Vec<Fault>>,
+    pub controller_mode: Mutex<ControllerMode>,
+}
+
+impl DummyRobotStatus {
+    pub fn new() -> Self {
+        Self {
+            battery_state: Mutex::new(BatteryState {
+                percentage: Some(1.0),
+                voltage: None,
+                is_charging: false,
+            }),
+            is_emergency_stopped: Mutex::new(false),
+            faults: Mutex::new(Vec::new()),
+            controller_mode: Mutex::new(ControllerMode::Unknown),
+        }
+    }
+}
+
+impl Default for DummyRobotStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RobotStatus for DummyRobotStatus {
+    fn battery_state(&self) -> Result<BatteryState, Error> {
+        Ok(self.battery_state.lock().unwrap().clone())
+    }
+    fn is_emergency_stopped(&self) -> Result<bool, Error> {
+        Ok(*self.is_emergency_stopped.lock().unwrap())
+    }
+    fn faults(&self) -> Result<Vec<Fault>, Error> {
+        Ok(self.faults.lock().unwrap().clone())
+    }
+    fn controller_mode(&self) -> Result<ControllerMode, Error> {
+        Ok(self.controller_mode.lock().unwrap().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::FaultLevel;
+    use assert_approx_eq::assert_approx_eq;
+    #[test]
+    fn test_set_get() {
+        let status = DummyRobotStatus::new();
+        assert_approx_eq!(status.battery_state().unwrap().percentage.unwrap(), 1.0);
+        assert!(!status.is_emergency_stopped().unwrap());
+        assert!(status.faults().unwrap().is_empty());
+        assert_eq!(status.controller_mode().unwrap(), ControllerMode::Unknown);
+
+        status.battery_state.lock().unwrap().percentage = Some(0.1);
+        *status.is_emergency_stopped.lock().unwrap() = true;
+        status.faults.lock().unwrap().push(Fault {
+            name: "left_wheel".to_owned(),
+            level: FaultLevel::Error,
+            message: "overcurrent".to_owned(),
+        });
+        *status.controller_mode.lock().unwrap() = ControllerMode::Manual;
+
+        assert_approx_eq!(status.battery_state().unwrap().percentage.unwrap(), 0.1);
+        assert!(status.is_emergency_stopped().unwrap());
+        let faults = status.faults().unwrap();
+        assert_eq!(faults.len(), 1);
+        assert_eq!(faults[0].name, "left_wheel");
+        assert_eq!(faults[0].level, FaultLevel::Error);
+        assert_eq!(status.controller_mode().unwrap(), ControllerMode::Manual);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::traits::Speaker;
+use std::sync::Mutex;
+
+/// Dummy Speaker for Debug or Tests, which records the messages.
+#[derive(Debug, Default)]
+pub struct DummySpeaker {
+    pub messages: Mutex<Vec<String>>,
+}
+
+impl DummySpeaker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl Speaker for DummySpeaker {
+    fn speak(&self, message: &str) {
+        self.messages.lock().unwrap().push(message.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_speak() {
+        let speaker = DummySpeaker::new();
+        assert!(speaker.messages().is_empty());
+        speaker.speak("a");
+        speaker.speak("b");
+        assert_eq!(speaker.messages(), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::traits::gamepad::{Gamepad, GamepadEvent};
+use async_trait::async_trait;
+use futures::{channel::mpsc, lock::Mutex as AsyncMutex, StreamExt};
+use std::{sync::Arc, time::Duration};
+
+/// `Gamepad` which merges the events of multiple gamepads, e.g. for two operators or
+/// a gamepad with a 6DoF device.
+///
+/// `GamepadEvent::Unknown` of any gamepad is returned as is, so
+/// `openrr_teleop::ControlNodeSwitcher` stops when one of the gamepads stops.
+pub struct MergedGamepad<G: Gamepad> {
+    gamepads: Vec<Arc<G>>,
+    receiver: AsyncMutex<mpsc::UnboundedReceiver<GamepadEvent>>,
+}
+
+impl<G> MergedGamepad<G>
+where
+    G: 'static + Gamepad,
+{
+    pub fn new(gamepads: Vec<G>) -> Self {
+        let (sender, receiver) = mpsc::unbounded();
+        let gamepads: Vec<_> = gamepads.into_iter().map(Arc::new).collect();
+        for gamepad in &gamepads {
+            let gamepad = gamepad.clone();
+            let sender = sender.clone();
+            // Some gamepads block the thread in `next_event`, so each gamepad needs a thread.
+            std::thread::spawn(move || loop {
+                let event = futures::executor::block_on(gamepad.next_event());
+                let is_unknown = event == GamepadEvent::Unknown;
+                if sender.unbounded_send(event).is_err() || is_unknown {
+                    return;
+                }
+            });
+        }
+        Self {
+            gamepads,
+            receiver: AsyncMutex::new(receiver),
+        }
+    }
+}
+
+#[async_trait]
+impl<G> Gamepad for MergedGamepad<G>
+where
+    G: 'static + Gamepad,
+{
+    async fn next_event(&self) -> GamepadEvent {
+        self.receiver
+            .lock()
+            .await
+            .next()
+            .await
+            .unwrap_or(GamepadEvent::Unknown)
+    }
+
+    fn stop(&self) {
+        for gamepad in &self.gamepads {
+            gamepad.stop();
+        }
+    }
+
+    fn rumble(&self, intensity: f64, duration: Duration) {
+        for gamepad in &self.gamepads {
+            gamepad.rumble(intensity, duration);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gamepad::{Axis, Button},
+        GamepadScript, ScriptedGamepad,
+    };
+
+    #[test]
+    fn test_merge() {
+        let merged = MergedGamepad::new(vec![
+            ScriptedGamepad::new(
+                GamepadScript::new()
+                    .press(Button::South)
+                    .wait(Duration::from_millis(100)),
+            ),
+            ScriptedGamepad::new(
+                GamepadScript::new()
+                    .wait(Duration::from_millis(50))
+                    .axis(Axis::LinearX, 0.5)
+                    .wait(Duration::from_millis(100)),
+            ),
+        ]);
+        tokio_test::block_on(async {
+            assert_eq!(
+                merged.next_event().await,
+                GamepadEvent::ButtonPressed(Button::South)
+            );
+            assert_eq!(
+                merged.next_event().await,
+                GamepadEvent::AxisChanged(Axis::LinearX, 0.5)
+            );
+            // The first gamepad ends.
+            assert_eq!(merged.next_event().await, GamepadEvent::Unknown);
+        });
+
+        merged.rumble(0.5, Duration::from_millis(100));
+        for gamepad in &merged.gamepads {
+            assert_eq!(gamepad.rumbles(), vec![(0.5, Duration::from_millis(100))]);
+        }
+    }
+
+    #[test]
+    fn test_stop() {
+        let merged = MergedGamepad::new(vec![ScriptedGamepad::new(
+            GamepadScript::new()
+                .wait(Duration::from_secs(10))
+                .press(Button::South),
+        )]);
+        merged.stop();
+        assert_eq!(
+            tokio_test::block_on(merged.next_event()),
+            GamepadEvent::Unknown
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use crate::error::Error;
+use crate::traits::{BaseVelocity, Localization, MoveBase};
+use nalgebra::{Isometry2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, warn};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeofenceConfig {
+    /// Vertices `[x, y]` of the polygon in `frame_id`.
+    pub polygon: Vec<[f64; 2]>,
+    #[serde(default = "default_geofence_frame_id")]
+    pub frame_id: String,
+    /// The base moves with the velocity for this duration, and the velocity is rejected
+    /// if the base goes out of the polygon.
+    #[serde(default = "default_geofence_lookahead_secs")]
+    pub lookahead_secs: f64,
+}
+
+fn default_geofence_frame_id() -> String {
+    "map".to_owned()
+}
+
+fn default_geofence_lookahead_secs() -> f64 {
+    0.5
+}
+
+impl GeofenceConfig {
+    /// Returns true if the point is inside of the polygon.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arci::GeofenceConfig;
+    ///
+    /// let geofence = GeofenceConfig {
+    ///     polygon: vec![[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [1.0, 1.0], [0.0, 2.0]],
+    ///     frame_id: "map".to_owned(),
+    ///     lookahead_secs: 0.5,
+    /// };
+    /// assert!(geofence.contains(1.0, 0.5));
+    /// assert!(!geofence.contains(1.0, 1.5));
+    /// assert!(!geofence.contains(3.0, 1.0));
+    /// ```
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let mut is_inside = false;
+        let mut prev = match self.polygon.last() {
+            Some(prev) => prev,
+            None => return false,
+        };
+        for current in &self.polygon {
+            if (current[1] > y) != (prev[1] > y)
+                && x < (prev[0] - current[0]) * (y - current[1]) / (prev[1] - current[1])
+                    + current[0]
+            {
+                is_inside = !is_inside;
+            }
+            prev = current;
+        }
+        is_inside
+    }
+
+    /// Returns the distance from the point to the nearest edge of the polygon.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arci::GeofenceConfig;
+    ///
+    /// let geofence = GeofenceConfig {
+    ///     polygon: vec![[0.0, 0.0], [2.0, 0.0], [2.0, 2.0], [0.0, 2.0]],
+    ///     frame_id: "map".to_owned(),
+    ///     lookahead_secs: 0.5,
+    /// };
+    /// assert_eq!(geofence.distance_to_edge(1.0, 0.5), 0.5);
+    /// assert_eq!(geofence.distance_to_edge(3.0, 1.0), 1.0);
+    /// assert_eq!(geofence.distance_to_edge(-3.0, -4.0), 5.0);
+    /// ```
+    pub fn distance_to_edge(&self, x: f64, y: f64) -> f64 {
+        let point = Vector2::new(x, y);
+        let mut prev = match self.polygon.last() {
+            Some(prev) => Vector2::new(prev[0], prev[1]),
+            None => return f64::INFINITY,
+        };
+        let mut min_distance = f64::INFINITY;
+        for current in &self.polygon {
+            let current = Vector2::new(current[0], current[1]);
+            let edge = current - prev;
+            let ratio = if edge.norm_squared() > 0.0 {
+                nalgebra::clamp((point - prev).dot(&edge) / edge.norm_squared(), 0.0, 1.0)
+            } else {
+                0.0
+            };
+            min_distance = min_distance.min((point - (prev + edge * ratio)).norm());
+            prev = current;
+        }
+        min_distance
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SafeMoveBaseConfig {
+    #[serde(default = "default_limit")]
+    pub max_linear_vel: f64,
+    #[serde(default = "default_limit")]
+    pub max_angular_vel: f64,
+    #[serde(default = "default_limit")]
+    pub max_linear_acc: f64,
+    #[serde(default = "default_limit")]
+    pub max_angular_acc: f64,
+    /// Upper bound of the time step used to limit the acceleration, so that the base does not
+    /// jump to a high velocity after commands are paused.
+    #[serde(default = "default_max_command_interval_secs")]
+    pub max_command_interval_secs: f64,
+    /// Zero velocity is sent if no command arrives within this duration.
+    #[serde(default)]
+    pub deadman_timeout_secs: Option<f64>,
+    #[serde(default)]
+    pub geofence: Option<GeofenceConfig>,
+}
+
+fn default_limit() -> f64 {
+    f64::INFINITY
+}
+
+fn default_max_command_interval_secs() -> f64 {
+    0.1
+}
+
+impl Default for SafeMoveBaseConfig {
+    fn default() -> Self {
+        Self {
+            max_linear_vel: default_limit(),
+            max_angular_vel: default_limit(),
+            max_linear_acc: default_limit(),
+            max_angular_acc: default_limit(),
+            max_command_interval_secs: default_max_command_interval_secs(),
+            deadman_timeout_secs: None,
+            geofence: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CommandState {
+    last_velocity: BaseVelocity,
+    last_command_time: Option<Instant>,
+}
+
+struct SafeMoveBaseInner<M>
+where
+    M: MoveBase,
+{
+    client: M,
+    config: SafeMoveBaseConfig,
+    localization: Option<Arc<dyn Localization>>,
+    state: Mutex<CommandState>,
+}
+
+/// SafeMoveBase limits the velocity and the acceleration of the base.
+///
+/// * Zero velocity is always sent as is to stop the base immediately.
+/// * If `deadman_timeout_secs` is specified, zero velocity is sent when no command arrives
+///   within the timeout.
+/// * If `geofence` is specified, velocities which make the base leave the polygon are rejected
+///   and the base is stopped. Rotation in place is always allowed, and so is the motion toward
+///   the polygon while the base is outside of it.
+pub struct SafeMoveBase<M>
+where
+    M: MoveBase + 'static,
+{
+    inner: Arc<SafeMoveBaseInner<M>>,
+}
+
+impl<M> SafeMoveBase<M>
+where
+    M: MoveBase + 'static,
+{
+    /// `localization` is required if the geofence is enabled.
+    pub fn new(
+        client: M,
+        config: SafeMoveBaseConfig,
+        localization: Option<Arc<dyn Localization>>,
+    ) -> Self {
+        let deadman_timeout_secs = config.deadman_timeout_secs;
+        let inner = Arc::new(SafeMoveBaseInner {
+            client,
+            config,
+            localization,
+            state: Mutex::new(CommandState::default()),
+        });
+        if let Some(timeout_secs) = deadman_timeout_secs {
+            let weak = Arc::downgrade(&inner);
+            std::thread::spawn(move || {
+                Self::watch_deadman(weak, Duration::from_secs_f64(timeout_secs))
+            });
+        }
+        Self { inner }
+    }
+
+    pub fn config(&self) -> &SafeMoveBaseConfig {
+        &self.inner.config
+    }
+
+    fn watch_deadman(inner: Weak<SafeMoveBaseInner<M>>, timeout: Duration) {
+        let period = (timeout / 4).min(Duration::from_millis(50));
+        loop {
+            std::thread::sleep(period);
+            // Stop watching after SafeMoveBase is dropped.
+            let inner = match inner.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+            let mut state = inner.state.lock().unwrap();
+            let is_timeout =
+                matches!(state.last_command_time, Some(time) if time.elapsed() > timeout);
+            if is_timeout && !is_zero(&state.last_velocity) {
+                warn!(
+                    "No velocity command within {:?}, stopping the base",
+                    timeout
+                );
+                match inner.client.send_velocity(&BaseVelocity::default()) {
+                    Ok(_) => state.last_velocity = BaseVelocity::default(),
+                    Err(e) => error!("Failed to stop the base: {}", e),
+                }
+            }
+        }
+    }
+
+    fn limit_velocity(&self, velocity: &BaseVelocity, state: &CommandState) -> BaseVelocity {
+        let config = &self.inner.config;
+        let dt = state
+            .last_command_time
+            .map_or(config.max_command_interval_secs, |time| {
+                time.elapsed().as_secs_f64()
+            })
+            .min(config.max_command_interval_secs);
+        let limit = |target: f64, last: f64, max_vel: f64, max_acc: f64| {
+            let target = nalgebra::clamp(target, -max_vel, max_vel);
+            last + nalgebra::clamp(target - last, -max_acc * dt, max_acc * dt)
+        };
+        BaseVelocity::new(
+            limit(
+                velocity.x,
+                state.last_velocity.x,
+                config.max_linear_vel,
+                config.max_linear_acc,
+            ),
+            limit(
+                velocity.y,
+                state.last_velocity.y,
+                config.max_linear_vel,
+                config.max_linear_acc,
+            ),
+            limit(
+                velocity.theta,
+                state.last_velocity.theta,
+                config.max_angular_vel,
+                config.max_angular_acc,
+            ),
+        )
+    }
+
+    fn check_geofence(&self, velocity: &BaseVelocity) -> Result<(), Error> {
+        let geofence = match &self.inner.config.geofence {
+            Some(geofence) => geofence,
+            None => return Ok(()),
+        };
+        if velocity.x == 0.0 && velocity.y == 0.0 {
+            return Ok(());
+        }
+        let localization =
+            self.inner
+                .localization
+                .as_ref()
+                .ok_or_else(|| Error::Uninitialized {
+                    message: "localization for geofence".to_owned(),
+                })?;
+        let pose = localization.current_pose(&geofence.frame_id)?;
+        let t = geofence.lookahead_secs;
+        let predicted_pose = pose
+            * Isometry2::new(
+                Vector2::new(velocity.x * t, velocity.y * t),
+                velocity.theta * t,
+            );
+        let (x, y) = (pose.translation.x, pose.translation.y);
+        let (next_x, next_y) = (predicted_pose.translation.x, predicted_pose.translation.y);
+        // Outside of the polygon (e.g. after the localization jumps), the base is allowed to
+        // move back toward it.
+        if geofence.contains(next_x, next_y)
+            || (!geofence.contains(x, y)
+                && geofence.distance_to_edge(next_x, next_y) < geofence.distance_to_edge(x, y))
+        {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Velocity {:?} is rejected: the base will leave the geofence (current pose = {})",
+                velocity,
+                pose
+            )
+            .into())
+        }
+    }
+}
+
+fn is_zero(velocity: &BaseVelocity) -> bool {
+    velocity.x == 0.0 && velocity.y == 0.0 && velocity.theta == 0.0
+}
+
+impl<M> MoveBase for SafeMoveBase<M>
+where
+    M: MoveBase + 'static,
+{
+    fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), Error> {
+        let mut state = self.inner.state.lock().unwrap();
+        let limited_velocity = if is_zero(velocity) {
+            *velocity
+        } else {
+            self.limit_velocity(velocity, &state)
+        };
+        debug!("limited velocity: {:?}", limited_velocity);
+        let geofence_result = self.check_geofence(&limited_velocity);
+        let sent_velocity = if geofence_result.is_ok() {
+            limited_velocity
+        } else {
+            BaseVelocity::default()
+        };
+        self.inner.client.send_velocity(&sent_velocity)?;
+        state.last_velocity = sent_velocity;
+        state.last_command_time = Some(Instant::now());
+        geofence_result
+    }
+
+    fn current_velocity(&self) -> Result<BaseVelocity, Error> {
+        self.inner.client.current_velocity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DummyLocalization, DummyMoveBase};
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_velocity_limit() {
+        let client = Arc::new(DummyMoveBase::new());
+        let base = SafeMoveBase::new(
+            client.clone(),
+            SafeMoveBaseConfig {
+                max_linear_vel: 0.5,
+                max_angular_vel: 1.0,
+                ..Default::default()
+            },
+            None,
+        );
+        base.send_velocity(&BaseVelocity::new(1.0, -0.2, -2.0))
+            .unwrap();
+        let vel = client.current_velocity().unwrap();
+        assert_approx_eq!(vel.x, 0.5);
+        assert_approx_eq!(vel.y, -0.2);
+        assert_approx_eq!(vel.theta, -1.0);
+    }
+
+    #[test]
+    fn test_acceleration_limit() {
+        let client = Arc::new(DummyMoveBase::new());
+        let base = SafeMoveBase::new(
+            client.clone(),
+            SafeMoveBaseConfig {
+                max_linear_acc: 1.0,
+                max_angular_acc: 2.0,
+                max_command_interval_secs: 0.1,
+                ..Default::default()
+            },
+            None,
+        );
+        // The first command is limited by `max_command_interval_secs`.
+        base.send_velocity(&BaseVelocity::new(1.0, 0.0, -1.0))
+            .unwrap();
+        let vel = client.current_velocity().unwrap();
+        assert_approx_eq!(vel.x, 0.1);
+        assert_approx_eq!(vel.y, 0.0);
+        assert_approx_eq!(vel.theta, -0.2);
+        std::thread::sleep(Duration::from_millis(200));
+        base.send_velocity(&BaseVelocity::new(1.0, 0.0, -1.0))
+            .unwrap();
+        let vel = client.current_velocity().unwrap();
+        assert_approx_eq!(vel.x, 0.2);
+        assert_approx_eq!(vel.theta, -0.4);
+        // Stop immediately
+        base.send_velocity(&BaseVelocity::default()).unwrap();
+        let vel = client.current_velocity().unwrap();
+        assert_approx_eq!(vel.x, 0.0);
+        assert_approx_eq!(vel.theta, 0.0);
+    }
+
+    #[test]
+    fn test_deadman() {
+        let client = Arc::new(DummyMoveBase::new());
+        let base = SafeMoveBase::new(
+            client.clone(),
+            SafeMoveBaseConfig {
+                deadman_timeout_secs: Some(0.1),
+                ..Default::default()
+            },
+            None,
+        );
+        base.send_velocity(&BaseVelocity::new(0.3, 0.0, 0.0))
+            .unwrap();
+        assert_approx_eq!(client.current_velocity().unwrap().x, 0.3);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_approx_eq!(client.current_velocity().unwrap().x, 0.0);
+    }
+
+    #[test]
+    fn test_geofence() {
+        let client = Arc::new(DummyMoveBase::new());
+        let geofence = GeofenceConfig {
+            polygon: vec![[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]],
+            frame_id: "map".to_owned(),
+            lookahead_secs: 1.0,
+        };
+        let mut localization = DummyLocalization::new();
+        localization.current_pose = Isometry2::new(Vector2::new(0.5, 0.0), 0.0);
+        let base = SafeMoveBase::new(
+            client.clone(),
+            SafeMoveBaseConfig {
+                geofence: Some(geofence.clone()),
+                ..Default::default()
+            },
+            Some(Arc::new(localization)),
+        );
+        base.send_velocity(&BaseVelocity::new(0.3, 0.0, 0.0))
+            .unwrap();
+        assert_approx_eq!(client.current_velocity().unwrap().x, 0.3);
+        // Leave the geofence within the lookahead time
+        assert!(base
+            .send_velocity(&BaseVelocity::new(0.6, 0.0, 0.0))
+            .is_err());
+        assert_approx_eq!(client.current_velocity().unwrap().x, 0.0);
+        base.send_velocity(&BaseVelocity::new(-0.6, 0.0, 0.0))
+            .unwrap();
+        assert_approx_eq!(client.current_velocity().unwrap().x, -0.6);
+        base.send_velocity(&BaseVelocity::new(0.0, 0.0, 3.0))
+            .unwrap();
+
+        // Outside of the geofence, only the motion toward it is allowed.
+        let mut localization = DummyLocalization::new();
+        localization.current_pose = Isometry2::new(Vector2::new(1.5, 0.0), 0.0);
+        let base = SafeMoveBase::new(
+            client.clone(),
+            SafeMoveBaseConfig {
+                geofence: Some(geofence.clone()),
+                ..Default::default()
+            },
+            Some(Arc::new(localization)),
+        );
+        base.send_velocity(&BaseVelocity::new(-0.3, 0.0, 0.0))
+            .unwrap();
+        assert_approx_eq!(client.current_velocity().unwrap().x, -0.3);
+        assert!(base
+            .send_velocity(&BaseVelocity::new(0.3, 0.0, 0.0))
+            .is_err());
+        assert!(base
+            .send_velocity(&BaseVelocity::new(0.0, 0.3, 0.0))
+            .is_err());
+        assert_approx_eq!(client.current_velocity().unwrap().x, 0.0);
+
+        // Geofence requires localization.
+        let base = SafeMoveBase::new(
+            client,
+            SafeMoveBaseConfig {
+                geofence: Some(geofence),
+                ..Default::default()
+            },
+            None,
+        );
+        assert!(base
+            .send_velocity(&BaseVelocity::new(0.1, 0.0, 0.0))
+            .is_err());
+    }
+}
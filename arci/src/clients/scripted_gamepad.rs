@@ -0,0 +1,402 @@
+use crate::error::Error;
+use crate::traits::gamepad::{Axis, Button, Gamepad, GamepadEvent};
+use async_trait::async_trait;
+use futures::{channel::mpsc, lock::Mutex as AsyncMutex, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// `GamepadEvent` with the time from the start of the script.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "TimedGamepadEventRepr", into = "TimedGamepadEventRepr")]
+pub struct TimedGamepadEvent {
+    pub time_secs: f64,
+    pub event: GamepadEvent,
+}
+
+/// TOML friendly representation of `TimedGamepadEvent`, e.g.
+/// `{ time_secs = 0.5, type = "AxisChanged", axis = "LeftStickY", value = 1.0 }`.
+#[derive(Serialize, Deserialize)]
+struct TimedGamepadEventRepr {
+    time_secs: f64,
+    #[serde(flatten)]
+    event: GamepadEventRepr,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum GamepadEventRepr {
+    ButtonPressed { button: Button },
+    ButtonReleased { button: Button },
+    AxisChanged { axis: Axis, value: f64 },
+    Connected,
+    Disconnected,
+    Unknown,
+}
+
+impl From<TimedGamepadEventRepr> for TimedGamepadEvent {
+    fn from(repr: TimedGamepadEventRepr) -> Self {
+        let event = match repr.event {
+            GamepadEventRepr::ButtonPressed { button } => GamepadEvent::ButtonPressed(button),
+            GamepadEventRepr::ButtonReleased { button } => GamepadEvent::ButtonReleased(button),
+            GamepadEventRepr::AxisChanged { axis, value } => GamepadEvent::AxisChanged(axis, value),
+            GamepadEventRepr::Connected => GamepadEvent::Connected,
+            GamepadEventRepr::Disconnected => GamepadEvent::Disconnected,
+            GamepadEventRepr::Unknown => GamepadEvent::Unknown,
+        };
+        Self {
+            time_secs: repr.time_secs,
+            event,
+        }
+    }
+}
+
+impl From<TimedGamepadEvent> for TimedGamepadEventRepr {
+    fn from(timed_event: TimedGamepadEvent) -> Self {
+        let event = match timed_event.event {
+            GamepadEvent::ButtonPressed(button) => GamepadEventRepr::ButtonPressed { button },
+            GamepadEvent::ButtonReleased(button) => GamepadEventRepr::ButtonReleased { button },
+            GamepadEvent::AxisChanged(axis, value) => GamepadEventRepr::AxisChanged { axis, value },
+            GamepadEvent::Connected => GamepadEventRepr::Connected,
+            GamepadEvent::Disconnected => GamepadEventRepr::Disconnected,
+            GamepadEvent::Unknown => GamepadEventRepr::Unknown,
+        };
+        Self {
+            time_secs: timed_event.time_secs,
+            event,
+        }
+    }
+}
+
+/// Timeline of `GamepadEvent`s replayed by `ScriptedGamepad`.
+///
+/// # Example
+///
+/// ```
+/// use arci::{gamepad::{Axis, Button}, GamepadScript};
+/// use std::time::Duration;
+///
+/// let script = GamepadScript::new()
+///     .press(Button::RightTrigger2)
+///     .axis(Axis::LeftStickY, 1.0)
+///     .wait(Duration::from_millis(500))
+///     .release(Button::RightTrigger2);
+/// assert_eq!(script.events.len(), 3);
+/// assert_eq!(script.events[2].time_secs, 0.5);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GamepadScript {
+    #[serde(default)]
+    pub events: Vec<TimedGamepadEvent>,
+    /// Time of the events added by the builder methods.
+    #[serde(skip)]
+    time_secs: f64,
+}
+
+impl GamepadScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the script from a TOML file.
+    pub fn try_new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {:?}: {}", path, e))?;
+        let mut script: Self =
+            toml::from_str(&s).map_err(|e| anyhow::anyhow!("failed to parse {:?}: {}", path, e))?;
+        script.time_secs = script.duration().as_secs_f64();
+        Ok(script)
+    }
+
+    /// Saves the script as a TOML file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let s = toml::to_string(self).map_err(anyhow::Error::from)?;
+        std::fs::write(path, s)
+            .map_err(|e| anyhow::anyhow!("failed to write {:?}: {}", path, e))?;
+        Ok(())
+    }
+
+    /// Time of the last event or the end of the last `wait`.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(
+            self.events
+                .iter()
+                .map(|e| e.time_secs)
+                .fold(self.time_secs, f64::max),
+        )
+    }
+
+    /// Adds `event` at the current time of the builder.
+    pub fn event(mut self, event: GamepadEvent) -> Self {
+        self.events.push(TimedGamepadEvent {
+            time_secs: self.time_secs,
+            event,
+        });
+        self
+    }
+
+    pub fn press(self, button: Button) -> Self {
+        self.event(GamepadEvent::ButtonPressed(button))
+    }
+
+    pub fn release(self, button: Button) -> Self {
+        self.event(GamepadEvent::ButtonReleased(button))
+    }
+
+    pub fn axis(self, axis: Axis, value: f64) -> Self {
+        self.event(GamepadEvent::AxisChanged(axis, value))
+    }
+
+    /// Delays the following events by `duration`.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.time_secs += duration.as_secs_f64();
+        self
+    }
+}
+
+/// `Gamepad` which replays `GamepadScript`, for Tests.
+///
+/// The timeline starts at the first call of `next_event`. `GamepadEvent::Unknown` is returned
+/// after `GamepadScript::duration`, so `openrr_teleop::ControlNodeSwitcher` stops there.
+/// The requested rumbles are recorded and can be checked by `rumbles`.
+pub struct ScriptedGamepad {
+    script: Mutex<Option<GamepadScript>>,
+    rumbles: Mutex<Vec<(f64, Duration)>>,
+    sender: Mutex<Option<mpsc::UnboundedSender<GamepadEvent>>>,
+    receiver: AsyncMutex<mpsc::UnboundedReceiver<GamepadEvent>>,
+    is_running: Arc<AtomicBool>,
+}
+
+impl ScriptedGamepad {
+    pub fn new(script: GamepadScript) -> Self {
+        let (sender, receiver) = mpsc::unbounded();
+        Self {
+            script: Mutex::new(Some(script)),
+            rumbles: Mutex::new(vec![]),
+            sender: Mutex::new(Some(sender)),
+            receiver: AsyncMutex::new(receiver),
+            is_running: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    /// Intensities and durations of the rumbles requested so far.
+    pub fn rumbles(&self) -> Vec<(f64, Duration)> {
+        self.rumbles.lock().unwrap().clone()
+    }
+
+    fn start(&self) {
+        let mut script = match self.script.lock().unwrap().take() {
+            Some(script) => script,
+            None => return,
+        };
+        let sender = self.sender.lock().unwrap().take().unwrap();
+        let is_running = self.is_running.clone();
+        let duration = script.duration();
+        script.events.sort_by(|a, b| {
+            a.time_secs
+                .partial_cmp(&b.time_secs)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        std::thread::spawn(move || {
+            const CHECK_UNIT: Duration = Duration::from_millis(10);
+            let start = Instant::now();
+            let sleep_until = |time: Instant| loop {
+                if !is_running.load(Ordering::Relaxed) {
+                    return false;
+                }
+                let now = Instant::now();
+                if now >= time {
+                    return true;
+                }
+                std::thread::sleep(CHECK_UNIT.min(time - now));
+            };
+            for timed_event in script.events {
+                if !sleep_until(start + Duration::from_secs_f64(timed_event.time_secs))
+                    || sender.unbounded_send(timed_event.event).is_err()
+                {
+                    return;
+                }
+            }
+            // Keeps the last state until the end of the last `wait`.
+            sleep_until(start + duration);
+        });
+    }
+}
+
+#[async_trait]
+impl Gamepad for ScriptedGamepad {
+    async fn next_event(&self) -> GamepadEvent {
+        self.start();
+        self.receiver
+            .lock()
+            .await
+            .next()
+            .await
+            .unwrap_or(GamepadEvent::Unknown)
+    }
+
+    fn stop(&self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+
+    fn rumble(&self, intensity: f64, duration: Duration) {
+        self.rumbles.lock().unwrap().push((intensity, duration));
+    }
+}
+
+/// `Gamepad` which records the events of another `Gamepad` as `GamepadScript`.
+pub struct GamepadRecorder<G: Gamepad> {
+    gamepad: G,
+    start: Instant,
+    script: Mutex<GamepadScript>,
+}
+
+impl<G: Gamepad> GamepadRecorder<G> {
+    pub fn new(gamepad: G) -> Self {
+        Self {
+            gamepad,
+            start: Instant::now(),
+            script: Mutex::new(GamepadScript::new()),
+        }
+    }
+
+    /// Events recorded so far, with the time from the creation of the recorder.
+    pub fn script(&self) -> GamepadScript {
+        self.script.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl<G: Gamepad> Gamepad for GamepadRecorder<G> {
+    async fn next_event(&self) -> GamepadEvent {
+        let event = self.gamepad.next_event().await;
+        self.script.lock().unwrap().events.push(TimedGamepadEvent {
+            time_secs: self.start.elapsed().as_secs_f64(),
+            event: event.clone(),
+        });
+        event
+    }
+
+    fn stop(&self) {
+        self.gamepad.stop();
+    }
+
+    fn rumble(&self, intensity: f64, duration: Duration) {
+        self.gamepad.rumble(intensity, duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script() -> GamepadScript {
+        GamepadScript::new()
+            .press(Button::RightTrigger2)
+            .wait(Duration::from_millis(50))
+            .axis(Axis::LeftStickY, 0.5)
+            .release(Button::RightTrigger2)
+            .wait(Duration::from_millis(50))
+    }
+
+    #[test]
+    fn test_builder() {
+        let script = script();
+        assert_eq!(script.events.len(), 3);
+        assert_eq!(script.events[0].time_secs, 0.0);
+        assert_eq!(script.events[1].time_secs, 0.05);
+        assert_eq!(script.events[2].time_secs, 0.05);
+        assert_eq!(script.duration(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("arci_gamepad_script_{}.toml", std::process::id()));
+        let script = script().event(GamepadEvent::Unknown);
+        script.save(&path).unwrap();
+        let loaded = GamepadScript::try_new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.events, script.events);
+        assert_eq!(loaded.duration(), Duration::from_millis(100));
+
+        let loaded: GamepadScript = toml::from_str(
+            r#"
+[[events]]
+time_secs = 0.5
+type = "AxisChanged"
+axis = "RightStickX"
+value = -1.0
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            loaded.events,
+            vec![TimedGamepadEvent {
+                time_secs: 0.5,
+                event: GamepadEvent::AxisChanged(Axis::RightStickX, -1.0)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_replay_and_record() {
+        let recorder = GamepadRecorder::new(ScriptedGamepad::new(script()));
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            assert_eq!(
+                recorder.next_event().await,
+                GamepadEvent::ButtonPressed(Button::RightTrigger2)
+            );
+            assert_eq!(
+                recorder.next_event().await,
+                GamepadEvent::AxisChanged(Axis::LeftStickY, 0.5)
+            );
+            assert!(start.elapsed() >= Duration::from_millis(50));
+            assert_eq!(
+                recorder.next_event().await,
+                GamepadEvent::ButtonReleased(Button::RightTrigger2)
+            );
+            assert_eq!(recorder.next_event().await, GamepadEvent::Unknown);
+            assert!(start.elapsed() >= Duration::from_millis(100));
+            assert_eq!(recorder.next_event().await, GamepadEvent::Unknown);
+        });
+        let recorded = recorder.script();
+        assert_eq!(recorded.events.len(), 5);
+        assert_eq!(
+            recorded.events[1].event,
+            GamepadEvent::AxisChanged(Axis::LeftStickY, 0.5)
+        );
+        assert!(recorded.events[1].time_secs >= 0.05);
+
+        recorder.rumble(0.5, Duration::from_millis(100));
+        assert_eq!(
+            recorder.gamepad.rumbles(),
+            vec![(0.5, Duration::from_millis(100))]
+        );
+    }
+
+    #[test]
+    fn test_stop() {
+        let gamepad = ScriptedGamepad::new(
+            GamepadScript::new()
+                .wait(Duration::from_secs(10))
+                .press(Button::South),
+        );
+        gamepad.stop();
+        let start = Instant::now();
+        assert_eq!(
+            tokio_test::block_on(gamepad.next_event()),
+            GamepadEvent::Unknown
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,378 @@
+use crate::error::Error;
+use crate::traits::TransformResolver;
+use nalgebra::{Isometry3, Translation3};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::RwLock,
+    time::{Duration, SystemTime},
+};
+
+#[derive(Debug)]
+struct TransformHistory {
+    parent: String,
+    is_static: bool,
+    /// Sorted by the time.
+    samples: VecDeque<(SystemTime, Isometry3<f64>)>,
+}
+
+impl TransformHistory {
+    fn lookup(&self, time: SystemTime, extrapolation_limit: Duration) -> Option<Isometry3<f64>> {
+        let (first_time, first) = self.samples.front()?;
+        let (last_time, last) = self.samples.back()?;
+        if self.is_static || time == SystemTime::UNIX_EPOCH {
+            return Some(*last);
+        }
+        if time <= *first_time {
+            return if first_time.duration_since(time).ok()? <= extrapolation_limit {
+                Some(*first)
+            } else {
+                None
+            };
+        }
+        if time >= *last_time {
+            return if time.duration_since(*last_time).ok()? <= extrapolation_limit {
+                Some(*last)
+            } else {
+                None
+            };
+        }
+        let index = self.samples.iter().position(|(t, _)| *t >= time)?;
+        let (time0, transform0) = &self.samples[index - 1];
+        let (time1, transform1) = &self.samples[index];
+        let ratio = time.duration_since(*time0).ok()?.as_secs_f64()
+            / time1.duration_since(*time0).ok()?.as_secs_f64();
+        Some(interpolate(transform0, transform1, ratio))
+    }
+}
+
+fn interpolate(a: &Isometry3<f64>, b: &Isometry3<f64>, ratio: f64) -> Isometry3<f64> {
+    Isometry3::from_parts(
+        Translation3::from(a.translation.vector.lerp(&b.translation.vector, ratio)),
+        a.rotation.slerp(&b.rotation, ratio),
+    )
+}
+
+/// TransformBuffer is a transform tree which stores timestamped and static transforms
+/// between frames, like the buffer of tf2.
+///
+/// Each frame has at most one parent. Transforms are interpolated linearly (slerp for rotation)
+/// between the stored samples, and samples older than `cache_duration` from the latest one are
+/// discarded. `SystemTime::UNIX_EPOCH` means the latest transform.
+#[derive(Debug)]
+pub struct TransformBuffer {
+    cache_duration: Duration,
+    extrapolation_limit: Duration,
+    frames: RwLock<HashMap<String, TransformHistory>>,
+}
+
+impl TransformBuffer {
+    /// `extrapolation_limit` is the allowable time difference to the oldest or the latest sample.
+    /// The nearest sample is returned within the limit.
+    pub fn new(cache_duration: Duration, extrapolation_limit: Duration) -> Self {
+        Self {
+            cache_duration,
+            extrapolation_limit,
+            frames: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Set the transform of `child` frame in `parent` frame at `time`.
+    pub fn set_transform(
+        &self,
+        parent: &str,
+        child: &str,
+        transform: Isometry3<f64>,
+        time: SystemTime,
+    ) -> Result<(), Error> {
+        self.insert(parent, child, transform, time, false)
+    }
+
+    /// Set the transform of `child` frame in `parent` frame which does not change over time.
+    pub fn set_static_transform(
+        &self,
+        parent: &str,
+        child: &str,
+        transform: Isometry3<f64>,
+    ) -> Result<(), Error> {
+        self.insert(parent, child, transform, SystemTime::UNIX_EPOCH, true)
+    }
+
+    /// Returns names of all frames which are known to this buffer.
+    pub fn frame_names(&self) -> Vec<String> {
+        let frames = self.frames.read().unwrap();
+        let mut names = frames
+            .iter()
+            .flat_map(|(child, history)| vec![child.clone(), history.parent.clone()])
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Returns the parent of `frame`, or `None` if `frame` is a root or does not exist.
+    pub fn parent(&self, frame: &str) -> Option<String> {
+        self.frames
+            .read()
+            .unwrap()
+            .get(frame)
+            .map(|history| history.parent.clone())
+    }
+
+    fn insert(
+        &self,
+        parent: &str,
+        child: &str,
+        transform: Isometry3<f64>,
+        time: SystemTime,
+        is_static: bool,
+    ) -> Result<(), Error> {
+        let mut frames = self.frames.write().unwrap();
+        if Self::ancestors(&frames, parent).iter().any(|f| f == child) {
+            return Err(anyhow::anyhow!(
+                "Setting {} as the parent of {} makes a loop",
+                parent,
+                child
+            )
+            .into());
+        }
+        let history = frames
+            .entry(child.to_owned())
+            .or_insert_with(|| TransformHistory {
+                parent: parent.to_owned(),
+                is_static,
+                samples: VecDeque::new(),
+            });
+        if history.parent != parent || history.is_static != is_static {
+            history.parent = parent.to_owned();
+            history.is_static = is_static;
+            history.samples.clear();
+        }
+        if is_static {
+            history.samples.clear();
+            history.samples.push_back((time, transform));
+            return Ok(());
+        }
+        let index = history
+            .samples
+            .iter()
+            .rposition(|(t, _)| *t <= time)
+            .map_or(0, |i| i + 1);
+        history.samples.insert(index, (time, transform));
+        let latest_time = history.samples.back().unwrap().0;
+        while history.samples.len() > 1 {
+            match latest_time.duration_since(history.samples.front().unwrap().0) {
+                Ok(age) if age > self.cache_duration => {
+                    history.samples.pop_front();
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns `frame` and its ancestors in order.
+    fn ancestors(frames: &HashMap<String, TransformHistory>, frame: &str) -> Vec<String> {
+        let mut ancestors = vec![frame.to_owned()];
+        let mut current = frame;
+        while let Some(history) = frames.get(current) {
+            current = &history.parent;
+            ancestors.push(current.to_owned());
+        }
+        ancestors
+    }
+
+    /// Returns the transform of `frame` in `ancestor` frame.
+    fn transform_from_ancestor(
+        &self,
+        frames: &HashMap<String, TransformHistory>,
+        ancestor: &str,
+        frame: &str,
+        time: SystemTime,
+    ) -> Result<Isometry3<f64>, Error> {
+        let mut transform = Isometry3::identity();
+        let mut current = frame;
+        while current != ancestor {
+            let history = &frames[current];
+            let parent_to_current =
+                history
+                    .lookup(time, self.extrapolation_limit)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Transform from {} to {} is not available at {:?}",
+                            history.parent,
+                            current,
+                            time
+                        )
+                    })?;
+            transform = parent_to_current * transform;
+            current = &history.parent;
+        }
+        Ok(transform)
+    }
+}
+
+impl TransformResolver for TransformBuffer {
+    /// Returns the transform of `to` frame in `from` frame.
+    fn resolve_transformation(
+        &self,
+        from: &str,
+        to: &str,
+        time: SystemTime,
+    ) -> Result<Isometry3<f64>, Error> {
+        let frames = self.frames.read().unwrap();
+        let is_known =
+            |frame: &str| frames.contains_key(frame) || frames.values().any(|h| h.parent == frame);
+        for frame in &[from, to] {
+            if !is_known(frame) {
+                return Err(anyhow::anyhow!("Frame {} does not exist", frame).into());
+            }
+        }
+        let from_ancestors = Self::ancestors(&frames, from);
+        let common_ancestor = Self::ancestors(&frames, to)
+            .into_iter()
+            .find(|frame| from_ancestors.contains(frame))
+            .ok_or_else(|| anyhow::anyhow!("Frame {} and {} are not connected", from, to))?;
+        let ancestor_to_from =
+            self.transform_from_ancestor(&frames, &common_ancestor, from, time)?;
+        let ancestor_to_to = self.transform_from_ancestor(&frames, &common_ancestor, to, time)?;
+        Ok(ancestor_to_from.inverse() * ancestor_to_to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use nalgebra::Vector3;
+
+    fn translation(x: f64, y: f64, z: f64) -> Isometry3<f64> {
+        Isometry3::translation(x, y, z)
+    }
+
+    #[test]
+    fn test_tree() {
+        let buffer = TransformBuffer::new(Duration::from_secs(10), Duration::from_secs(0));
+        let time = SystemTime::now();
+        buffer
+            .set_static_transform("base", "arm", translation(0.0, 0.0, 1.0))
+            .unwrap();
+        buffer
+            .set_transform(
+                "arm",
+                "hand",
+                Isometry3::new(
+                    Vector3::new(0.5, 0.0, 0.0),
+                    Vector3::new(0.0, 0.0, std::f64::consts::FRAC_PI_2),
+                ),
+                time,
+            )
+            .unwrap();
+        buffer
+            .set_static_transform("base", "camera", translation(0.1, 0.0, 1.5))
+            .unwrap();
+        buffer
+            .set_transform("map", "base", translation(1.0, 2.0, 0.0), time)
+            .unwrap();
+        assert_eq!(
+            buffer.frame_names(),
+            vec!["arm", "base", "camera", "hand", "map"]
+        );
+        assert_eq!(buffer.parent("hand"), Some("arm".to_owned()));
+        assert_eq!(buffer.parent("map"), None);
+
+        let t = buffer.resolve_transformation("map", "hand", time).unwrap();
+        assert_approx_eq!(t.translation.x, 1.5);
+        assert_approx_eq!(t.translation.y, 2.0);
+        assert_approx_eq!(t.translation.z, 1.0);
+        let t = buffer
+            .resolve_transformation("camera", "hand", time)
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 0.4);
+        assert_approx_eq!(t.translation.z, -0.5);
+        let t = buffer
+            .resolve_transformation("hand", "camera", time)
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 0.0);
+        assert_approx_eq!(t.translation.y, 0.4);
+        assert_approx_eq!(t.translation.z, 0.5);
+        let t = buffer.resolve_transformation("hand", "hand", time).unwrap();
+        assert_approx_eq!(t.translation.vector.norm(), 0.0);
+
+        assert!(buffer
+            .resolve_transformation("map", "unknown", time)
+            .is_err());
+        buffer
+            .set_static_transform("world", "other", translation(0.0, 0.0, 0.0))
+            .unwrap();
+        assert!(buffer.resolve_transformation("map", "other", time).is_err());
+        // Loop
+        assert!(buffer
+            .set_static_transform("hand", "base", translation(0.0, 0.0, 0.0))
+            .is_err());
+        assert!(buffer
+            .set_static_transform("hand", "hand", translation(0.0, 0.0, 0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let buffer = TransformBuffer::new(Duration::from_secs(10), Duration::from_millis(100));
+        let time = SystemTime::now();
+        buffer
+            .set_transform("map", "base", translation(0.0, 0.0, 0.0), time)
+            .unwrap();
+        buffer
+            .set_transform(
+                "map",
+                "base",
+                Isometry3::new(Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0)),
+                time + Duration::from_secs(2),
+            )
+            .unwrap();
+        let t = buffer
+            .resolve_transformation("map", "base", time + Duration::from_millis(500))
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 0.5);
+        assert_approx_eq!(t.rotation.angle(), 0.25);
+        // Latest
+        let t = buffer
+            .resolve_transformation("map", "base", SystemTime::UNIX_EPOCH)
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 2.0);
+        // Within the extrapolation limit
+        let t = buffer
+            .resolve_transformation("map", "base", time - Duration::from_millis(50))
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 0.0);
+        // Extrapolation
+        assert!(buffer
+            .resolve_transformation("map", "base", time + Duration::from_secs(3))
+            .is_err());
+        assert!(buffer
+            .resolve_transformation("map", "base", time - Duration::from_secs(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_cache_duration() {
+        let buffer = TransformBuffer::new(Duration::from_secs(1), Duration::from_secs(0));
+        let time = SystemTime::now();
+        for i in 0..5 {
+            buffer
+                .set_transform(
+                    "map",
+                    "base",
+                    translation(i as f64, 0.0, 0.0),
+                    time + Duration::from_millis(500 * i),
+                )
+                .unwrap();
+        }
+        assert!(buffer
+            .resolve_transformation("map", "base", time + Duration::from_millis(500))
+            .is_err());
+        let t = buffer
+            .resolve_transformation("map", "base", time + Duration::from_millis(1250))
+            .unwrap();
+        assert_approx_eq!(t.translation.x, 2.5);
+    }
+}
@@ -3,6 +3,7 @@ mod joint_trajectory_client;
 mod localization;
 mod move_base;
 mod navigation;
+mod robot_status;
 mod speaker;
 mod transform_resolver;
 
@@ -11,5 +12,6 @@ pub use joint_trajectory_client::*;
 pub use localization::*;
 pub use move_base::*;
 pub use navigation::*;
+pub use robot_status::*;
 pub use speaker::*;
 pub use transform_resolver::*;
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use auto_impl::auto_impl;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Button {
@@ -34,14 +35,26 @@ pub enum Axis {
     RightTrigger,
     DPadX,
     DPadY,
+    /// Translation of 6DoF devices such as SpaceMouse.
+    LinearX,
+    LinearY,
+    LinearZ,
+    /// Rotation of 6DoF devices such as SpaceMouse.
+    AngularX,
+    AngularY,
+    AngularZ,
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum GamepadEvent {
     ButtonPressed(Button),
     ButtonReleased(Button),
     AxisChanged(Axis, f64),
+    /// The gamepad is connected (again).
+    Connected,
+    /// The gamepad is disconnected. The buttons and axes should be considered released.
+    Disconnected,
     Unknown,
 }
 
@@ -50,4 +63,7 @@ pub enum GamepadEvent {
 pub trait Gamepad: Send + Sync {
     async fn next_event(&self) -> GamepadEvent;
     fn stop(&self);
+    /// Vibrates the gamepad with `intensity` (0.0 to 1.0) for `duration`, replacing the ongoing
+    /// vibration. Does nothing if the gamepad doesn't support force feedback.
+    fn rumble(&self, _intensity: f64, _duration: Duration) {}
 }
@@ -2,7 +2,7 @@ use crate::error::Error;
 use auto_impl::auto_impl;
 use nalgebra::Isometry2;
 
-#[auto_impl(Box, Arc)]
+#[auto_impl(&, Box, Arc)]
 pub trait Localization: Send + Sync {
     fn current_pose(&self, frame_id: &str) -> Result<Isometry2<f64>, Error>;
 }
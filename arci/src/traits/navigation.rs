@@ -2,6 +2,98 @@ use crate::error::Error;
 use crate::Isometry2;
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+use serde::{Deserialize, Serialize};
+
+/// Status of the current (or the last) navigation goal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NavigationStatus {
+    /// No goal has been sent.
+    Idle,
+    /// The goal has been sent but not yet processed.
+    Pending,
+    /// The robot is moving to the goal.
+    Active,
+    Succeeded,
+    Aborted,
+    Canceled,
+}
+
+impl NavigationStatus {
+    /// Returns true if the goal is finished (succeeded, aborted or canceled).
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            NavigationStatus::Succeeded | NavigationStatus::Aborted | NavigationStatus::Canceled
+        )
+    }
+}
+
+/// Progress of the current navigation goal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavigationFeedback {
+    pub current_pose: Isometry2<f64>,
+    /// Straight line distance from the current pose to the goal in meters.
+    pub distance_remaining: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaypointTolerance {
+    /// Allowable distance in meters.
+    pub translation: f64,
+    /// Allowable yaw angle error in radians.
+    pub rotation: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    pub pose: Isometry2<f64>,
+    /// If `Some`, the robot goes to the next waypoint without stopping once it
+    /// enters the tolerance. If `None`, it waits until the goal succeeds.
+    pub tolerance: Option<WaypointTolerance>,
+}
+
+impl Waypoint {
+    pub fn new(pose: Isometry2<f64>) -> Self {
+        Self {
+            pose,
+            tolerance: None,
+        }
+    }
+
+    pub fn with_tolerance(pose: Isometry2<f64>, translation: f64, rotation: f64) -> Self {
+        Self {
+            pose,
+            tolerance: Some(WaypointTolerance {
+                translation,
+                rotation,
+            }),
+        }
+    }
+
+    /// Returns true if `pose` is within the tolerance of this waypoint.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arci::{Isometry2, Waypoint};
+    /// use nalgebra::Vector2;
+    ///
+    /// let waypoint = Waypoint::with_tolerance(Isometry2::new(Vector2::new(1.0, 0.0), 0.0), 0.1, 0.2);
+    /// assert!(waypoint.is_reached(&Isometry2::new(Vector2::new(0.95, 0.0), 0.1)));
+    /// assert!(!waypoint.is_reached(&Isometry2::new(Vector2::new(0.8, 0.0), 0.0)));
+    /// assert!(!waypoint.is_reached(&Isometry2::new(Vector2::new(1.0, 0.0), 0.3)));
+    /// ```
+    pub fn is_reached(&self, pose: &Isometry2<f64>) -> bool {
+        match self.tolerance {
+            Some(tolerance) => {
+                let diff = self.pose.inverse() * pose;
+                diff.translation.vector.norm() <= tolerance.translation
+                    && diff.rotation.angle().abs() <= tolerance.rotation
+            }
+            None => false,
+        }
+    }
+}
 
 #[async_trait]
 #[auto_impl(Box, Arc)]
@@ -14,4 +106,28 @@ pub trait Navigation: Send + Sync {
     ) -> Result<(), Error>;
 
     fn cancel(&self) -> Result<(), Error>;
+
+    fn status(&self) -> Result<NavigationStatus, Error>;
+
+    /// Returns `None` if there is no active goal.
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, Error>;
+
+    /// Plan a path from the current pose to the goal without executing it.
+    fn plan_path(&self, goal: Isometry2<f64>, frame_id: &str)
+        -> Result<Vec<Isometry2<f64>>, Error>;
+
+    /// Visit the waypoints in order. `timeout` is applied to each waypoint.
+    ///
+    /// The default implementation ignores the tolerance and calls `move_to` for each waypoint.
+    async fn follow_waypoints(
+        &self,
+        waypoints: &[Waypoint],
+        frame_id: &str,
+        timeout: std::time::Duration,
+    ) -> Result<(), Error> {
+        for waypoint in waypoints {
+            self.move_to(waypoint.pose, frame_id, timeout).await?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,73 @@
+use crate::error::Error;
+use auto_impl::auto_impl;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatteryState {
+    /// Charge percentage on 0 to 1 range. `None` if unmeasured.
+    pub percentage: Option<f64>,
+    /// Voltage in Volts. `None` if unmeasured.
+    pub voltage: Option<f64>,
+    pub is_charging: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FaultLevel {
+    Warn,
+    Error,
+    Stale,
+}
+
+/// Fault of a motor, a driver or any other component of the robot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fault {
+    pub name: String,
+    pub level: FaultLevel,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControllerMode {
+    #[default]
+    Unknown,
+    Idle,
+    Manual,
+    Autonomous,
+    Other(String),
+}
+
+impl std::str::FromStr for ControllerMode {
+    type Err = std::convert::Infallible;
+
+    /// Parse controller mode (case insensitive).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use arci::ControllerMode;
+    ///
+    /// assert_eq!("manual".parse::<ControllerMode>().unwrap(), ControllerMode::Manual);
+    /// assert_eq!("Auto".parse::<ControllerMode>().unwrap(), ControllerMode::Autonomous);
+    /// assert_eq!(
+    ///     "docking".parse::<ControllerMode>().unwrap(),
+    ///     ControllerMode::Other("docking".to_owned())
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "" | "unknown" => ControllerMode::Unknown,
+            "idle" => ControllerMode::Idle,
+            "manual" | "teleop" => ControllerMode::Manual,
+            "auto" | "autonomous" => ControllerMode::Autonomous,
+            _ => ControllerMode::Other(s.to_owned()),
+        })
+    }
+}
+
+#[auto_impl(Box, Arc)]
+pub trait RobotStatus: Send + Sync {
+    fn battery_state(&self) -> Result<BatteryState, Error>;
+    fn is_emergency_stopped(&self) -> Result<bool, Error>;
+    fn faults(&self) -> Result<Vec<Fault>, Error>;
+    fn controller_mode(&self) -> Result<ControllerMode, Error>;
+}
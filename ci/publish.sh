@@ -16,11 +16,19 @@ MEMBERS=(
     # depend on arci and openrr-client
     "openrr-command"
     "openrr-gui"
+
+    # depend on arci and openrr-client
+    "openrr-behavior-tree"
+
+    # depend on arci, openrr-client and openrr-behavior-tree
     "openrr-teleop"
 
     # depend on arci and some openrr-* crates
     "arci-gamepad-gilrs"
+    "arci-gamepad-keyboard"
+    "arci-remote"
     "arci-ros"
+    "arci-ros2"
     "arci-speak-audio"
     "arci-speak-cmd"
     "arci-urdf-viz"
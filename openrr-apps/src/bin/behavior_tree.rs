@@ -0,0 +1,44 @@
+use openrr_apps::{Error, RobotConfig};
+use openrr_behavior_tree::{BehaviorTree, BehaviorTreeConfig};
+use openrr_client::ArcRobotClient;
+use std::{path::PathBuf, sync::Arc};
+use structopt::StructOpt;
+use tracing::{info, warn};
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = env!("CARGO_BIN_NAME"),
+    about = "Runs a behavior tree. Ctrl-C cancels the tree."
+)]
+struct BehaviorTreeArgs {
+    #[structopt(short, long, parse(from_os_str))]
+    config_path: Option<PathBuf>,
+    /// Path to the tree definition.
+    #[structopt(parse(from_os_str))]
+    tree_path: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    tracing_subscriber::fmt::init();
+    let args = BehaviorTreeArgs::from_args();
+    info!("ParsedArgs {:?}", args);
+    let config_path =
+        openrr_apps::utils::get_apps_robot_config(args.config_path).ok_or(Error::NoConfigPath)?;
+    let tree_config = BehaviorTreeConfig::try_new(&args.tree_path)?;
+    let robot_config = RobotConfig::try_new(config_path)?;
+    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &robot_config)?;
+    let client: Arc<ArcRobotClient> = Arc::new(robot_config.create_robot_client()?);
+    let tree = Arc::new(BehaviorTree::new_from_config(tree_config, client)?);
+
+    let tree_cloned = tree.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            warn!("Canceling the tree");
+            tree_cloned.cancel();
+        }
+    });
+    let status = tree.run().await?;
+    info!("Result: {:?}", status);
+    Ok(())
+}
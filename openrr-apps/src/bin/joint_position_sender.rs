@@ -20,7 +20,7 @@ fn main() -> anyhow::Result<()> {
     debug!("opt: {:?}", opt);
     let config_path = openrr_apps::utils::get_apps_robot_config(opt.config_path).unwrap();
     let config = RobotConfig::try_new(&config_path)?;
-    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &config);
+    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &config)?;
     let client: BoxRobotClient = config.create_robot_client()?;
     joint_position_sender(
         client,
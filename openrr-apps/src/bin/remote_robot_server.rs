@@ -0,0 +1,55 @@
+use arci_remote::RemoteRobotServer;
+use openrr_apps::{Error, RobotConfig};
+use openrr_client::ArcRobotClient;
+use std::{net::SocketAddr, path::PathBuf};
+use structopt::StructOpt;
+use tracing::info;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = env!("CARGO_BIN_NAME"),
+    about = "A server to use the robot from another computer."
+)]
+struct RemoteRobotServerArgs {
+    #[structopt(short, long, parse(from_os_str))]
+    config_path: Option<PathBuf>,
+    /// Address to listen on. The server has no authentication, so it only accepts local
+    /// connections by default. Use e.g. `0.0.0.0:8888` only in a trusted network.
+    #[structopt(short, long, default_value = "127.0.0.1:8888")]
+    address: SocketAddr,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    tracing_subscriber::fmt::init();
+    let args = RemoteRobotServerArgs::from_args();
+    info!("ParsedArgs {:?}", args);
+    let config_path =
+        openrr_apps::utils::get_apps_robot_config(args.config_path).ok_or(Error::NoConfigPath)?;
+    let robot_config = RobotConfig::try_new(config_path)?;
+    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &robot_config)?;
+    let client: ArcRobotClient = robot_config.create_robot_client()?;
+
+    // Only the raw clients are exposed, the clients of the remote side check collisions
+    // and solve IK using their own config.
+    let joint_trajectory_clients = client
+        .joint_trajectory_clients()
+        .iter()
+        .filter(|(name, _)| client.is_raw_joint_trajectory_client(name))
+        .map(|(name, client)| (name.clone(), client.clone()))
+        .collect();
+    let server = RemoteRobotServer::new(
+        joint_trajectory_clients,
+        client.speakers().clone(),
+        client.localization().clone(),
+        client.move_base().clone(),
+        client.navigation().clone(),
+        client.robot_status().clone(),
+    )
+    .spawn(args.address)?;
+
+    tokio::signal::ctrl_c().await.unwrap();
+    info!("Stopping the server");
+    server.stop();
+    Ok(())
+}
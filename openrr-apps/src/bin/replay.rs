@@ -0,0 +1,89 @@
+use arci::MoveBase;
+use openrr_apps::{Error, RobotConfig};
+use openrr_client::{load_records, ArcRobotClient, Replayer};
+use std::{collections::HashMap, path::PathBuf};
+use structopt::StructOpt;
+use tracing::info;
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if speed > 0.0 {
+        Ok(speed)
+    } else {
+        Err(format!("speed must be positive but {}", speed))
+    }
+}
+
+fn parse_map(s: &str) -> Result<(String, String), String> {
+    let pos = s
+        .find('=')
+        .ok_or_else(|| format!("invalid RECORDED=ACTUAL: no `=` found in `{}`", s))?;
+    Ok((s[..pos].to_owned(), s[pos + 1..].to_owned()))
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = env!("CARGO_BIN_NAME"),
+    about = "Replays the commands recorded by `recorder_config`."
+)]
+struct ReplayArgs {
+    #[structopt(short, long, parse(from_os_str))]
+    config_path: Option<PathBuf>,
+    /// Path to the records.
+    #[structopt(parse(from_os_str))]
+    record_path: PathBuf,
+    /// Time scale of the replay, e.g. 2.0 replays twice as fast.
+    #[structopt(short, long, default_value = "1.0", parse(try_from_str = parse_speed))]
+    speed: f64,
+    /// Replays the commands of the recorded client with another client (RECORDED=ACTUAL).
+    #[structopt(short, long, parse(try_from_str = parse_map), number_of_values = 1)]
+    map: Vec<(String, String)>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    tracing_subscriber::fmt::init();
+    let args = ReplayArgs::from_args();
+    info!("ParsedArgs {:?}", args);
+    let config_path =
+        openrr_apps::utils::get_apps_robot_config(args.config_path).ok_or(Error::NoConfigPath)?;
+    // Load the records first, the recorder may write to the same file.
+    let replayer = Replayer::try_new(load_records(&args.record_path)?, args.speed)?;
+    let mut robot_config = RobotConfig::try_new(config_path)?;
+    robot_config.recorder_config = None;
+    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &robot_config)?;
+    let client: ArcRobotClient = robot_config.create_robot_client()?;
+
+    let map: HashMap<_, _> = args.map.into_iter().collect();
+    let mut joint_trajectory_clients = HashMap::new();
+    for (recorded, actual) in &map {
+        let actual_client = client
+            .joint_trajectory_clients()
+            .get(actual)
+            .ok_or_else(|| openrr_client::Error::NoJointTrajectoryClient(actual.to_owned()))?;
+        joint_trajectory_clients.insert(recorded.to_owned(), actual_client.clone());
+    }
+    for (name, joint_trajectory_client) in client.joint_trajectory_clients() {
+        if !map.contains_key(name) {
+            joint_trajectory_clients
+                .entry(name.to_owned())
+                .or_insert_with(|| joint_trajectory_client.clone());
+        }
+    }
+
+    info!(
+        "Replaying {:?} ({:?})",
+        args.record_path,
+        replayer.duration()
+    );
+    replayer
+        .replay(
+            &joint_trajectory_clients,
+            client
+                .move_base()
+                .as_ref()
+                .map(|m| m.as_ref() as &dyn MoveBase),
+        )
+        .await?;
+    Ok(())
+}
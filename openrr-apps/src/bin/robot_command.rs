@@ -1,7 +1,7 @@
 use crate::Error as OpenrrAppsError;
 use openrr_apps::{Error, RobotConfig};
-use openrr_command::{RobotCommand, RobotCommandExecutor};
-use std::path::PathBuf;
+use openrr_command::{RobotCommand, RobotCommandExecutor, RobotShell};
+use std::{path::PathBuf, sync::Arc};
 use structopt::StructOpt;
 use tracing::info;
 
@@ -13,8 +13,20 @@ use tracing::info;
 struct RobotCommandArgs {
     #[structopt(short, long, parse(from_os_str))]
     config_path: Option<PathBuf>,
+    /// Path to the history of the interactive shell.
+    #[structopt(long, parse(from_os_str))]
+    history_path: Option<PathBuf>,
+    /// Starts the interactive shell if no command is given.
     #[structopt(subcommand)]
-    command: RobotCommand,
+    command: Option<RobotCommand>,
+}
+
+/// `~/.openrr_robot_command_history` by default.
+fn history_path(history_path: Option<PathBuf>) -> Option<PathBuf> {
+    history_path.or_else(|| {
+        std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".openrr_robot_command_history"))
+    })
 }
 
 #[tokio::main]
@@ -24,10 +36,22 @@ async fn main() -> Result<(), Error> {
     info!("ParsedArgs {:?}", args);
     if let Some(config_path) = openrr_apps::utils::get_apps_robot_config(args.config_path) {
         let robot_config = RobotConfig::try_new(config_path)?;
-        openrr_apps::utils::init_with_anonymize(env!("CARGO_BIN_NAME"), &robot_config);
-        let client = robot_config.create_robot_client()?;
-        let executor = RobotCommandExecutor {};
-        Ok(executor.execute(&client, &args.command).await?)
+        openrr_apps::utils::init_with_anonymize(env!("CARGO_BIN_NAME"), &robot_config)?;
+        let client = Arc::new(robot_config.create_robot_client()?);
+        match &args.command {
+            Some(command) => {
+                let executor = RobotCommandExecutor {};
+                Ok(executor.execute(&client, command).await?)
+            }
+            None => {
+                let mut shell = RobotShell::new(client);
+                if let Some(path) = history_path(args.history_path) {
+                    shell = shell.with_history_path(path);
+                }
+                shell.run().await;
+                Ok(())
+            }
+        }
     } else {
         Err(OpenrrAppsError::NoConfigPath)
     }
@@ -1,12 +1,28 @@
+use arci::{Gamepad, GamepadRecorder, GamepadScript, MergedGamepad, ScriptedGamepad, Speaker};
 use arci_gamepad_gilrs::GilGamepad;
-use openrr_apps::{Error, RobotConfig, RobotTeleopConfig};
+use arci_gamepad_keyboard::KeyboardGamepad;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    execute, queue,
+    style::Print,
+    terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use openrr_apps::{Error, RobotConfig, RobotTeleopConfig, TeleopGamepad};
 use openrr_client::ArcRobotClient;
-use openrr_teleop::ControlNodeSwitcher;
+use openrr_teleop::{
+    BehaviorTreeNode, ControlNode, ControlNodeSwitcher, RobotStatusAlert, TrajectoryPlaybackNode,
+};
 #[cfg(feature = "ros")]
 use std::thread;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use structopt::StructOpt;
-use tracing::info;
+use tracing::{error, info, warn};
+use tracing_subscriber::EnvFilter;
 
 #[derive(StructOpt, Debug)]
 #[structopt(
@@ -16,30 +32,73 @@ use tracing::info;
 pub struct RobotTeleopArgs {
     #[structopt(short, long, parse(from_os_str))]
     config_path: PathBuf,
+    /// Replays the gamepad events in the file instead of the gamepad.
+    #[structopt(long, parse(from_os_str))]
+    gamepad_script_path: Option<PathBuf>,
+    /// Records the gamepad events to the file, which can be replayed by `--gamepad-script-path`.
+    #[structopt(long, parse(from_os_str))]
+    record_gamepad_path: Option<PathBuf>,
+    /// Shows the modes, the joint positions, the commanded velocity and the button bindings
+    /// in the terminal. The logs are written to stderr instead of stdout, so redirect it
+    /// (e.g. `2> teleop.log`) to keep the display clean.
+    #[structopt(long)]
+    status_display: bool,
 }
 
+const STATUS_DISPLAY_INTERVAL: Duration = Duration::from_millis(100);
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    tracing_subscriber::fmt::init();
     let args = RobotTeleopArgs::from_args();
+    if args.status_display {
+        // stdout is used by the status display.
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::from_default_env())
+            .with_writer(std::io::stderr)
+            .init();
+    } else {
+        tracing_subscriber::fmt::init();
+    }
 
     let teleop_config = RobotTeleopConfig::try_new(args.config_path)?;
     let robot_config =
         RobotConfig::try_new(teleop_config.robot_config_full_path().as_ref().unwrap())?;
-    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &robot_config);
+    openrr_apps::utils::init(env!("CARGO_BIN_NAME"), &robot_config)?;
     #[cfg(feature = "ros")]
     let use_ros = robot_config.has_ros_clients();
     let client: Arc<ArcRobotClient> = Arc::new(robot_config.create_robot_client()?);
 
     let speaker = client.speakers().values().next().unwrap();
 
-    let nodes = teleop_config.control_nodes_config.create_control_nodes(
+    let mut nodes = teleop_config.control_nodes_config.create_control_nodes(
         speaker.clone(),
         client.joint_trajectory_clients(),
         client.ik_solvers(),
         Some(client.clone()),
-        robot_config.openrr_clients_config.joints_poses,
+        &robot_config.openrr_clients_config,
+        Some(client.clone()),
     );
+    if let Some(behavior_tree_node_config) =
+        &teleop_config.control_nodes_config.behavior_tree_node_config
+    {
+        nodes.push(Box::new(BehaviorTreeNode::new_from_config(
+            behavior_tree_node_config.clone(),
+            client.clone(),
+            speaker.clone(),
+        )?));
+    }
+    for trajectory_playback_teleop_config in &teleop_config
+        .control_nodes_config
+        .trajectory_playback_teleop_configs
+    {
+        nodes.push(Box::new(TrajectoryPlaybackNode::new_from_config(
+            trajectory_playback_teleop_config.config.clone(),
+            client.joint_trajectory_clients()
+                [&trajectory_playback_teleop_config.joint_trajectory_client_name]
+                .clone(),
+            speaker.clone(),
+        )?));
+    }
     if nodes.is_empty() {
         panic!("No valid nodes");
     }
@@ -56,11 +115,34 @@ async fn main() -> Result<(), Error> {
         return Err(Error::NoSpecifiedNode(teleop_config.initial_mode));
     };
 
-    let switcher = Arc::new(ControlNodeSwitcher::new(
-        nodes,
-        speaker.clone(),
-        initial_node_index,
-    ));
+    let mut switcher = ControlNodeSwitcher::new(nodes, speaker.clone(), initial_node_index)
+        .with_mode_switch_button(teleop_config.mode_switch_button);
+    if let Some(deadman_timeout_secs) = teleop_config.deadman_timeout_secs {
+        switcher = switcher.with_deadman_timeout(Duration::from_secs_f64(deadman_timeout_secs));
+    }
+    let switcher = Arc::new(switcher);
+    let robot_status_alert = match (
+        &teleop_config.robot_status_alert_config,
+        client.robot_status(),
+    ) {
+        (Some(alert_config), Some(robot_status)) => {
+            let alert = Arc::new(RobotStatusAlert::new_from_config(
+                alert_config.clone(),
+                robot_status.clone(),
+                speaker.clone(),
+            ));
+            let alert_cloned = alert.clone();
+            tokio::spawn(async move {
+                alert_cloned.main().await;
+            });
+            Some(alert)
+        }
+        (Some(_), None) => {
+            warn!("robot_status_alert_config is set, but robot status client is not available");
+            None
+        }
+        _ => None,
+    };
     #[cfg(feature = "ros")]
     if use_ros {
         let switcher_cloned = switcher.clone();
@@ -72,11 +154,121 @@ async fn main() -> Result<(), Error> {
             switcher_cloned.stop();
         });
     }
-    switcher
-        .main(GilGamepad::new_from_config(
-            teleop_config.gil_gamepad_config,
-        ))
-        .await;
+    let gamepad: Box<dyn Gamepad> = match (&args.gamepad_script_path, teleop_config.gamepad) {
+        (Some(path), _) => Box::new(ScriptedGamepad::new(GamepadScript::try_new(path)?)),
+        (None, TeleopGamepad::Gilrs) => {
+            let gamepad = GilGamepad::new_from_config(teleop_config.gil_gamepad_config);
+            if teleop_config.additional_gil_gamepad_configs.is_empty() {
+                Box::new(gamepad)
+            } else {
+                let mut gamepads = vec![gamepad];
+                gamepads.extend(
+                    teleop_config
+                        .additional_gil_gamepad_configs
+                        .into_iter()
+                        .map(GilGamepad::new_from_config),
+                );
+                Box::new(MergedGamepad::new(gamepads))
+            }
+        }
+        (None, TeleopGamepad::Keyboard) => Box::new(KeyboardGamepad::new_from_config(
+            teleop_config.keyboard_gamepad_config,
+        )),
+        #[cfg(feature = "ros2")]
+        (None, TeleopGamepad::Ros2Joy) => {
+            let config = teleop_config
+                .ros2_joy_gamepad_config
+                .ok_or_else(|| Error::NoConfig("ros2_joy_gamepad_config".into()))?;
+            // The robot config may not have ROS2 clients. Calling init twice has no effect.
+            arci_ros2::init(env!("CARGO_BIN_NAME"))?;
+            Box::new(arci_ros2::Ros2JoyGamepad::new_from_config(config)?)
+        }
+        #[cfg(not(feature = "ros2"))]
+        (None, TeleopGamepad::__Ros2Joy) => unreachable!(),
+    };
+    let status_display = if args.status_display {
+        Some(spawn_status_display(switcher.clone()))
+    } else {
+        None
+    };
+    let (gamepad, recorder): (Box<dyn Gamepad>, _) = match &args.record_gamepad_path {
+        Some(path) => {
+            let recorder = Arc::new(GamepadRecorder::new(gamepad));
+            (Box::new(recorder.clone()), Some((recorder, path.clone())))
+        }
+        None => (gamepad, None),
+    };
+    // Ctrl-C does not stop the switcher while waiting for the gamepad events, so the control node
+    // is stopped, the terminal is restored and the recorded events are saved here.
+    let recorder_cloned = recorder.clone();
+    let is_status_display = args.status_display;
+    let switcher_cloned = switcher.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            switcher_cloned.stop();
+            switcher_cloned.stop_current_node().await;
+            if is_status_display {
+                leave_status_display();
+            }
+            if let Some((recorder, path)) = recorder_cloned {
+                save_gamepad_script(&recorder.script(), &path);
+            }
+            std::process::exit(0);
+        }
+    });
+    switcher.main(gamepad).await;
+    if let Some((recorder, path)) = recorder {
+        save_gamepad_script(&recorder.script(), &path);
+    }
+    if let Some(status_display) = status_display {
+        status_display.abort();
+        leave_status_display();
+    }
+    if let Some(alert) = robot_status_alert {
+        alert.stop();
+    }
 
     Ok(())
 }
+
+fn save_gamepad_script(script: &GamepadScript, path: &Path) {
+    match script.save(path) {
+        Ok(()) => info!("Saved the gamepad events to {:?}", path),
+        Err(e) => error!("Failed to save the gamepad events to {:?}: {}", path, e),
+    }
+}
+
+/// Draws the status of the switcher on the alternate screen of the terminal until aborted.
+fn spawn_status_display<N, S>(
+    switcher: Arc<ControlNodeSwitcher<N, S>>,
+) -> tokio::task::JoinHandle<()>
+where
+    N: 'static + ControlNode,
+    S: 'static + Speaker,
+{
+    tokio::spawn(async move {
+        if let Err(e) = execute!(std::io::stdout(), EnterAlternateScreen, Hide) {
+            error!("Failed to start the status display: {}", e);
+            return;
+        }
+        let mut interval = tokio::time::interval(STATUS_DISPLAY_INTERVAL);
+        loop {
+            interval.tick().await;
+            // The keyboard gamepad puts the terminal in the raw mode, which needs "\r\n".
+            let text = switcher.status().await.to_string().replace('\n', "\r\n");
+            if let Err(e) = draw_status(&text) {
+                error!("Failed to draw the status: {}", e);
+            }
+        }
+    })
+}
+
+fn draw_status(text: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout();
+    queue!(stdout, MoveTo(0, 0), Clear(ClearType::All), Print(text))?;
+    stdout.flush()
+}
+
+fn leave_status_display() {
+    let _ = execute!(std::io::stdout(), Show, LeaveAlternateScreen);
+}
@@ -6,6 +6,8 @@ use thiserror::Error;
 pub enum Error {
     #[error("openrr-apps: No ConfigPath is specified.")]
     NoConfigPath,
+    #[error("openrr-apps: No Config {:?} is specified.", .0)]
+    NoConfig(String),
     #[error("openrr-apps: Failed to parse {:?} as toml ({}).", .0, .1)]
     TomlParseFailure(PathBuf, #[source] toml::de::Error),
     #[error("openrr-apps: No File {:?} is found ({}).", .0, .1)]
@@ -16,8 +18,18 @@ pub enum Error {
     NoSpecifiedNode(String),
     #[error("openrr-apps: Config {:?} requires ros feature.", .0)]
     ConfigRequireRos(String),
+    #[error("openrr-apps: Config {:?} requires ros2 feature.", .0)]
+    ConfigRequireRos2(String),
+    #[error("openrr-apps: Config {:?} requires both localization and move base.", .0)]
+    ConfigRequireLocalizationAndMoveBase(String),
+    #[error("openrr-apps: arci: {:?}", .0)]
+    Arci(#[from] arci::Error),
+    #[error("openrr-apps: openrr-behavior-tree: {:?}", .0)]
+    OpenrrBehaviorTree(#[from] openrr_behavior_tree::Error),
     #[error("openrr-apps: openrr-client: {:?}", .0)]
     OpenrrClient(#[from] openrr_client::Error),
+    #[error("openrr-apps: openrr-teleop: {:?}", .0)]
+    OpenrrTeleop(#[from] openrr_teleop::Error),
     #[error("openrr-apps: openrr-command: {:?}", .0)]
     OpenrrCommand(#[from] openrr_command::Error),
 }
@@ -1,16 +1,29 @@
 use crate::Error;
-use arci::{JointTrajectoryClient, Localization, MoveBase, Navigation, Speaker};
+use arci::{
+    DummyRobotStatus, JointTrajectoryClient, Localization, MoveBase, Navigation, RobotStatus,
+    SafeMoveBase, SafeMoveBaseConfig, Speaker,
+};
+use arci_remote::{RemoteRobotClient, RemoteRobotClientConfig};
 #[cfg(feature = "ros")]
 use arci_ros::{
     RosCmdVelMoveBase, RosCmdVelMoveBaseConfig, RosControlClientConfig, RosEspeakClient,
     RosEspeakClientConfig, RosLocalizationClient, RosLocalizationClientConfig, RosNavClient,
-    RosNavClientConfig,
+    RosNavClientConfig, RosRobotStatusClient, RosRobotStatusClientConfig,
+};
+#[cfg(feature = "ros2")]
+use arci_ros2::{
+    Ros2CmdVelMoveBase, Ros2CmdVelMoveBaseConfig, Ros2ControlClientConfig, Ros2LocalizationClient,
+    Ros2LocalizationClientConfig, Ros2NavClient, Ros2NavClientConfig, Ros2SpeakClient,
+    Ros2SpeakClientConfig,
 };
 use arci_urdf_viz::{create_joint_trajectory_clients, UrdfVizWebClient, UrdfVizWebClientConfig};
 
 use arci_speak_audio::AudioSpeaker;
 use arci_speak_cmd::LocalCommand;
-use openrr_client::{OpenrrClientsConfig, PrintSpeaker, RobotClient};
+use openrr_client::{
+    GridNavigation, GridNavigationConfig, OpenrrClientsConfig, PrintSpeaker, Recorder,
+    RecorderConfig, RecordingJointTrajectoryClient, RecordingMoveBase, RobotClient,
+};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -37,6 +50,18 @@ pub enum SpeakConfig {
     __RosEspeak {
         config: toml::Value,
     },
+    #[cfg(feature = "ros2")]
+    Ros2Speak {
+        config: Ros2SpeakClientConfig,
+    },
+    // Not public API.
+    // A dummy variant to catch that there is a config that requires the ros2 feature.
+    #[doc(hidden)]
+    #[cfg(not(feature = "ros2"))]
+    #[serde(rename = "Ros2Speak")]
+    __Ros2Speak {
+        config: toml::Value,
+    },
     Audio {
         map: HashMap<String, PathBuf>,
     },
@@ -57,6 +82,12 @@ pub struct RobotConfig {
     // A dummy field to catch that there is a config that requires the ros feature.
     #[cfg(not(feature = "ros"))]
     ros_clients_configs: Option<toml::Value>,
+    #[cfg(feature = "ros2")]
+    #[serde(default)]
+    pub ros2_clients_configs: Vec<Ros2ControlClientConfig>,
+    // A dummy field to catch that there is a config that requires the ros2 feature.
+    #[cfg(not(feature = "ros2"))]
+    ros2_clients_configs: Option<toml::Value>,
     #[serde(default)]
     pub urdf_viz_clients_configs: Vec<UrdfVizWebClientConfig>,
 
@@ -73,25 +104,61 @@ pub struct RobotConfig {
     // A dummy field to catch that there is a config that requires the ros feature.
     #[cfg(not(feature = "ros"))]
     ros_cmd_vel_move_base_client_config: Option<toml::Value>,
+    #[cfg(feature = "ros2")]
+    pub ros2_cmd_vel_move_base_client_config: Option<Ros2CmdVelMoveBaseConfig>,
+    // A dummy field to catch that there is a config that requires the ros2 feature.
+    #[cfg(not(feature = "ros2"))]
+    ros2_cmd_vel_move_base_client_config: Option<toml::Value>,
     #[serde(default = "default_true")]
     pub use_move_base_urdf_viz_web_client: bool,
+    /// Limits of velocity and acceleration, deadman timeout and geofence of the move base.
+    pub move_base_safety_config: Option<SafeMoveBaseConfig>,
 
     #[cfg(feature = "ros")]
     pub ros_navigation_client_config: Option<RosNavClientConfig>,
     // A dummy field to catch that there is a config that requires the ros feature.
     #[cfg(not(feature = "ros"))]
     ros_navigation_client_config: Option<toml::Value>,
+    #[cfg(feature = "ros2")]
+    pub ros2_navigation_client_config: Option<Ros2NavClientConfig>,
+    // A dummy field to catch that there is a config that requires the ros2 feature.
+    #[cfg(not(feature = "ros2"))]
+    ros2_navigation_client_config: Option<toml::Value>,
     #[serde(default = "default_true")]
     pub use_navigation_urdf_viz_web_client: bool,
+    /// Navigation on the occupancy grid map using the localization and the move base of this robot.
+    /// This is used instead of the urdf-viz navigation if specified.
+    pub grid_navigation_config: Option<GridNavigationConfig>,
 
     #[cfg(feature = "ros")]
     pub ros_localization_client_config: Option<RosLocalizationClientConfig>,
     // A dummy field to catch that there is a config that requires the ros feature.
     #[cfg(not(feature = "ros"))]
     ros_localization_client_config: Option<toml::Value>,
+    #[cfg(feature = "ros2")]
+    pub ros2_localization_client_config: Option<Ros2LocalizationClientConfig>,
+    // A dummy field to catch that there is a config that requires the ros2 feature.
+    #[cfg(not(feature = "ros2"))]
+    ros2_localization_client_config: Option<toml::Value>,
     #[serde(default = "default_true")]
     pub use_localization_urdf_viz_web_client: bool,
 
+    #[cfg(feature = "ros")]
+    pub ros_robot_status_client_config: Option<RosRobotStatusClientConfig>,
+    // A dummy field to catch that there is a config that requires the ros feature.
+    #[cfg(not(feature = "ros"))]
+    ros_robot_status_client_config: Option<toml::Value>,
+    #[serde(default)]
+    pub use_dummy_robot_status: bool,
+
+    /// Use the clients exposed by `openrr_apps_remote_robot_server` instead of the local ones.
+    /// The IK and the collision check are computed locally using `openrr_clients_config`.
+    pub remote_robot_client_config: Option<RemoteRobotClientConfig>,
+
+    /// Record the commands and the states of the joint trajectory clients and the move base.
+    /// The records can be replayed by `openrr_apps_replay`.
+    pub recorder_config: Option<RecorderConfig>,
+
     pub openrr_clients_config: OpenrrClientsConfig,
 }
 
@@ -120,6 +187,19 @@ impl RobotConfig {
             || has_ros_espeak
             || self.ros_cmd_vel_move_base_client_config.is_some()
             || self.ros_navigation_client_config.is_some()
+            || self.ros_robot_status_client_config.is_some()
+    }
+    #[cfg(feature = "ros2")]
+    pub fn has_ros2_clients(&self) -> bool {
+        let has_ros2_speak = self
+            .speak_configs
+            .values()
+            .any(|speak_config| matches!(speak_config, SpeakConfig::Ros2Speak { .. }));
+        !self.ros2_clients_configs.is_empty()
+            || has_ros2_speak
+            || self.ros2_cmd_vel_move_base_client_config.is_some()
+            || self.ros2_navigation_client_config.is_some()
+            || self.ros2_localization_client_config.is_some()
     }
 
     pub fn create_robot_client<L, M, N>(&self) -> Result<RobotClient<L, M, N>, Error>
@@ -128,20 +208,146 @@ impl RobotConfig {
         M: MoveBase + From<Box<dyn MoveBase>>,
         N: Navigation + From<Box<dyn Navigation>>,
     {
+        let recorder = self.create_recorder()?;
+        if let Some(remote_robot_client_config) = &self.remote_robot_client_config {
+            return self.create_remote_robot_client(remote_robot_client_config, recorder.as_ref());
+        }
+
         let mut speakers = HashMap::new();
-        for (name, speaker) in self.create_speakers() {
+        for (name, speaker) in self.create_speakers()? {
             speakers.insert(name, speaker.into());
         }
 
+        // The navigation may share the localization and the move base with the robot client.
+        let localization: Option<Arc<dyn Localization>> =
+            self.create_localization()?.map(Arc::from);
+        let move_base = self.create_move_base()?.map(|move_base| {
+            self.wrap_move_base(
+                Arc::from(move_base),
+                localization.as_ref(),
+                recorder.as_ref(),
+            )
+        });
+        let navigation = self.create_navigation(localization.as_ref(), move_base.as_ref())?;
+
+        Ok(RobotClient::try_new(
+            self.openrr_clients_config.clone(),
+            self.wrap_joint_trajectory_clients(
+                self.create_raw_joint_trajectory_clients()?,
+                recorder.as_ref(),
+            ),
+            speakers,
+            localization.map(|l| (Box::new(l) as Box<dyn Localization>).into()),
+            move_base.map(|m| (Box::new(m) as Box<dyn MoveBase>).into()),
+            navigation.map(|n| n.into()),
+            self.create_robot_status(),
+        )?)
+    }
+    fn create_remote_robot_client<L, M, N>(
+        &self,
+        config: &RemoteRobotClientConfig,
+        recorder: Option<&Arc<Recorder>>,
+    ) -> Result<RobotClient<L, M, N>, Error>
+    where
+        L: Localization + From<Box<dyn Localization>>,
+        M: MoveBase + From<Box<dyn MoveBase>>,
+        N: Navigation + From<Box<dyn Navigation>>,
+    {
+        let remote = Arc::new(RemoteRobotClient::new_from_config(config.clone())?);
+        let info = remote.info()?;
+
+        // The speakers of this config speak locally in addition to the remote ones.
+        // The default speaker is used only if there is no speaker.
+        let mut speakers = remote.create_speakers(&info)?;
+        if !self.speak_configs.is_empty() || speakers.is_empty() {
+            for (name, speaker) in self.create_speakers()? {
+                speakers.insert(name, speaker.into());
+            }
+        }
+
+        let localization = if info.has_localization {
+            Some(remote.clone() as Arc<dyn Localization>)
+        } else {
+            None
+        };
+        let move_base = if info.has_move_base {
+            Some(self.wrap_move_base(remote.clone(), localization.as_ref(), recorder))
+        } else {
+            None
+        };
+        let navigation = if info.has_navigation {
+            Some(Box::new(remote.clone()) as Box<dyn Navigation>)
+        } else if let Some(grid_navigation_config) = &self.grid_navigation_config {
+            Some(self.create_grid_navigation(
+                grid_navigation_config,
+                localization.as_ref(),
+                move_base.as_ref(),
+            )?)
+        } else {
+            None
+        };
+        let robot_status = if info.has_robot_status {
+            Some(remote.clone() as Arc<dyn RobotStatus>)
+        } else {
+            None
+        };
+
         Ok(RobotClient::try_new(
             self.openrr_clients_config.clone(),
-            self.create_raw_joint_trajectory_clients(),
+            self.wrap_joint_trajectory_clients(
+                remote.create_joint_trajectory_clients(&info)?,
+                recorder,
+            ),
             speakers,
-            self.create_localization().map(|l| l.into()),
-            self.create_move_base().map(|m| m.into()),
-            self.create_navigation().map(|n| n.into()),
+            localization.map(|l| (Box::new(l) as Box<dyn Localization>).into()),
+            move_base.map(|m| (Box::new(m) as Box<dyn MoveBase>).into()),
+            navigation.map(|n| n.into()),
+            robot_status,
         )?)
     }
+    fn create_recorder(&self) -> Result<Option<Arc<Recorder>>, Error> {
+        Ok(match &self.recorder_config {
+            Some(config) => Some(Arc::new(Recorder::new_from_config(config)?)),
+            None => None,
+        })
+    }
+    fn wrap_joint_trajectory_clients(
+        &self,
+        clients: HashMap<String, Arc<dyn JointTrajectoryClient>>,
+        recorder: Option<&Arc<Recorder>>,
+    ) -> HashMap<String, Arc<dyn JointTrajectoryClient>> {
+        match recorder {
+            Some(recorder) => clients
+                .into_iter()
+                .map(|(name, client)| {
+                    let client =
+                        RecordingJointTrajectoryClient::new(name.clone(), client, recorder.clone());
+                    (name, Arc::new(client) as Arc<dyn JointTrajectoryClient>)
+                })
+                .collect(),
+            None => clients,
+        }
+    }
+    /// The recorder records the commands which passed the safety checks.
+    fn wrap_move_base(
+        &self,
+        move_base: Arc<dyn MoveBase>,
+        localization: Option<&Arc<dyn Localization>>,
+        recorder: Option<&Arc<Recorder>>,
+    ) -> Arc<dyn MoveBase> {
+        let move_base = match recorder {
+            Some(recorder) => Arc::new(RecordingMoveBase::new(move_base, recorder.clone())),
+            None => move_base,
+        };
+        match &self.move_base_safety_config {
+            Some(config) => Arc::new(SafeMoveBase::new(
+                move_base,
+                config.clone(),
+                localization.cloned(),
+            )),
+            None => move_base,
+        }
+    }
     fn create_localization_without_ros(&self) -> Option<Box<dyn Localization>> {
         if self.use_localization_urdf_viz_web_client {
             let urdf_viz_client = Box::new(UrdfVizWebClient::default());
@@ -160,42 +366,105 @@ impl RobotConfig {
             self.create_localization_without_ros()
         }
     }
-    fn create_localization(&self) -> Option<Box<dyn Localization>> {
+    #[cfg(feature = "ros2")]
+    fn create_localization_with_ros2(&self) -> Result<Option<Box<dyn Localization>>, Error> {
+        Ok(match &self.ros2_localization_client_config {
+            Some(config) => Some(
+                Box::new(Ros2LocalizationClient::new_from_config(config.clone())?)
+                    as Box<dyn Localization>,
+            ),
+            None => None,
+        })
+    }
+    fn create_localization(&self) -> Result<Option<Box<dyn Localization>>, Error> {
+        #[cfg(feature = "ros2")]
+        if let Some(localization) = self.create_localization_with_ros2()? {
+            return Ok(Some(localization));
+        }
         #[cfg(not(feature = "ros"))]
         {
-            self.create_localization_without_ros()
+            Ok(self.create_localization_without_ros())
         }
         #[cfg(feature = "ros")]
         {
-            self.create_localization_with_ros()
+            Ok(self.create_localization_with_ros())
+        }
+    }
+    fn create_grid_navigation(
+        &self,
+        config: &GridNavigationConfig,
+        localization: Option<&Arc<dyn Localization>>,
+        move_base: Option<&Arc<dyn MoveBase>>,
+    ) -> Result<Box<dyn Navigation>, Error> {
+        match (localization, move_base) {
+            (Some(localization), Some(move_base)) => Ok(Box::new(GridNavigation::new_from_config(
+                config.clone(),
+                localization.clone(),
+                move_base.clone(),
+            )?)),
+            _ => Err(Error::ConfigRequireLocalizationAndMoveBase(
+                "grid_navigation_config".into(),
+            )),
         }
     }
-    fn create_navigation_without_ros(&self) -> Option<Box<dyn Navigation>> {
-        if self.use_navigation_urdf_viz_web_client {
+    fn create_navigation_without_ros(
+        &self,
+        localization: Option<&Arc<dyn Localization>>,
+        move_base: Option<&Arc<dyn MoveBase>>,
+    ) -> Result<Option<Box<dyn Navigation>>, Error> {
+        if let Some(grid_navigation_config) = &self.grid_navigation_config {
+            Ok(Some(self.create_grid_navigation(
+                grid_navigation_config,
+                localization,
+                move_base,
+            )?))
+        } else if self.use_navigation_urdf_viz_web_client {
             let urdf_viz_client = Box::new(UrdfVizWebClient::default());
-            Some(urdf_viz_client as Box<dyn Navigation>)
+            Ok(Some(urdf_viz_client as Box<dyn Navigation>))
         } else {
-            None
+            Ok(None)
         }
     }
     #[cfg(feature = "ros")]
-    fn create_navigation_with_ros(&self) -> Option<Box<dyn Navigation>> {
+    fn create_navigation_with_ros(
+        &self,
+        localization: Option<&Arc<dyn Localization>>,
+        move_base: Option<&Arc<dyn MoveBase>>,
+    ) -> Result<Option<Box<dyn Navigation>>, Error> {
         if let Some(ros_navigation_client_config) = &self.ros_navigation_client_config {
-            Some(Box::new(RosNavClient::new_from_config(
+            Ok(Some(Box::new(RosNavClient::new_from_config(
                 ros_navigation_client_config.clone(),
-            )) as Box<dyn Navigation>)
+            )) as Box<dyn Navigation>))
         } else {
-            self.create_navigation_without_ros()
+            self.create_navigation_without_ros(localization, move_base)
         }
     }
-    fn create_navigation(&self) -> Option<Box<dyn Navigation>> {
+    #[cfg(feature = "ros2")]
+    fn create_navigation_with_ros2(&self) -> Result<Option<Box<dyn Navigation>>, Error> {
+        Ok(match &self.ros2_navigation_client_config {
+            Some(config) => {
+                Some(Box::new(Ros2NavClient::new_from_config(config.clone())?)
+                    as Box<dyn Navigation>)
+            }
+            None => None,
+        })
+    }
+    fn create_navigation(
+        &self,
+        localization: Option<&Arc<dyn Localization>>,
+        move_base: Option<&Arc<dyn MoveBase>>,
+    ) -> Result<Option<Box<dyn Navigation>>, Error> {
+        #[cfg(feature = "ros2")]
+        if let Some(navigation) = self.create_navigation_with_ros2()? {
+            return Ok(Some(navigation));
+        }
         #[cfg(not(feature = "ros"))]
         {
-            self.create_navigation_without_ros()
+            self.create_navigation_without_ros(localization, move_base)
         }
         #[cfg(feature = "ros")]
         {
-            self.create_navigation_with_ros()
+            self.create_navigation_with_ros(localization, move_base)
         }
     }
     fn create_move_base_without_ros(&self) -> Option<Box<dyn MoveBase>> {
@@ -218,14 +487,54 @@ impl RobotConfig {
             self.create_move_base_without_ros()
         }
     }
-    fn create_move_base(&self) -> Option<Box<dyn MoveBase>> {
+    #[cfg(feature = "ros2")]
+    fn create_move_base_with_ros2(&self) -> Result<Option<Box<dyn MoveBase>>, Error> {
+        Ok(match &self.ros2_cmd_vel_move_base_client_config {
+            Some(config) => {
+                Some(Box::new(Ros2CmdVelMoveBase::new(&config.topic)?) as Box<dyn MoveBase>)
+            }
+            None => None,
+        })
+    }
+    fn create_move_base(&self) -> Result<Option<Box<dyn MoveBase>>, Error> {
+        #[cfg(feature = "ros2")]
+        if let Some(move_base) = self.create_move_base_with_ros2()? {
+            return Ok(Some(move_base));
+        }
         #[cfg(not(feature = "ros"))]
         {
-            self.create_move_base_without_ros()
+            Ok(self.create_move_base_without_ros())
+        }
+        #[cfg(feature = "ros")]
+        {
+            Ok(self.create_move_base_with_ros())
+        }
+    }
+    fn create_robot_status_without_ros(&self) -> Option<Arc<dyn RobotStatus>> {
+        if self.use_dummy_robot_status {
+            Some(Arc::new(DummyRobotStatus::new()) as Arc<dyn RobotStatus>)
+        } else {
+            None
+        }
+    }
+    #[cfg(feature = "ros")]
+    fn create_robot_status_with_ros(&self) -> Option<Arc<dyn RobotStatus>> {
+        if let Some(ros_robot_status_client_config) = &self.ros_robot_status_client_config {
+            Some(Arc::new(RosRobotStatusClient::new(
+                ros_robot_status_client_config.clone(),
+            )) as Arc<dyn RobotStatus>)
+        } else {
+            self.create_robot_status_without_ros()
+        }
+    }
+    fn create_robot_status(&self) -> Option<Arc<dyn RobotStatus>> {
+        #[cfg(not(feature = "ros"))]
+        {
+            self.create_robot_status_without_ros()
         }
         #[cfg(feature = "ros")]
         {
-            self.create_move_base_with_ros()
+            self.create_robot_status_with_ros()
         }
     }
     fn create_print_speaker(&self) -> Box<dyn Speaker> {
@@ -241,7 +550,11 @@ impl RobotConfig {
     fn create_ros_espeak_client(&self, topic: &str) -> Box<dyn Speaker> {
         Box::new(RosEspeakClient::new(topic))
     }
-    fn create_speakers(&self) -> HashMap<String, Box<dyn Speaker>> {
+    #[cfg(feature = "ros2")]
+    fn create_ros2_speak_client(&self, topic: &str) -> Result<Box<dyn Speaker>, Error> {
+        Ok(Box::new(Ros2SpeakClient::new(topic)?))
+    }
+    fn create_speakers(&self) -> Result<HashMap<String, Box<dyn Speaker>>, Error> {
         let mut speakers = HashMap::new();
         for (name, speak_config) in &self.speak_configs {
             speakers.insert(
@@ -253,6 +566,12 @@ impl RobotConfig {
                     }
                     #[cfg(not(feature = "ros"))]
                     SpeakConfig::__RosEspeak { .. } => unreachable!(),
+                    #[cfg(feature = "ros2")]
+                    SpeakConfig::Ros2Speak { config } => {
+                        self.create_ros2_speak_client(&config.topic)?
+                    }
+                    #[cfg(not(feature = "ros2"))]
+                    SpeakConfig::__Ros2Speak { .. } => unreachable!(),
                     SpeakConfig::Audio { ref map } => self.create_audio_speaker(map.clone()),
                     SpeakConfig::Command => self.create_local_command_speaker(),
                     SpeakConfig::Print => self.create_print_speaker(),
@@ -265,19 +584,21 @@ impl RobotConfig {
                 self.create_print_speaker(),
             );
         }
-        speakers
+        Ok(speakers)
     }
     fn create_raw_joint_trajectory_clients(
         &self,
-    ) -> HashMap<String, Arc<dyn JointTrajectoryClient>> {
+    ) -> Result<HashMap<String, Arc<dyn JointTrajectoryClient>>, Error> {
         #[cfg(not(feature = "ros"))]
-        let raw_joint_trajectory_clients = create_joint_trajectory_clients(
+        #[allow(unused_mut)]
+        let mut raw_joint_trajectory_clients = create_joint_trajectory_clients(
             self.urdf_viz_clients_configs.clone(),
             self.urdf_viz_clients_total_complete_allowable_error,
             self.urdf_viz_clients_complete_timeout_sec,
         );
         #[cfg(feature = "ros")]
-        let raw_joint_trajectory_clients = {
+        #[allow(unused_mut)]
+        let mut raw_joint_trajectory_clients = {
             let mut clients = if self.urdf_viz_clients_configs.is_empty() {
                 HashMap::new()
             } else {
@@ -293,7 +614,12 @@ impl RobotConfig {
             );
             clients
         };
-        raw_joint_trajectory_clients
+        #[cfg(feature = "ros2")]
+        raw_joint_trajectory_clients.extend(
+            arci_ros2::create_joint_trajectory_clients(self.ros2_clients_configs.clone())?
+                .into_iter(),
+        );
+        Ok(raw_joint_trajectory_clients)
     }
 }
 
@@ -354,11 +680,59 @@ impl RobotConfig {
                     "ros_navigation_client_config".into(),
                 ));
             }
+            if config.ros_robot_status_client_config.is_some() {
+                return Err(Error::ConfigRequireRos(
+                    "ros_robot_status_client_config".into(),
+                ));
+            }
+        }
+        // Returns an error if a config requires ros2 feature but ros2 feature is disabled.
+        #[cfg(not(feature = "ros2"))]
+        {
+            for (name, speak_config) in &config.speak_configs {
+                if matches!(speak_config, SpeakConfig::__Ros2Speak { .. }) {
+                    return Err(Error::ConfigRequireRos2(format!("speak_configs.{}", name)));
+                }
+            }
+            if config.ros2_clients_configs.is_some() {
+                return Err(Error::ConfigRequireRos2("ros2_clients_configs".into()));
+            }
+            if config.ros2_cmd_vel_move_base_client_config.is_some() {
+                return Err(Error::ConfigRequireRos2(
+                    "ros2_cmd_vel_move_base_client_config".into(),
+                ));
+            }
+            if config.ros2_navigation_client_config.is_some() {
+                return Err(Error::ConfigRequireRos2(
+                    "ros2_navigation_client_config".into(),
+                ));
+            }
+            if config.ros2_localization_client_config.is_some() {
+                return Err(Error::ConfigRequireRos2(
+                    "ros2_localization_client_config".into(),
+                ));
+            }
         }
 
         if config.openrr_clients_config.urdf_path.is_some() {
             config.openrr_clients_config.resolve_path(path.as_ref())?;
         }
+        if let Some(joints_poses_path) = &config.openrr_clients_config.joints_poses_path {
+            config.openrr_clients_config.joints_poses_path = Some(
+                openrr_client::resolve_relative_path(path.as_ref(), joints_poses_path)?,
+            );
+        }
+        config.openrr_clients_config.merge_joints_poses_file()?;
+        if let Some(recorder_config) = &mut config.recorder_config {
+            recorder_config.path =
+                openrr_client::resolve_relative_path(path.as_ref(), &recorder_config.path)?;
+        }
+        if let Some(grid_navigation_config) = &mut config.grid_navigation_config {
+            grid_navigation_config.map_path = openrr_client::resolve_relative_path(
+                path.as_ref(),
+                &grid_navigation_config.map_path,
+            )?;
+        }
         for speak_config in config.speak_configs.values_mut() {
             if let SpeakConfig::Audio { ref mut map } = speak_config {
                 resolve_audio_file_path(path.as_ref(), map)?;
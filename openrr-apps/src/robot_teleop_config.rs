@@ -1,21 +1,63 @@
 use std::path::PathBuf;
 
+use arci::gamepad::Button;
 use arci_gamepad_gilrs::GilGamepadConfig;
+use arci_gamepad_keyboard::KeyboardGamepadConfig;
+#[cfg(feature = "ros2")]
+use arci_ros2::Ros2JoyGamepadConfig;
 use openrr_client::resolve_relative_path;
-use openrr_teleop::ControlNodesConfig;
+use openrr_teleop::{ControlNodesConfig, RobotStatusAlertConfig};
 use serde::{Deserialize, Serialize};
 
 use crate::Error;
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum TeleopGamepad {
+    /// Joystick devices (`gil_gamepad_config`).
+    #[default]
+    Gilrs,
+    /// Keyboard of the terminal, e.g. over SSH (`keyboard_gamepad_config`).
+    Keyboard,
+    /// `sensor_msgs/msg/Joy` topic of ROS2 (`ros2_joy_gamepad_config`).
+    #[cfg(feature = "ros2")]
+    Ros2Joy,
+    // Not public API.
+    // A dummy variant to catch that there is a config that requires the ros2 feature.
+    #[doc(hidden)]
+    #[cfg(not(feature = "ros2"))]
+    #[serde(rename = "Ros2Joy")]
+    __Ros2Joy,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RobotTeleopConfig {
     pub robot_config_path: String,
     robot_config_full_path: Option<PathBuf>,
     #[serde(default)]
     pub initial_mode: String,
+    /// Button to switch the control nodes.
+    #[serde(default = "default_mode_switch_button")]
+    pub mode_switch_button: Button,
+    /// Stops the current control node if no gamepad event arrives for this duration.
+    pub deadman_timeout_secs: Option<f64>,
     pub control_nodes_config: ControlNodesConfig,
+    /// Gamepad backend to use.
+    #[serde(default)]
+    pub gamepad: TeleopGamepad,
     #[serde(default)]
     pub gil_gamepad_config: GilGamepadConfig,
+    /// Other joystick devices used together with `gil_gamepad_config`, e.g. a 6DoF device.
+    #[serde(default)]
+    pub additional_gil_gamepad_configs: Vec<GilGamepadConfig>,
+    #[serde(default)]
+    pub keyboard_gamepad_config: KeyboardGamepadConfig,
+    #[cfg(feature = "ros2")]
+    pub ros2_joy_gamepad_config: Option<Ros2JoyGamepadConfig>,
+    // A dummy field to catch that there is a config that requires the ros2 feature.
+    #[cfg(not(feature = "ros2"))]
+    ros2_joy_gamepad_config: Option<toml::Value>,
+    /// Speak alerts of the robot status. Requires robot status client in the robot config.
+    pub robot_status_alert_config: Option<RobotStatusAlertConfig>,
 }
 
 impl RobotTeleopConfig {
@@ -25,8 +67,35 @@ impl RobotTeleopConfig {
                 .map_err(|e| Error::NoFile(path.as_ref().to_owned(), e))?,
         )
         .map_err(|e| Error::TomlParseFailure(path.as_ref().to_owned(), e))?;
+        // Returns an error if a config requires ros2 feature but ros2 feature is disabled.
+        #[cfg(not(feature = "ros2"))]
+        {
+            if config.gamepad == TeleopGamepad::__Ros2Joy {
+                return Err(Error::ConfigRequireRos2("gamepad".into()));
+            }
+            if config.ros2_joy_gamepad_config.is_some() {
+                return Err(Error::ConfigRequireRos2("ros2_joy_gamepad_config".into()));
+            }
+        }
         config.robot_config_full_path =
-            Some(resolve_relative_path(path, &config.robot_config_path)?);
+            Some(resolve_relative_path(&path, &config.robot_config_path)?);
+        if let Some(behavior_tree_node_config) =
+            &mut config.control_nodes_config.behavior_tree_node_config
+        {
+            for tree in &mut behavior_tree_node_config.trees {
+                tree.path = resolve_relative_path(&path, &tree.path)?;
+            }
+        }
+        for trajectory_playback_teleop_config in &mut config
+            .control_nodes_config
+            .trajectory_playback_teleop_configs
+        {
+            let node_config = &mut trajectory_playback_teleop_config.config;
+            node_config.record_path = resolve_relative_path(&path, &node_config.record_path)?;
+        }
+        config
+            .control_nodes_config
+            .validate(config.mode_switch_button)?;
         Ok(config)
     }
 
@@ -34,3 +103,7 @@ impl RobotTeleopConfig {
         &self.robot_config_full_path
     }
 }
+
+fn default_mode_switch_button() -> Button {
+    Button::North
+}
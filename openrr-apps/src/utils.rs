@@ -1,4 +1,4 @@
-use crate::RobotConfig;
+use crate::{Error, RobotConfig};
 use rand::prelude::*;
 use std::path::PathBuf;
 use tracing::{debug, warn};
@@ -46,17 +46,22 @@ mod test {
 }
 
 /// Do something needed to start the program
-pub fn init(name: &str, config: &RobotConfig) {
+pub fn init(name: &str, config: &RobotConfig) -> Result<(), Error> {
     #[cfg(feature = "ros")]
     if config.has_ros_clients() {
         arci_ros::init(name);
     }
+    #[cfg(feature = "ros2")]
+    if config.has_ros2_clients() {
+        arci_ros2::init(name)?;
+    }
     debug!("init {} with {:?}", name, config);
+    Ok(())
 }
 
 /// Do something needed to start the program for multiple
-pub fn init_with_anonymize(name: &str, config: &RobotConfig) {
+pub fn init_with_anonymize(name: &str, config: &RobotConfig) -> Result<(), Error> {
     let suffix: u64 = rand::thread_rng().gen();
     let anon_name = format!("{}_{}", name, suffix);
-    init(&anon_name, config);
+    init(&anon_name, config)
 }
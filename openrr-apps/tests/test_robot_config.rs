@@ -5,14 +5,28 @@ fn verify_sample_configs() {
     let files = vec![
         "config/pr2_robot_client_config_for_ros.toml",
         "config/pr2_robot_client_config_for_urdf_viz.toml",
+        "config/sample_robot_client_config_for_remote.toml",
         "config/sample_robot_client_config_for_urdf_viz.toml",
         "config/sample_robot_client_config_for_urdf_viz_with_multiple_speaker.toml",
+        "config/sample_robot_client_config_for_urdf_viz_with_grid_navigation.toml",
         "config/ur10_robot_client_config_for_ros.toml",
+        "config/ur10_robot_client_config_for_ros2.toml",
         "config/ur10_robot_client_config_for_urdf_viz.toml",
     ];
     for f in files {
         let result = RobotConfig::try_new(f);
-        if cfg!(not(feature = "ros")) && f.contains("ros") {
+        if f.contains("ros2") {
+            if cfg!(feature = "ros2") {
+                assert!(result.is_ok(), "{:?} {:?}", f, result);
+            } else {
+                assert!(
+                    matches!(result, Err(openrr_apps::Error::ConfigRequireRos2(..))),
+                    "{:?} {:?}",
+                    f,
+                    result
+                );
+            }
+        } else if cfg!(not(feature = "ros")) && f.contains("ros") {
             assert!(
                 matches!(result, Err(openrr_apps::Error::ConfigRequireRos(..))),
                 "{:?} {:?}",
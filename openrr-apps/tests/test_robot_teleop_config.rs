@@ -1,4 +1,6 @@
+use arci::gamepad::Button;
 use openrr_apps::RobotTeleopConfig;
+use openrr_teleop::ControlNodesConfig;
 
 #[test]
 fn verify_sample_configs() {
@@ -15,3 +17,42 @@ fn verify_sample_configs() {
         assert!(result.is_ok(), "{:?} {:?}", f, result);
     }
 }
+
+#[test]
+fn test_conflicting_bindings() {
+    let config: ControlNodesConfig = toml::from_str(
+        r#"
+joy_joint_teleop_configs = []
+ik_node_teleop_configs = []
+move_base_mode = "base"
+"#,
+    )
+    .unwrap();
+    assert!(config.validate(Button::North).is_ok());
+    // The default deadman button of MoveBaseNode is RightTrigger2.
+    assert!(matches!(
+        config.validate(Button::RightTrigger2),
+        Err(openrr_teleop::Error::ConflictingBindings(..))
+    ));
+
+    let config: ControlNodesConfig = toml::from_str(
+        r#"
+ik_node_teleop_configs = []
+
+[[joy_joint_teleop_configs]]
+client_name = "arm"
+
+[joy_joint_teleop_configs.config]
+mode = "arm"
+
+[joy_joint_teleop_configs.config.bindings]
+enable = "LeftTrigger"
+turbo = "LeftTrigger"
+"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        config.validate(Button::North),
+        Err(openrr_teleop::Error::ConflictingBindings(..))
+    ));
+}
@@ -0,0 +1,142 @@
+use crate::{Context, Error, Node, NodeStatus};
+use arci::Navigation;
+use k::nalgebra::{Isometry2, Vector2};
+use openrr_client::{isometry, ArcRobotClient};
+use std::time::Duration;
+use tracing::warn;
+
+/// Duration to stop the joints when the tree is canceled.
+const STOP_DURATION_SECS: f64 = 0.1;
+
+/// Runs the action node until it finishes or the tree is canceled.
+///
+/// Errors of the action are treated as `NodeStatus::Failure`.
+pub(crate) async fn run_action(
+    node: &Node,
+    context: &Context,
+    path: &str,
+) -> Result<NodeStatus, Error> {
+    let result = tokio::select! {
+        result = execute(node, &context.client) => result,
+        _ = context.canceled() => {
+            stop(node, &context.client).await;
+            return Err(Error::Canceled);
+        }
+    };
+    match result {
+        Ok(()) => Ok(NodeStatus::Success),
+        Err(e) => {
+            warn!("{}: {}", path, e);
+            Ok(NodeStatus::Failure)
+        }
+    }
+}
+
+async fn execute(node: &Node, client: &ArcRobotClient) -> Result<(), openrr_client::Error> {
+    match node {
+        Node::Wait { secs } => {
+            tokio::time::sleep(Duration::from_secs_f64(*secs)).await;
+        }
+        Node::SendJointsPose {
+            client_name,
+            pose_name,
+            duration_secs,
+        } => {
+            client
+                .send_joints_pose(client_name, pose_name, *duration_secs)
+                .await?;
+        }
+        Node::SendJoints {
+            client_name,
+            positions,
+            duration_secs,
+        } => {
+            client
+                .send_joint_positions(client_name, positions, *duration_secs)
+                .await?;
+        }
+        Node::Gripper {
+            client_name,
+            position,
+            duration_secs,
+        } => {
+            let dof = client.current_joint_positions(client_name).await?.len();
+            client
+                .send_joint_positions(client_name, &vec![*position; dof], *duration_secs)
+                .await?;
+        }
+        Node::MoveIk {
+            client_name,
+            x,
+            y,
+            z,
+            roll,
+            pitch,
+            yaw,
+            duration_secs,
+        } => {
+            client
+                .move_ik(
+                    client_name,
+                    &isometry(*x, *y, *z, *roll, *pitch, *yaw),
+                    *duration_secs,
+                )
+                .await?;
+        }
+        Node::MoveTo {
+            x,
+            y,
+            yaw,
+            frame_id,
+            timeout_secs,
+        } => {
+            if client.navigation().is_none() {
+                return Err(arci::Error::Uninitialized {
+                    message: "navigation".to_owned(),
+                }
+                .into());
+            }
+            client
+                .move_to(
+                    Isometry2::new(Vector2::new(*x, *y), *yaw),
+                    frame_id,
+                    Duration::from_secs_f64(*timeout_secs),
+                )
+                .await?;
+        }
+        Node::Speak {
+            speaker_name,
+            message,
+        } => {
+            client.speak(speaker_name, message);
+        }
+        _ => unreachable!("{} is not an action", node.kind()),
+    }
+    Ok(())
+}
+
+/// Stops the motion started by the action.
+pub(crate) async fn stop(node: &Node, client: &ArcRobotClient) {
+    let result = match node {
+        Node::SendJointsPose { client_name, .. }
+        | Node::SendJoints { client_name, .. }
+        | Node::Gripper { client_name, .. }
+        | Node::MoveIk { client_name, .. } => {
+            match client.current_joint_positions(client_name).await {
+                Ok(positions) => {
+                    client
+                        .send_joint_positions(client_name, &positions, STOP_DURATION_SECS)
+                        .await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Node::MoveTo { .. } if client.navigation().is_some() => {
+            client.cancel().map_err(openrr_client::Error::from)
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        warn!("Failed to stop {}: {}", node.kind(), e);
+    }
+}
@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("openrr-behavior-tree: The tree is already running.")]
+    AlreadyRunning,
+    #[error("openrr-behavior-tree: The tree is canceled.")]
+    Canceled,
+    #[error("openrr-behavior-tree: Invalid node {} ({}).", .0, .1)]
+    InvalidNode(String, String),
+    #[error("openrr-behavior-tree: No File {:?} is found ({}).", .0, .1)]
+    NoFile(PathBuf, #[source] std::io::Error),
+    #[error("openrr-behavior-tree: Failed to parse {:?} as toml ({}).", .0, .1)]
+    TomlParseFailure(PathBuf, #[source] toml::de::Error),
+}
@@ -0,0 +1,8 @@
+mod action;
+mod error;
+mod node;
+mod tree;
+
+pub use error::*;
+pub use node::*;
+pub use tree::*;
@@ -0,0 +1,278 @@
+use crate::{
+    action::{run_action, stop},
+    Context, Error,
+};
+use async_recursion::async_recursion;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeStatus {
+    Success,
+    Failure,
+}
+
+/// Node of a behavior tree.
+///
+/// Control nodes (`sequence`, `fallback`, `parallel`, `retry` and `timeout`) decide which
+/// children are ticked, and the other nodes are actions using `RobotClient`.
+/// Failures of the actions are handled by the control nodes, but cancellation stops the whole tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Node {
+    /// Ticks the children in order until one of them fails.
+    Sequence { children: Vec<Node> },
+    /// Ticks the children in order until one of them succeeds.
+    Fallback { children: Vec<Node> },
+    /// Ticks the children concurrently.
+    /// Succeeds if `success_threshold` children succeed. All children by default.
+    Parallel {
+        children: Vec<Node>,
+        #[serde(default)]
+        success_threshold: Option<usize>,
+    },
+    /// Ticks the child until it succeeds, at most `max_attempts` times.
+    Retry {
+        max_attempts: usize,
+        child: Box<Node>,
+    },
+    /// Fails if the child does not finish within `timeout_secs`.
+    Timeout { timeout_secs: f64, child: Box<Node> },
+    /// Succeeds after `secs`.
+    Wait { secs: f64 },
+    /// `RobotClient::send_joints_pose`
+    SendJointsPose {
+        client_name: String,
+        pose_name: String,
+        #[serde(default = "default_duration_secs")]
+        duration_secs: f64,
+    },
+    /// `RobotClient::send_joint_positions`
+    SendJoints {
+        client_name: String,
+        positions: Vec<f64>,
+        #[serde(default = "default_duration_secs")]
+        duration_secs: f64,
+    },
+    /// Sends `position` to all joints of the gripper client.
+    Gripper {
+        client_name: String,
+        position: f64,
+        #[serde(default = "default_duration_secs")]
+        duration_secs: f64,
+    },
+    /// `RobotClient::move_ik`
+    MoveIk {
+        client_name: String,
+        x: f64,
+        y: f64,
+        z: f64,
+        #[serde(default)]
+        roll: f64,
+        #[serde(default)]
+        pitch: f64,
+        #[serde(default)]
+        yaw: f64,
+        #[serde(default = "default_duration_secs")]
+        duration_secs: f64,
+    },
+    /// `Navigation::move_to`
+    MoveTo {
+        x: f64,
+        y: f64,
+        yaw: f64,
+        #[serde(default = "default_frame_id")]
+        frame_id: String,
+        #[serde(default = "default_timeout_secs")]
+        timeout_secs: f64,
+    },
+    /// `RobotClient::speak`
+    Speak {
+        speaker_name: String,
+        message: String,
+    },
+}
+
+fn default_duration_secs() -> f64 {
+    3.0
+}
+
+fn default_frame_id() -> String {
+    "map".to_owned()
+}
+
+fn default_timeout_secs() -> f64 {
+    100.0
+}
+
+impl Node {
+    /// Name of the node type used in the logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Node::Sequence { .. } => "sequence",
+            Node::Fallback { .. } => "fallback",
+            Node::Parallel { .. } => "parallel",
+            Node::Retry { .. } => "retry",
+            Node::Timeout { .. } => "timeout",
+            Node::Wait { .. } => "wait",
+            Node::SendJointsPose { .. } => "send_joints_pose",
+            Node::SendJoints { .. } => "send_joints",
+            Node::Gripper { .. } => "gripper",
+            Node::MoveIk { .. } => "move_ik",
+            Node::MoveTo { .. } => "move_to",
+            Node::Speak { .. } => "speak",
+        }
+    }
+
+    /// Children of the control nodes.
+    pub fn children(&self) -> Vec<&Node> {
+        match self {
+            Node::Sequence { children }
+            | Node::Fallback { children }
+            | Node::Parallel { children, .. } => children.iter().collect(),
+            Node::Retry { child, .. } | Node::Timeout { child, .. } => vec![child],
+            _ => vec![],
+        }
+    }
+
+    /// Checks the parameters of this node and all descendants.
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |message: String| Err(Error::InvalidNode(self.kind().to_owned(), message));
+        match self {
+            Node::Parallel {
+                children,
+                success_threshold: Some(threshold),
+            } if *threshold > children.len() => {
+                return invalid(format!(
+                    "success_threshold {} is larger than the number of children {}",
+                    threshold,
+                    children.len()
+                ));
+            }
+            Node::Retry { max_attempts, .. } if *max_attempts == 0 => {
+                return invalid("max_attempts must be positive".to_owned());
+            }
+            Node::Timeout { timeout_secs, .. } if *timeout_secs <= 0.0 || timeout_secs.is_nan() => {
+                return invalid(format!("timeout_secs {} must be positive", timeout_secs));
+            }
+            Node::Wait { secs } if *secs < 0.0 || secs.is_nan() => {
+                return invalid(format!("secs {} must not be negative", secs));
+            }
+            _ => {}
+        }
+        for child in self.children() {
+            child.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Stops the motion of all actions of this node and the descendants, in the same way as
+    /// cancellation.
+    #[async_recursion]
+    async fn stop_actions(&self, context: &Context) {
+        let children = self.children();
+        if children.is_empty() {
+            stop(self, &context.client).await;
+        }
+        for child in children {
+            child.stop_actions(context).await;
+        }
+    }
+
+    /// Ticks this node until it finishes. `path` is used to identify the node in the logs.
+    #[async_recursion]
+    pub(crate) async fn tick(&self, context: &Context, path: &str) -> Result<NodeStatus, Error> {
+        context.check_canceled()?;
+        debug!("{}: start", path);
+        let child_path =
+            |index: usize, child: &Node| format!("{}/{}:{}", path, index, child.kind());
+        let status = match self {
+            Node::Sequence { children } => {
+                let mut status = NodeStatus::Success;
+                for (i, child) in children.iter().enumerate() {
+                    if child.tick(context, &child_path(i, child)).await? == NodeStatus::Failure {
+                        status = NodeStatus::Failure;
+                        break;
+                    }
+                }
+                status
+            }
+            Node::Fallback { children } => {
+                let mut status = NodeStatus::Failure;
+                for (i, child) in children.iter().enumerate() {
+                    if child.tick(context, &child_path(i, child)).await? == NodeStatus::Success {
+                        status = NodeStatus::Success;
+                        break;
+                    }
+                }
+                status
+            }
+            Node::Parallel {
+                children,
+                success_threshold,
+            } => {
+                let paths: Vec<_> = children
+                    .iter()
+                    .enumerate()
+                    .map(|(i, child)| child_path(i, child))
+                    .collect();
+                let results = join_all(
+                    children
+                        .iter()
+                        .zip(&paths)
+                        .map(|(child, path)| child.tick(context, path)),
+                )
+                .await;
+                let mut successes = 0;
+                for result in results {
+                    if result? == NodeStatus::Success {
+                        successes += 1;
+                    }
+                }
+                if successes >= success_threshold.unwrap_or_else(|| children.len()) {
+                    NodeStatus::Success
+                } else {
+                    NodeStatus::Failure
+                }
+            }
+            Node::Retry {
+                max_attempts,
+                child,
+            } => {
+                let mut status = NodeStatus::Failure;
+                for attempt in 1..=*max_attempts {
+                    status = child.tick(context, &child_path(0, child)).await?;
+                    if status == NodeStatus::Success {
+                        break;
+                    }
+                    warn!("{}: attempt {}/{} failed", path, attempt, max_attempts);
+                }
+                status
+            }
+            Node::Timeout {
+                timeout_secs,
+                child,
+            } => {
+                match tokio::time::timeout(
+                    Duration::from_secs_f64(*timeout_secs),
+                    child.tick(context, &child_path(0, child)),
+                )
+                .await
+                {
+                    Ok(result) => result?,
+                    Err(_) => {
+                        warn!("{}: timeout ({} secs)", path, timeout_secs);
+                        // The motion may continue after the action is dropped.
+                        child.stop_actions(context).await;
+                        NodeStatus::Failure
+                    }
+                }
+            }
+            _ => run_action(self, context, path).await?,
+        };
+        info!("{}: {:?}", path, status);
+        Ok(status)
+    }
+}
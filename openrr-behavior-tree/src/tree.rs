@@ -0,0 +1,150 @@
+use crate::{Error, Node, NodeStatus};
+use openrr_client::ArcRobotClient;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::info;
+
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Behavior tree definition.
+///
+/// ```toml
+/// [root]
+/// type = "sequence"
+///
+/// [[root.children]]
+/// type = "send_joints_pose"
+/// client_name = "arm"
+/// pose_name = "zero"
+///
+/// [[root.children]]
+/// type = "speak"
+/// speaker_name = "Default"
+/// message = "done"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BehaviorTreeConfig {
+    pub root: Node,
+}
+
+impl BehaviorTreeConfig {
+    pub fn try_new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let config: BehaviorTreeConfig = toml::from_str(
+            &std::fs::read_to_string(&path)
+                .map_err(|e| Error::NoFile(path.as_ref().to_owned(), e))?,
+        )
+        .map_err(|e| Error::TomlParseFailure(path.as_ref().to_owned(), e))?;
+        Ok(config)
+    }
+}
+
+pub(crate) struct Context {
+    pub(crate) client: Arc<ArcRobotClient>,
+    is_canceled: Arc<AtomicBool>,
+}
+
+impl Context {
+    pub(crate) fn check_canceled(&self) -> Result<(), Error> {
+        if self.is_canceled.load(Ordering::Relaxed) {
+            Err(Error::Canceled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Completes when the tree is canceled.
+    pub(crate) async fn canceled(&self) {
+        while !self.is_canceled.load(Ordering::Relaxed) {
+            tokio::time::sleep(CANCEL_CHECK_INTERVAL).await;
+        }
+    }
+}
+
+/// Runs a behavior tree using `RobotClient`.
+///
+/// `cancel` stops the running action and the whole tree, e.g. from another task or teleop.
+pub struct BehaviorTree {
+    root: Node,
+    context: Context,
+    is_running: AtomicBool,
+}
+
+impl BehaviorTree {
+    pub fn try_new(root: Node, client: Arc<ArcRobotClient>) -> Result<Self, Error> {
+        root.validate()?;
+        Ok(Self {
+            root,
+            context: Context {
+                client,
+                is_canceled: Arc::new(AtomicBool::new(false)),
+            },
+            is_running: AtomicBool::new(false),
+        })
+    }
+
+    pub fn new_from_config(
+        config: BehaviorTreeConfig,
+        client: Arc<ArcRobotClient>,
+    ) -> Result<Self, Error> {
+        Self::try_new(config.root, client)
+    }
+
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Ticks the root node until the tree finishes.
+    ///
+    /// Returns `Error::Canceled` if `cancel` is called while running, and
+    /// `Error::AlreadyRunning` if the tree is running in another task.
+    pub async fn run(&self) -> Result<NodeStatus, Error> {
+        self.set_running()?;
+        self.tick_root().await
+    }
+
+    /// Runs the tree in a new task.
+    ///
+    /// Unlike spawning `run`, the tree is running when this returns, so `cancel` right after
+    /// this is not missed.
+    pub fn spawn(self: &Arc<Self>) -> Result<JoinHandle<Result<NodeStatus, Error>>, Error> {
+        self.set_running()?;
+        let tree = self.clone();
+        Ok(tokio::spawn(async move { tree.tick_root().await }))
+    }
+
+    fn set_running(&self) -> Result<(), Error> {
+        self.is_running
+            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .map(|_| ())
+            .map_err(|_| Error::AlreadyRunning)
+    }
+
+    async fn tick_root(&self) -> Result<NodeStatus, Error> {
+        info!("Start behavior tree");
+        let result = self.root.tick(&self.context, self.root.kind()).await;
+        // Clears the cancel request only after the tree finishes.
+        self.context.is_canceled.store(false, Ordering::Relaxed);
+        self.is_running.store(false, Ordering::Relaxed);
+        info!("Finish behavior tree: {:?}", result);
+        result
+    }
+
+    /// Cancels the tree if it is running.
+    pub fn cancel(&self) {
+        if self.is_running() {
+            self.context.is_canceled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,298 @@
+use arci::{
+    async_trait, DummyJointTrajectoryClient, DummyLocalization, DummyMoveBase, DummyNavigation,
+    JointTrajectoryClient, Localization, MoveBase, Navigation, NavigationFeedback,
+    NavigationStatus,
+};
+use k::nalgebra::Isometry2;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use openrr_behavior_tree::*;
+use openrr_client::{ArcRobotClient, OpenrrClientsConfig, RobotClient};
+
+fn robot_client() -> Arc<ArcRobotClient> {
+    robot_client_with_navigation(Arc::new(DummyNavigation::new()))
+}
+
+fn robot_client_with_navigation(navigation: Arc<dyn Navigation>) -> Arc<ArcRobotClient> {
+    let mut raw_joint_trajectory_clients = HashMap::new();
+    raw_joint_trajectory_clients.insert(
+        "arm".to_owned(),
+        Arc::new(DummyJointTrajectoryClient::new(
+            vec![
+                "l_shoulder_yaw",
+                "l_shoulder_pitch",
+                "l_shoulder_roll",
+                "l_elbow_pitch",
+                "l_wrist_yaw",
+                "l_wrist_pitch",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        )) as Arc<dyn JointTrajectoryClient>,
+    );
+    // The sample URDF of openrr-planner.
+    let mut config: OpenrrClientsConfig = toml::from_str(
+        r#"
+urdf_path = "../openrr-planner/sample.urdf"
+
+[[joints_poses]]
+pose_name = "home"
+client_name = "arm"
+positions = [1.0, 2.0, 0.0, 0.0, 0.0, 0.0]
+"#,
+    )
+    .unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    Arc::new(
+        RobotClient::try_new(
+            config,
+            raw_joint_trajectory_clients,
+            HashMap::new(),
+            Some(Arc::new(DummyLocalization::new()) as Arc<dyn Localization>),
+            Some(Arc::new(DummyMoveBase::new()) as Arc<dyn MoveBase>),
+            Some(navigation),
+            None,
+        )
+        .unwrap(),
+    )
+}
+
+fn tree(config: &str) -> (BehaviorTree, Arc<ArcRobotClient>) {
+    let config: BehaviorTreeConfig = toml::from_str(config).unwrap();
+    let client = robot_client();
+    (
+        BehaviorTree::new_from_config(config, client.clone()).unwrap(),
+        client,
+    )
+}
+
+#[tokio::test]
+async fn test_sequence() {
+    let (tree, client) = tree(
+        r#"
+[root]
+type = "sequence"
+
+[[root.children]]
+type = "send_joints_pose"
+client_name = "arm"
+pose_name = "home"
+
+[[root.children]]
+type = "gripper"
+client_name = "arm"
+position = 0.5
+"#,
+    );
+    assert_eq!(tree.run().await.unwrap(), NodeStatus::Success);
+    assert_eq!(
+        client.current_joint_positions("arm").await.unwrap(),
+        vec![0.5; 6]
+    );
+}
+
+#[tokio::test]
+async fn test_sequence_failure() {
+    let (tree, client) = tree(
+        r#"
+[root]
+type = "sequence"
+
+[[root.children]]
+type = "send_joints_pose"
+client_name = "arm"
+pose_name = "unknown"
+
+[[root.children]]
+type = "send_joints_pose"
+client_name = "arm"
+pose_name = "home"
+"#,
+    );
+    assert_eq!(tree.run().await.unwrap(), NodeStatus::Failure);
+    assert_eq!(
+        client.current_joint_positions("arm").await.unwrap(),
+        vec![0.0; 6]
+    );
+}
+
+#[tokio::test]
+async fn test_fallback_and_retry() {
+    let (tree, client) = tree(
+        r#"
+[root]
+type = "fallback"
+
+[[root.children]]
+type = "retry"
+max_attempts = 2
+child = { type = "send_joints", client_name = "leg", positions = [1.0] }
+
+[[root.children]]
+type = "send_joints"
+client_name = "arm"
+positions = [0.3, 0.4, 0.0, 0.0, 0.0, 0.0]
+"#,
+    );
+    assert_eq!(tree.run().await.unwrap(), NodeStatus::Success);
+    assert_eq!(
+        client.current_joint_positions("arm").await.unwrap(),
+        vec![0.3, 0.4, 0.0, 0.0, 0.0, 0.0]
+    );
+}
+
+#[tokio::test]
+async fn test_parallel_and_timeout() {
+    let (tree, _client) = tree(
+        r#"
+[root]
+type = "parallel"
+success_threshold = 1
+
+[[root.children]]
+type = "timeout"
+timeout_secs = 0.05
+child = { type = "wait", secs = 10.0 }
+
+[[root.children]]
+type = "move_to"
+x = 1.0
+y = 2.0
+yaw = 0.0
+"#,
+    );
+    assert_eq!(tree.run().await.unwrap(), NodeStatus::Success);
+}
+
+/// Navigation which does not reach the goal until it is canceled.
+#[derive(Default)]
+struct EndlessNavigation {
+    is_canceled: AtomicBool,
+}
+
+#[async_trait]
+impl Navigation for EndlessNavigation {
+    async fn move_to(
+        &self,
+        _goal: Isometry2<f64>,
+        _frame_id: &str,
+        _timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        while !self.is_canceled.load(Ordering::Relaxed) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    }
+    fn cancel(&self) -> Result<(), arci::Error> {
+        self.is_canceled.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+    fn status(&self) -> Result<NavigationStatus, arci::Error> {
+        Ok(NavigationStatus::Active)
+    }
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, arci::Error> {
+        Ok(None)
+    }
+    fn plan_path(
+        &self,
+        goal: Isometry2<f64>,
+        _frame_id: &str,
+    ) -> Result<Vec<Isometry2<f64>>, arci::Error> {
+        Ok(vec![goal])
+    }
+}
+
+#[tokio::test]
+async fn test_timeout_stops_action() {
+    let navigation = Arc::new(EndlessNavigation::default());
+    let config: BehaviorTreeConfig = toml::from_str(
+        r#"
+[root]
+type = "timeout"
+timeout_secs = 0.05
+child = { type = "move_to", x = 1.0, y = 2.0, yaw = 0.0 }
+"#,
+    )
+    .unwrap();
+    let tree =
+        BehaviorTree::new_from_config(config, robot_client_with_navigation(navigation.clone()))
+            .unwrap();
+    assert_eq!(tree.run().await.unwrap(), NodeStatus::Failure);
+    assert!(navigation.is_canceled.load(Ordering::Relaxed));
+}
+
+#[tokio::test]
+async fn test_cancel() {
+    let (tree, _client) = tree(
+        r#"
+[root]
+type = "wait"
+secs = 10.0
+"#,
+    );
+    let tree = Arc::new(tree);
+    let tree_clone = tree.clone();
+    let handle = tokio::spawn(async move { tree_clone.run().await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(tree.is_running());
+    tree.cancel();
+    let result = tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(result, Err(Error::Canceled)));
+    assert!(!tree.is_running());
+}
+
+#[test]
+fn test_validate() {
+    let config: BehaviorTreeConfig = toml::from_str(
+        r#"
+[root]
+type = "sequence"
+
+[[root.children]]
+type = "retry"
+max_attempts = 0
+child = { type = "wait", secs = 1.0 }
+"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        BehaviorTree::new_from_config(config, robot_client()),
+        Err(Error::InvalidNode(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_spawn_and_cancel() {
+    let (tree, _client) = tree(
+        r#"
+[root]
+type = "wait"
+secs = 10.0
+"#,
+    );
+    let tree = Arc::new(tree);
+    // Canceling the stopped tree does not affect the next run.
+    tree.cancel();
+    let handle = tree.spawn().unwrap();
+    assert!(tree.is_running());
+    assert!(matches!(tree.spawn(), Err(Error::AlreadyRunning)));
+    // The cancel before the first tick is not missed.
+    tree.cancel();
+    let result = tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(result, Err(Error::Canceled)));
+    assert!(!tree.is_running());
+}
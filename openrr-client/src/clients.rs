@@ -4,6 +4,8 @@ mod collision_check_client;
 mod ik_client;
 mod local_move;
 mod print_speaker;
+mod recorder;
+mod replayer;
 
 pub use chain_wrapper::*;
 pub use collision_avoid_client::*;
@@ -11,3 +13,5 @@ pub use collision_check_client::*;
 pub use ik_client::*;
 pub use local_move::*;
 pub use print_speaker::*;
+pub use recorder::*;
+pub use replayer::*;
@@ -1,9 +1,9 @@
-use arci::{Error, JointTrajectoryClient, TrajectoryPoint};
+use arci::{Error, JointTrajectoryClient, TrajectoryPoint, TransformResolver};
 use async_trait::async_trait;
 use k::Isometry3;
 use k::{nalgebra as na, Constraints};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 pub fn isometry(x: f64, y: f64, z: f64, roll: f64, pitch: f64, yaw: f64) -> k::Isometry3<f64> {
     k::Isometry3::from_parts(
@@ -79,6 +79,52 @@ impl IkSolverWithChain {
     pub fn constraints(&self) -> &Constraints {
         &self.constraints
     }
+    /// Name of the frame in which IK target poses are expressed, i.e. the parent link of the
+    /// first joint of the IK chain (the root link if the chain starts from the root).
+    pub fn root_frame(&self) -> Option<String> {
+        let first = self.ik_arm.iter().next()?;
+        let node = first.parent().unwrap_or_else(|| first.clone());
+        let link = node.link();
+        link.as_ref().map(|link| link.name.to_owned())
+    }
+    /// Name of the end link of the IK chain (the link of `ik_target`).
+    pub fn end_link_name(&self) -> Option<String> {
+        let end = self.ik_arm.iter().last()?;
+        let link = end.link();
+        link.as_ref().map(|link| link.name.to_owned())
+    }
+    /// Create the tool frame from the config if the tool is attached to the end link
+    /// or one of its descendants by fixed joints. Returns `None` if such a link is not found,
+    /// because the offset of the tool would change with the joints which IK does not solve.
+    pub fn tool_frame(&self, config: &ToolFrameConfig) -> Option<ToolFrame> {
+        fn find_offset(node: &k::Node<f64>, link_name: &str) -> Option<Isometry3<f64>> {
+            for child in node.children().iter() {
+                if !matches!(child.joint().joint_type, k::JointType::Fixed) {
+                    continue;
+                }
+                let local_transform = child.joint().local_transform();
+                let is_target =
+                    matches!(child.link().as_ref(), Some(link) if link.name == link_name);
+                if is_target {
+                    return Some(local_transform);
+                }
+                if let Some(offset) = find_offset(child, link_name) {
+                    return Some(local_transform * offset);
+                }
+            }
+            None
+        }
+        let end = self.ik_arm.iter().last()?;
+        let offset = if self.end_link_name()? == config.link {
+            Isometry3::identity()
+        } else {
+            find_offset(end, &config.link)?
+        };
+        Some(ToolFrame {
+            name: config.name.to_owned(),
+            offset: offset * config.transform(),
+        })
+    }
     pub fn generate_trajectory_with_interpolation(
         &self,
         current_pose: &Isometry3<f64>,
@@ -105,6 +151,31 @@ impl IkSolverWithChain {
         max_resolution: f64,
         min_number_of_points: i32,
     ) -> Result<Vec<TrajectoryPoint>, Error> {
+        self.generate_trajectory_with_interpolation_and_constraints_for_tool(
+            current_pose,
+            target_pose,
+            &Isometry3::identity(),
+            constraints,
+            duration_sec,
+            max_resolution,
+            min_number_of_points,
+        )
+    }
+    /// Same as `generate_trajectory_with_interpolation_and_constraints` but the poses are the
+    /// poses of the tool whose pose in the end link frame is `tool_offset`.
+    /// Poses are interpolated at the tool, not at the end link.
+    #[allow(clippy::too_many_arguments)]
+    pub fn generate_trajectory_with_interpolation_and_constraints_for_tool(
+        &self,
+        current_pose: &Isometry3<f64>,
+        target_pose: &Isometry3<f64>,
+        tool_offset: &Isometry3<f64>,
+        constraints: &Constraints,
+        duration_sec: f64,
+        max_resolution: f64,
+        min_number_of_points: i32,
+    ) -> Result<Vec<TrajectoryPoint>, Error> {
+        let tool_offset_inverse = tool_offset.inverse();
         let target_position = target_pose.translation.vector;
         let target_rotation = target_pose.rotation;
         let current_position = current_pose.translation.vector;
@@ -121,7 +192,8 @@ impl IkSolverWithChain {
             let tar_pos = current_position.lerp(&target_position, t);
             let tar_rot = current_rotation.slerp(&target_rotation, t);
             self.solve_with_constraints(
-                &k::Isometry3::from_parts(na::Translation3::from(tar_pos), tar_rot),
+                &(k::Isometry3::from_parts(na::Translation3::from(tar_pos), tar_rot)
+                    * tool_offset_inverse),
                 constraints,
             )?;
             let trajectory = TrajectoryPoint::new(
@@ -132,6 +204,185 @@ impl IkSolverWithChain {
         }
         Ok(traj)
     }
+    /// Jacobian of the end link (in the root frame) at the current joint positions of the chain.
+    ///
+    /// The rows are the linear and the angular velocity, and the columns are the joints.
+    pub fn jacobian(&self) -> na::Matrix6xX<f64> {
+        let jacobian = k::jacobian(&self.ik_arm);
+        assert_eq!(jacobian.nrows(), 6);
+        // Both are column-major.
+        na::Matrix6xX::from_iterator(jacobian.ncols(), jacobian.iter().cloned())
+    }
+    /// Joint velocities to move the end link by `twist` (in the root frame) from the current
+    /// joint positions of the chain.
+    pub fn servo_joint_velocities(&self, twist: &Twist, config: &ServoConfig) -> Vec<f64> {
+        let jacobian = self.jacobian();
+        let positions = self.ik_arm.joint_positions();
+        let limits: Vec<_> = self
+            .ik_arm
+            .iter_joints()
+            .map(|joint| joint.limits.as_ref().map(|range| (range.min, range.max)))
+            .collect();
+        damped_servo_joint_velocities(&jacobian, &positions, &limits, twist, config)
+    }
+    /// Trajectory point to move the tool by `twist` (in the root frame) for `duration` from
+    /// `current_positions`.
+    ///
+    /// `tool_offset` is the pose of the tool in the end link frame.
+    pub fn servo_trajectory_point(
+        &self,
+        current_positions: &[f64],
+        tool_offset: &Isometry3<f64>,
+        twist: &Twist,
+        config: &ServoConfig,
+        duration: std::time::Duration,
+    ) -> TrajectoryPoint {
+        self.set_joint_positions_clamped(current_positions);
+        let end_pose = self.end_transform();
+        let tool_pose = end_pose * tool_offset;
+        // Velocity of the end link origin on the same rigid body.
+        let end_twist = Twist::new(
+            twist.linear
+                + twist
+                    .angular
+                    .cross(&(end_pose.translation.vector - tool_pose.translation.vector)),
+            twist.angular,
+        );
+        let velocities = self.servo_joint_velocities(&end_twist, config);
+        let dt = duration.as_secs_f64();
+        let target_positions: Vec<_> = self
+            .joint_positions()
+            .iter()
+            .zip(&velocities)
+            .map(|(position, velocity)| position + velocity * dt)
+            .collect();
+        self.set_joint_positions_clamped(&target_positions);
+        TrajectoryPoint {
+            positions: self.joint_positions(),
+            velocities: Some(velocities),
+            time_from_start: duration,
+        }
+    }
+}
+
+/// Linear [m/s] and angular [rad/s] velocity.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Twist {
+    pub linear: na::Vector3<f64>,
+    pub angular: na::Vector3<f64>,
+}
+
+impl Twist {
+    pub fn new(linear: na::Vector3<f64>, angular: na::Vector3<f64>) -> Self {
+        Self { linear, angular }
+    }
+}
+
+/// Parameters of the Cartesian velocity (servo) control.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ServoConfig {
+    /// Damping factor of the damped least squares, which limits the joint velocities near
+    /// singularities.
+    #[serde(default = "default_servo_damping")]
+    pub damping: f64,
+    /// The velocity is scaled down while the manipulability is smaller than this.
+    #[serde(default = "default_servo_singularity_threshold")]
+    pub singularity_threshold: f64,
+    /// The velocity is scaled down while a joint moves within this distance [rad or m] to its limit.
+    #[serde(default = "default_servo_joint_limit_margin")]
+    pub joint_limit_margin: f64,
+    /// Maximum joint velocity [rad/s or m/s].
+    #[serde(default = "default_servo_max_joint_velocity")]
+    pub max_joint_velocity: f64,
+}
+
+impl Default for ServoConfig {
+    fn default() -> Self {
+        Self {
+            damping: default_servo_damping(),
+            singularity_threshold: default_servo_singularity_threshold(),
+            joint_limit_margin: default_servo_joint_limit_margin(),
+            max_joint_velocity: default_servo_max_joint_velocity(),
+        }
+    }
+}
+
+fn default_servo_damping() -> f64 {
+    0.05
+}
+fn default_servo_singularity_threshold() -> f64 {
+    0.01
+}
+fn default_servo_joint_limit_margin() -> f64 {
+    0.1
+}
+fn default_servo_max_joint_velocity() -> f64 {
+    1.0
+}
+
+/// Damped least squares with the slowdown near singularities and joint limits.
+///
+/// All joint velocities are scaled by the same ratio to keep the direction of the end link.
+fn damped_servo_joint_velocities(
+    jacobian: &na::Matrix6xX<f64>,
+    positions: &[f64],
+    limits: &[Option<(f64, f64)>],
+    twist: &Twist,
+    config: &ServoConfig,
+) -> Vec<f64> {
+    let target = na::Vector6::new(
+        twist.linear.x,
+        twist.linear.y,
+        twist.linear.z,
+        twist.angular.x,
+        twist.angular.y,
+        twist.angular.z,
+    );
+    let jacobian_t = jacobian.transpose();
+    let jjt = jacobian * &jacobian_t;
+    let damped = jjt + na::Matrix6::identity() * config.damping.powi(2);
+    let velocities = match damped.try_inverse() {
+        Some(inverse) => &jacobian_t * (inverse * target),
+        None => return vec![0.0; positions.len()],
+    };
+
+    let manipulability = if jacobian.ncols() >= 6 {
+        jjt.determinant()
+    } else {
+        (&jacobian_t * jacobian).determinant()
+    }
+    .max(0.0)
+    .sqrt();
+    let mut scale = if config.singularity_threshold > 0.0 {
+        (manipulability / config.singularity_threshold).min(1.0)
+    } else {
+        1.0
+    };
+    for ((velocity, position), limit) in velocities.iter().zip(positions).zip(limits) {
+        let distance = match limit {
+            Some((_, max)) if *velocity > 0.0 => max - position,
+            Some((min, _)) if *velocity < 0.0 => position - min,
+            _ => continue,
+        };
+        if config.joint_limit_margin > 0.0 {
+            scale = scale.min((distance / config.joint_limit_margin).clamp(0.0, 1.0));
+        } else if distance <= 0.0 {
+            scale = 0.0;
+        }
+    }
+    let max_velocity = velocities.amax() * scale;
+    if max_velocity > config.max_joint_velocity {
+        scale *= config.max_joint_velocity / max_velocity;
+    }
+    velocities.iter().map(|velocity| velocity * scale).collect()
+}
+
+/// Tool center point attached to the end link of an IK chain.
+#[derive(Clone, Debug)]
+pub struct ToolFrame {
+    pub name: String,
+    /// Pose of the tool in the end link (IK target) frame.
+    pub offset: Isometry3<f64>,
 }
 
 pub struct IkClient<T>
@@ -140,6 +391,7 @@ where
 {
     pub client: T,
     pub ik_solver_with_chain: Arc<IkSolverWithChain>,
+    tool: Mutex<Option<ToolFrame>>,
 }
 
 impl<T> IkClient<T>
@@ -163,13 +415,34 @@ where
         Self {
             client,
             ik_solver_with_chain,
+            tool: Mutex::new(None),
         }
     }
 
+    /// Set the active tool. If `None`, the IK target link itself is used.
+    pub fn set_tool(&self, tool: Option<ToolFrame>) {
+        *self.tool.lock().unwrap() = tool;
+    }
+    pub fn tool(&self) -> Option<ToolFrame> {
+        self.tool.lock().unwrap().clone()
+    }
+    /// Pose of the active tool in the IK target frame (identity if no tool is active).
+    pub fn tool_offset(&self) -> Isometry3<f64> {
+        self.tool
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map_or_else(Isometry3::identity, |tool| tool.offset)
+    }
+    /// Pose of the active tool calculated from the current joint positions of the IK chain.
+    pub fn end_transform(&self) -> Isometry3<f64> {
+        self.ik_solver_with_chain.end_transform() * self.tool_offset()
+    }
+
     pub fn current_end_transform(&self) -> Result<k::Isometry3<f64>, Error> {
         let current_joint_angles = self.client.current_joint_positions()?;
         self.set_joint_positions_clamped(&current_joint_angles);
-        Ok(self.ik_solver_with_chain.end_transform())
+        Ok(self.end_transform())
     }
 
     pub async fn move_ik_with_constraints(
@@ -179,7 +452,7 @@ where
         duration_sec: f64,
     ) -> Result<(), Error> {
         self.ik_solver_with_chain
-            .solve_with_constraints(&target_pose, constraints)?;
+            .solve_with_constraints(&(target_pose * self.tool_offset().inverse()), constraints)?;
 
         let positions = self.ik_solver_with_chain.joint_positions();
         let duration = std::time::Duration::from_secs_f64(duration_sec);
@@ -194,9 +467,10 @@ where
     ) -> Result<(), Error> {
         let mut traj = self
             .ik_solver_with_chain
-            .generate_trajectory_with_interpolation_and_constraints(
+            .generate_trajectory_with_interpolation_and_constraints_for_tool(
                 &self.current_end_transform()?,
                 target_pose,
+                &self.tool_offset(),
                 constraints,
                 duration_sec,
                 0.05,
@@ -213,11 +487,8 @@ where
         target_pose: &k::Isometry3<f64>,
         duration_sec: f64,
     ) -> Result<(), Error> {
-        self.ik_solver_with_chain.solve(&target_pose)?;
-
-        let positions = self.ik_solver_with_chain.joint_positions();
-        let duration = std::time::Duration::from_secs_f64(duration_sec);
-        self.client.send_joint_positions(positions, duration).await
+        self.move_ik_with_constraints(target_pose, self.constraints(), duration_sec)
+            .await
     }
 
     pub async fn move_ik_with_interpolation(
@@ -225,19 +496,58 @@ where
         target_pose: &k::Isometry3<f64>,
         duration_sec: f64,
     ) -> Result<(), Error> {
-        let mut traj = self
-            .ik_solver_with_chain
-            .generate_trajectory_with_interpolation(
-                &self.current_end_transform()?,
-                target_pose,
-                duration_sec,
-                0.05,
-                10,
-            )?;
-        let dof = self.client.joint_names().len();
-        traj.first_mut().unwrap().velocities = Some(vec![0.0; dof]);
-        traj.last_mut().unwrap().velocities = Some(vec![0.0; dof]);
-        self.client.send_joint_trajectory(traj).await
+        self.move_ik_with_interpolation_and_constraints(
+            target_pose,
+            self.constraints(),
+            duration_sec,
+        )
+        .await
+    }
+
+    /// Convert the pose in `frame_id` into the pose in the IK root frame using `resolver`.
+    pub fn pose_in_root_frame(
+        &self,
+        resolver: &dyn TransformResolver,
+        pose: &k::Isometry3<f64>,
+        frame_id: &str,
+    ) -> Result<k::Isometry3<f64>, Error> {
+        let root_frame =
+            self.ik_solver_with_chain
+                .root_frame()
+                .ok_or_else(|| Error::Uninitialized {
+                    message: "root link of IK chain".to_owned(),
+                })?;
+        if root_frame == frame_id {
+            return Ok(*pose);
+        }
+        Ok(
+            resolver.resolve_transformation(&root_frame, frame_id, std::time::SystemTime::now())?
+                * pose,
+        )
+    }
+
+    /// Move to the target pose in `frame_id`, which is converted into the IK root frame at call time.
+    pub async fn move_ik_in_frame(
+        &self,
+        resolver: &dyn TransformResolver,
+        target_pose: &k::Isometry3<f64>,
+        frame_id: &str,
+        duration_sec: f64,
+    ) -> Result<(), Error> {
+        let target_pose = self.pose_in_root_frame(resolver, target_pose, frame_id)?;
+        self.move_ik(&target_pose, duration_sec).await
+    }
+
+    pub async fn move_ik_with_interpolation_in_frame(
+        &self,
+        resolver: &dyn TransformResolver,
+        target_pose: &k::Isometry3<f64>,
+        frame_id: &str,
+        duration_sec: f64,
+    ) -> Result<(), Error> {
+        let target_pose = self.pose_in_root_frame(resolver, target_pose, frame_id)?;
+        self.move_ik_with_interpolation(&target_pose, duration_sec)
+            .await
     }
 
     /// Get relative pose from current pose of the IK target
@@ -254,6 +564,33 @@ where
         Ok(())
     }
 
+    /// Moves the active tool by `twist` (in the IK root frame) for `duration` from the current
+    /// joint positions using the Jacobian.
+    ///
+    /// This is intended to be called repeatedly with a period shorter than `duration`
+    /// (e.g. teleoperation), and each call replaces the previous trajectory.
+    pub async fn servo(&self, twist: &Twist, duration: std::time::Duration) -> Result<(), Error> {
+        self.servo_with_config(twist, &ServoConfig::default(), duration)
+            .await
+    }
+
+    pub async fn servo_with_config(
+        &self,
+        twist: &Twist,
+        config: &ServoConfig,
+        duration: std::time::Duration,
+    ) -> Result<(), Error> {
+        let current_positions = self.client.current_joint_positions()?;
+        let point = self.ik_solver_with_chain.servo_trajectory_point(
+            &current_positions,
+            &self.tool_offset(),
+            twist,
+            config,
+            duration,
+        );
+        self.client.send_joint_trajectory(vec![point]).await
+    }
+
     pub fn constraints(&self) -> &Constraints {
         &self.ik_solver_with_chain.constraints()
     }
@@ -317,6 +654,34 @@ fn default_num_max_try() -> usize {
     300
 }
 
+/// Named tool frame (e.g. a suction cup or a pen) defined as an offset from a link.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ToolFrameConfig {
+    pub name: String,
+    /// Link to which the tool is attached. It must be the IK target link or fixed to it.
+    pub link: String,
+    /// `[x, y, z]`
+    #[serde(default)]
+    pub translation: [f64; 3],
+    /// `[roll, pitch, yaw]`
+    #[serde(default)]
+    pub rotation: [f64; 3],
+}
+
+impl ToolFrameConfig {
+    /// Pose of the tool in the `link` frame.
+    pub fn transform(&self) -> Isometry3<f64> {
+        isometry(
+            self.translation[0],
+            self.translation[1],
+            self.translation[2],
+            self.rotation[0],
+            self.rotation[1],
+            self.rotation[2],
+        )
+    }
+}
+
 pub fn create_ik_solver_with_chain(
     full_chain: &k::Chain<f64>,
     config: &IkSolverConfig,
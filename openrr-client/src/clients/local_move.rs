@@ -1,7 +1,15 @@
-use arci::{BaseVelocity, MoveBase, Navigation};
+use arci::{BaseVelocity, Localization, MoveBase};
 use k::nalgebra as na;
 use na::Isometry2;
 use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tracing::debug;
 
 use crate::Error;
 
@@ -14,6 +22,16 @@ pub struct LocalMoveConfig {
     pub angular_gain: f64,
     pub max_linear_vel: f64,
     pub max_angular_vel: f64,
+    /// Maximum change of the linear velocity per second. No limit if not specified.
+    #[serde(default = "default_max_acc")]
+    pub max_linear_acc: f64,
+    /// Maximum change of the angular velocity per second. No limit if not specified.
+    #[serde(default = "default_max_acc")]
+    pub max_angular_acc: f64,
+}
+
+fn default_max_acc() -> f64 {
+    f64::INFINITY
 }
 
 impl LocalMoveConfig {
@@ -25,34 +43,98 @@ impl LocalMoveConfig {
         .map_err(|e| Error::TomlParseFailure(path.as_ref().to_owned(), e))?;
         Ok(config)
     }
+
+    pub fn is_reached(&self, pose_error: Isometry2<f64>) -> bool {
+        pose_error.translation.vector.norm() < self.reach_distance_threshold
+            && pose_error.rotation.angle().abs() < self.reach_angle_threshold
+    }
+
+    pub fn control_velocity_from_pose_error(&self, pose_error: Isometry2<f64>) -> BaseVelocity {
+        // Calculate control velocity
+        // NOTE: pose_error = pose_reference - pose_current
+        let vel_x = na::clamp(
+            self.linear_gain * pose_error.translation.vector[0],
+            -self.max_linear_vel,
+            self.max_linear_vel,
+        );
+        let vel_y = na::clamp(
+            self.linear_gain * pose_error.translation.vector[1],
+            -self.max_linear_vel,
+            self.max_linear_vel,
+        );
+        let vel_theta = na::clamp(
+            self.angular_gain * pose_error.rotation.angle(),
+            -self.max_angular_vel,
+            self.max_angular_vel,
+        );
+        BaseVelocity::new(vel_x, vel_y, vel_theta)
+    }
+
+    /// Returns the velocity which is closest to `target` within the acceleration limits
+    /// from `current` in one control period.
+    pub fn limit_acceleration(
+        &self,
+        current: &BaseVelocity,
+        target: &BaseVelocity,
+    ) -> BaseVelocity {
+        let dt = 1.0 / self.control_frequency;
+        let max_linear_diff = self.max_linear_acc * dt;
+        let max_angular_diff = self.max_angular_acc * dt;
+        BaseVelocity::new(
+            current.x + na::clamp(target.x - current.x, -max_linear_diff, max_linear_diff),
+            current.y + na::clamp(target.y - current.y, -max_linear_diff, max_linear_diff),
+            current.theta
+                + na::clamp(
+                    target.theta - current.theta,
+                    -max_angular_diff,
+                    max_angular_diff,
+                ),
+        )
+    }
 }
 
-pub struct LocalMove<N, M>
+/// Moves the base to the goal pose precisely by feedback control using the current pose.
+pub struct LocalMove<L, M>
 where
-    N: Navigation,
+    L: Localization,
     M: MoveBase,
 {
-    pub nav_client: N,
+    pub localization_client: L,
     pub vel_client: M,
     pub config: LocalMoveConfig,
+    is_canceled: Arc<AtomicBool>,
 }
 
-impl<N, M> LocalMove<N, M>
+impl<L, M> LocalMove<L, M>
 where
-    N: Navigation,
+    L: Localization,
     M: MoveBase,
 {
-    pub fn new(nav_client: N, vel_client: M, config: LocalMoveConfig) -> Self {
+    pub fn new(localization_client: L, vel_client: M, config: LocalMoveConfig) -> Self {
         Self {
-            nav_client,
+            localization_client,
             vel_client,
             config,
+            is_canceled: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Share the cancellation flag with others. `move_to_pose` is canceled when `true` is stored.
+    pub fn with_cancel_flag(mut self, is_canceled: Arc<AtomicBool>) -> Self {
+        self.is_canceled = is_canceled;
+        self
+    }
+
     pub fn is_reached(&self, pose_error: Isometry2<f64>) -> bool {
-        pose_error.translation.vector.norm() < self.config.reach_distance_threshold
-            && pose_error.rotation.angle().abs() < self.config.reach_angle_threshold
+        self.config.is_reached(pose_error)
+    }
+
+    pub fn current_pose_error(
+        &self,
+        goal: &Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Isometry2<f64>, arci::Error> {
+        Ok(self.localization_client.current_pose(frame_id)?.inverse() * goal)
     }
 
     pub fn send_zero_velocity(&self) -> Result<(), arci::Error> {
@@ -64,33 +146,80 @@ where
         &self,
         pose_error: Isometry2<f64>,
     ) -> Result<(), arci::Error> {
-        // Calculate control velocity
-        // NOTE: pose_error = pose_reference - pose_current
-        let vel_x = na::clamp(
-            self.config.linear_gain * pose_error.translation.vector[0],
-            -self.config.max_linear_vel,
-            self.config.max_linear_vel,
-        );
-        let vel_y = na::clamp(
-            self.config.linear_gain * pose_error.translation.vector[1],
-            -self.config.max_linear_vel,
-            self.config.max_linear_vel,
-        );
-        let vel_theta = na::clamp(
-            self.config.angular_gain * pose_error.rotation.angle(),
-            -self.config.max_angular_vel,
-            self.config.max_angular_vel,
-        );
-
         self.vel_client
-            .send_velocity(&BaseVelocity::new(vel_x, vel_y, vel_theta))
+            .send_velocity(&self.config.control_velocity_from_pose_error(pose_error))
+    }
+
+    /// Move to the goal pose in `frame_id` and stop.
+    pub async fn move_to_pose(
+        &self,
+        goal: Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        self.is_canceled.store(false, Ordering::Relaxed);
+        let result = self.control_loop(&goal, frame_id, timeout).await;
+        let stop_result = self.send_zero_velocity();
+        result.and(stop_result)
+    }
+
+    /// Move to the goal pose relative to the current pose.
+    pub async fn move_to_relative(
+        &self,
+        relative_goal: Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        let goal = self.localization_client.current_pose(frame_id)? * relative_goal;
+        self.move_to_pose(goal, frame_id, timeout).await
+    }
+
+    pub fn cancel(&self) {
+        self.is_canceled.store(true, Ordering::Relaxed);
+    }
+
+    async fn control_loop(
+        &self,
+        goal: &Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        let period = Duration::from_secs_f64(1.0 / self.config.control_frequency);
+        let start_time = Instant::now();
+        let mut velocity = BaseVelocity::default();
+        loop {
+            if self.is_canceled.load(Ordering::Relaxed) {
+                return Err(anyhow::anyhow!("Local move is canceled").into());
+            }
+            let pose_error = self.current_pose_error(goal, frame_id)?;
+            if self.is_reached(pose_error) {
+                return Ok(());
+            }
+            if start_time.elapsed() > timeout {
+                return Err(anyhow::anyhow!(
+                    "Local move timeout ({:?}): pose error = {}",
+                    timeout,
+                    pose_error
+                )
+                .into());
+            }
+            velocity = self.config.limit_acceleration(
+                &velocity,
+                &self.config.control_velocity_from_pose_error(pose_error),
+            );
+            debug!("velocity: {:?}", velocity);
+            self.vel_client.send_velocity(&velocity)?;
+            tokio::time::sleep(period).await;
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use arci::{DummyMoveBase, DummyNavigation};
+    use crate::test_util::SimulatedBase;
+    use arci::{DummyLocalization, DummyMoveBase};
+    use assert_approx_eq::assert_approx_eq;
     use na::Vector2;
     #[test]
     fn test_config() {
@@ -108,8 +237,8 @@ mod tests {
         let config = LocalMoveConfig::try_new(path).unwrap();
         assert_eq!(config.linear_gain, 1.0);
         let move_base = DummyMoveBase::new();
-        let nav = DummyNavigation::new();
-        let local_move = LocalMove::new(Box::new(nav), Box::new(move_base), config);
+        let localization = DummyLocalization::new();
+        let local_move = LocalMove::new(Box::new(localization), Box::new(move_base), config);
 
         // Check convergence evaluations
         assert_eq!(
@@ -153,4 +282,104 @@ mod tests {
         assert_eq!(vel2.y, 0.0);
         assert_eq!(vel2.theta, 0.0);
     }
+
+    #[test]
+    fn test_limit_acceleration() {
+        let path = std::path::Path::new("tests/local_move_sample.toml");
+        let mut config = LocalMoveConfig::try_new(path).unwrap();
+        assert!(config.max_linear_acc.is_infinite());
+        let vel = config.limit_acceleration(
+            &BaseVelocity::new(0.0, 0.0, 0.0),
+            &BaseVelocity::new(1.0, -1.0, 1.0),
+        );
+        assert_approx_eq!(vel.x, 1.0);
+        assert_approx_eq!(vel.y, -1.0);
+        assert_approx_eq!(vel.theta, 1.0);
+
+        config.max_linear_acc = 0.5;
+        config.max_angular_acc = 2.0;
+        // control_frequency = 10.0
+        let vel = config.limit_acceleration(
+            &BaseVelocity::new(0.1, 0.0, 0.0),
+            &BaseVelocity::new(1.0, -1.0, -1.0),
+        );
+        assert_approx_eq!(vel.x, 0.15);
+        assert_approx_eq!(vel.y, -0.05);
+        assert_approx_eq!(vel.theta, -0.2);
+    }
+
+    fn simulated_local_move() -> LocalMove<Arc<SimulatedBase>, Arc<SimulatedBase>> {
+        let config = LocalMoveConfig {
+            reach_distance_threshold: 0.01,
+            reach_angle_threshold: 0.01,
+            control_frequency: 1000.0,
+            linear_gain: 2.0,
+            angular_gain: 2.0,
+            max_linear_vel: 0.5,
+            max_angular_vel: 1.0,
+            max_linear_acc: 1.0,
+            max_angular_acc: 2.0,
+        };
+        let base = Arc::new(SimulatedBase::new(
+            Isometry2::new(Vector2::new(1.0, 1.0), 0.0),
+            0.01,
+        ));
+        LocalMove::new(base.clone(), base, config)
+    }
+
+    #[test]
+    fn test_move_to_pose() {
+        let local_move = simulated_local_move();
+        let goal = Isometry2::new(Vector2::new(1.5, 0.8), 0.5);
+        tokio_test::block_on(local_move.move_to_pose(goal, "map", Duration::from_secs(10)))
+            .unwrap();
+        let pose_error = local_move.current_pose_error(&goal, "map").unwrap();
+        assert!(local_move.is_reached(pose_error));
+
+        // Relative to the current pose (= goal)
+        tokio_test::block_on(local_move.move_to_relative(
+            Isometry2::new(Vector2::new(0.2, 0.0), 0.0),
+            "map",
+            Duration::from_secs(10),
+        ))
+        .unwrap();
+        let pose = local_move.localization_client.current_pose("map").unwrap();
+        assert!((pose.translation.x - (1.5 + 0.2 * 0.5_f64.cos())).abs() < 0.01);
+        assert!((pose.translation.y - (0.8 + 0.2 * 0.5_f64.sin())).abs() < 0.01);
+
+        // Too short timeout
+        assert!(tokio_test::block_on(local_move.move_to_pose(
+            Isometry2::new(Vector2::new(10.0, 0.0), 0.0),
+            "map",
+            Duration::from_millis(10)
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let is_canceled = Arc::new(AtomicBool::new(false));
+        let local_move = Arc::new(simulated_local_move().with_cancel_flag(is_canceled.clone()));
+        let local_move_cloned = local_move.clone();
+        let handle = std::thread::spawn(move || {
+            tokio_test::block_on(local_move_cloned.move_to_pose(
+                Isometry2::new(Vector2::new(100.0, 0.0), 0.0),
+                "map",
+                Duration::from_secs(10),
+            ))
+        });
+        // Wait until the base starts moving.
+        while local_move
+            .localization_client
+            .current_pose("map")
+            .unwrap()
+            .translation
+            .x
+            <= 1.0
+        {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        is_canceled.store(true, Ordering::Relaxed);
+        assert!(handle.join().unwrap().is_err());
+    }
 }
@@ -0,0 +1,495 @@
+use crate::Error;
+use arci::{BaseVelocity, JointTrajectoryClient, MoveBase, TrajectoryPoint};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedTrajectoryPoint {
+    pub positions: Vec<f64>,
+    pub velocities: Option<Vec<f64>>,
+    pub time_from_start_sec: f64,
+}
+
+impl From<&TrajectoryPoint> for RecordedTrajectoryPoint {
+    fn from(point: &TrajectoryPoint) -> Self {
+        Self {
+            positions: point.positions.clone(),
+            velocities: point.velocities.clone(),
+            time_from_start_sec: point.time_from_start.as_secs_f64(),
+        }
+    }
+}
+
+impl From<&RecordedTrajectoryPoint> for TrajectoryPoint {
+    fn from(point: &RecordedTrajectoryPoint) -> Self {
+        Self {
+            positions: point.positions.clone(),
+            velocities: point.velocities.clone(),
+            time_from_start: Duration::from_secs_f64(point.time_from_start_sec),
+        }
+    }
+}
+
+/// Commands sent to the clients and states measured from the clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecordEvent {
+    /// `JointTrajectoryClient::send_joint_positions` is called.
+    SendJointPositions {
+        client: String,
+        positions: Vec<f64>,
+        duration_sec: f64,
+    },
+    /// `JointTrajectoryClient::send_joint_trajectory` is called.
+    SendJointTrajectory {
+        client: String,
+        trajectory: Vec<RecordedTrajectoryPoint>,
+    },
+    /// Joint positions measured when a command is finished.
+    JointState { client: String, positions: Vec<f64> },
+    /// `MoveBase::send_velocity` is called.
+    SendVelocity { x: f64, y: f64, theta: f64 },
+    /// Velocity measured by `MoveBase::current_velocity`.
+    BaseVelocityState { x: f64, y: f64, theta: f64 },
+}
+
+impl RecordEvent {
+    /// Returns true if this is a command, which is re-sent by `Replayer`.
+    pub fn is_command(&self) -> bool {
+        matches!(
+            self,
+            RecordEvent::SendJointPositions { .. }
+                | RecordEvent::SendJointTrajectory { .. }
+                | RecordEvent::SendVelocity { .. }
+        )
+    }
+}
+
+/// An event and the time (seconds since UNIX epoch) when it happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordEntry {
+    pub stamp: f64,
+    #[serde(flatten)]
+    pub event: RecordEvent,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecorderConfig {
+    /// File to write the records as JSON Lines. The records are appended if the file exists,
+    /// so use another file to replay a new session alone.
+    pub path: PathBuf,
+}
+
+/// Writes `RecordEntry` as JSON Lines.
+pub struct Recorder {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Recorder {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Appends the records to the file, which is created if it does not exist.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| Error::NoFile(path.as_ref().to_owned(), e))?;
+        Ok(Self::new(Box::new(BufWriter::new(file))))
+    }
+
+    pub fn new_from_config(config: &RecorderConfig) -> Result<Self, Error> {
+        Self::create(&config.path)
+    }
+
+    /// Writes the event with the current time.
+    ///
+    /// Failures are only logged to avoid stopping the robot because of the recorder.
+    pub fn record(&self, event: RecordEvent) {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let entry = RecordEntry { stamp, event };
+        let mut writer = self.writer.lock().unwrap();
+        let result = serde_json::to_writer(&mut *writer, &entry)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writeln!(writer))
+            .and_then(|_| writer.flush());
+        if let Err(e) = result {
+            warn!("Failed to record {:?}: {}", entry, e);
+        }
+    }
+}
+
+/// Reads records written by `Recorder`.
+pub fn load_records<P: AsRef<Path>>(path: P) -> Result<Vec<RecordEntry>, Error> {
+    let file = File::open(&path).map_err(|e| Error::NoFile(path.as_ref().to_owned(), e))?;
+    let mut entries = vec![];
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| Error::NoFile(path.as_ref().to_owned(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        entries.push(
+            serde_json::from_str(&line)
+                .map_err(|e| Error::JsonParseFailure(path.as_ref().to_owned(), i + 1, e))?,
+        );
+    }
+    Ok(entries)
+}
+
+/// Joint trajectory of `client` in the records, e.g. to play back a recorded or planned motion.
+///
+/// `time_from_start` is measured from the first command of `client`. Each command cuts
+/// the rest of the trajectory sent before it, as the clients do.
+pub fn joint_trajectory_from_records(
+    entries: &[RecordEntry],
+    client: &str,
+) -> Vec<TrajectoryPoint> {
+    let mut trajectory: Vec<TrajectoryPoint> = vec![];
+    let mut first_stamp = None;
+    for entry in entries {
+        let points = match &entry.event {
+            RecordEvent::SendJointPositions {
+                client: name,
+                positions,
+                duration_sec,
+            } if name == client => vec![TrajectoryPoint::new(
+                positions.clone(),
+                Duration::from_secs_f64(*duration_sec),
+            )],
+            RecordEvent::SendJointTrajectory {
+                client: name,
+                trajectory,
+            } if name == client => trajectory.iter().map(From::from).collect(),
+            _ => continue,
+        };
+        let start = Duration::from_secs_f64(
+            (entry.stamp - *first_stamp.get_or_insert(entry.stamp)).max(0.0),
+        );
+        trajectory.retain(|point| point.time_from_start <= start);
+        trajectory.extend(points.into_iter().map(|mut point| {
+            point.time_from_start += start;
+            point
+        }));
+    }
+    trajectory
+}
+
+/// JointTrajectoryClient which records the commands and the measured joint positions.
+pub struct RecordingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    name: String,
+    client: C,
+    recorder: Arc<Recorder>,
+}
+
+impl<C> RecordingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    /// `name` is used to find the client when the records are replayed.
+    pub fn new(name: String, client: C, recorder: Arc<Recorder>) -> Self {
+        Self {
+            name,
+            client,
+            recorder,
+        }
+    }
+
+    fn record_state(&self) {
+        if let Ok(positions) = self.client.current_joint_positions() {
+            self.recorder.record(RecordEvent::JointState {
+                client: self.name.clone(),
+                positions,
+            });
+        }
+    }
+}
+
+#[async_trait]
+impl<C> JointTrajectoryClient for RecordingJointTrajectoryClient<C>
+where
+    C: JointTrajectoryClient,
+{
+    fn joint_names(&self) -> &[String] {
+        self.client.joint_names()
+    }
+
+    fn current_joint_positions(&self) -> Result<Vec<f64>, arci::Error> {
+        self.client.current_joint_positions()
+    }
+
+    async fn send_joint_positions(
+        &self,
+        positions: Vec<f64>,
+        duration: Duration,
+    ) -> Result<(), arci::Error> {
+        self.recorder.record(RecordEvent::SendJointPositions {
+            client: self.name.clone(),
+            positions: positions.clone(),
+            duration_sec: duration.as_secs_f64(),
+        });
+        let result = self.client.send_joint_positions(positions, duration).await;
+        self.record_state();
+        result
+    }
+
+    async fn send_joint_trajectory(
+        &self,
+        trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<(), arci::Error> {
+        self.recorder.record(RecordEvent::SendJointTrajectory {
+            client: self.name.clone(),
+            trajectory: trajectory.iter().map(From::from).collect(),
+        });
+        let result = self.client.send_joint_trajectory(trajectory).await;
+        self.record_state();
+        result
+    }
+}
+
+/// MoveBase which records the velocity commands and the measured velocities.
+pub struct RecordingMoveBase<M>
+where
+    M: MoveBase,
+{
+    move_base: M,
+    recorder: Arc<Recorder>,
+}
+
+impl<M> RecordingMoveBase<M>
+where
+    M: MoveBase,
+{
+    pub fn new(move_base: M, recorder: Arc<Recorder>) -> Self {
+        Self {
+            move_base,
+            recorder,
+        }
+    }
+}
+
+impl<M> MoveBase for RecordingMoveBase<M>
+where
+    M: MoveBase,
+{
+    fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), arci::Error> {
+        self.recorder.record(RecordEvent::SendVelocity {
+            x: velocity.x,
+            y: velocity.y,
+            theta: velocity.theta,
+        });
+        self.move_base.send_velocity(velocity)
+    }
+
+    fn current_velocity(&self) -> Result<BaseVelocity, arci::Error> {
+        let velocity = self.move_base.current_velocity()?;
+        self.recorder.record(RecordEvent::BaseVelocityState {
+            x: velocity.x,
+            y: velocity.y,
+            theta: velocity.theta,
+        });
+        Ok(velocity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arci::{DummyJointTrajectoryClient, DummyMoveBase};
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn parse(buffer: &SharedBuffer) -> Vec<RecordEntry> {
+        String::from_utf8(buffer.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_recording_joint_trajectory_client() {
+        let buffer = SharedBuffer::default();
+        let recorder = Arc::new(Recorder::new(Box::new(buffer.clone())));
+        let client = RecordingJointTrajectoryClient::new(
+            "arm".to_owned(),
+            DummyJointTrajectoryClient::new(vec!["a".to_owned(), "b".to_owned()]),
+            recorder,
+        );
+        tokio_test::block_on(client.send_joint_positions(vec![1.0, 2.0], Duration::from_secs(1)))
+            .unwrap();
+        tokio_test::block_on(client.send_joint_trajectory(vec![TrajectoryPoint::new(
+            vec![3.0, 4.0],
+            Duration::from_millis(500),
+        )]))
+        .unwrap();
+
+        let events: Vec<_> = parse(&buffer).into_iter().map(|e| e.event).collect();
+        assert_eq!(
+            events,
+            vec![
+                RecordEvent::SendJointPositions {
+                    client: "arm".to_owned(),
+                    positions: vec![1.0, 2.0],
+                    duration_sec: 1.0,
+                },
+                RecordEvent::JointState {
+                    client: "arm".to_owned(),
+                    positions: vec![1.0, 2.0],
+                },
+                RecordEvent::SendJointTrajectory {
+                    client: "arm".to_owned(),
+                    trajectory: vec![RecordedTrajectoryPoint {
+                        positions: vec![3.0, 4.0],
+                        velocities: None,
+                        time_from_start_sec: 0.5,
+                    }],
+                },
+                RecordEvent::JointState {
+                    client: "arm".to_owned(),
+                    positions: vec![3.0, 4.0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recording_move_base() {
+        let buffer = SharedBuffer::default();
+        let recorder = Arc::new(Recorder::new(Box::new(buffer.clone())));
+        let move_base = RecordingMoveBase::new(DummyMoveBase::new(), recorder);
+        move_base
+            .send_velocity(&BaseVelocity::new(0.1, 0.0, -0.2))
+            .unwrap();
+        move_base.current_velocity().unwrap();
+
+        let entries = parse(&buffer);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].stamp <= entries[1].stamp);
+        assert_eq!(
+            entries[0].event,
+            RecordEvent::SendVelocity {
+                x: 0.1,
+                y: 0.0,
+                theta: -0.2
+            }
+        );
+        assert!(entries[0].event.is_command());
+        assert_eq!(
+            entries[1].event,
+            RecordEvent::BaseVelocityState {
+                x: 0.1,
+                y: 0.0,
+                theta: -0.2
+            }
+        );
+        assert!(!entries[1].event.is_command());
+    }
+
+    #[test]
+    fn test_joint_trajectory_from_records() {
+        let entries = vec![
+            RecordEntry {
+                stamp: 10.0,
+                event: RecordEvent::SendJointPositions {
+                    client: "arm".to_owned(),
+                    positions: vec![1.0],
+                    duration_sec: 1.0,
+                },
+            },
+            RecordEntry {
+                stamp: 10.5,
+                event: RecordEvent::SendJointPositions {
+                    client: "head".to_owned(),
+                    positions: vec![5.0],
+                    duration_sec: 1.0,
+                },
+            },
+            RecordEntry {
+                stamp: 12.0,
+                event: RecordEvent::SendJointTrajectory {
+                    client: "arm".to_owned(),
+                    trajectory: vec![
+                        RecordedTrajectoryPoint {
+                            positions: vec![2.0],
+                            velocities: None,
+                            time_from_start_sec: 1.0,
+                        },
+                        RecordedTrajectoryPoint {
+                            positions: vec![3.0],
+                            velocities: None,
+                            time_from_start_sec: 2.0,
+                        },
+                    ],
+                },
+            },
+            // Cuts the last point of the trajectory above.
+            RecordEntry {
+                stamp: 13.5,
+                event: RecordEvent::SendJointPositions {
+                    client: "arm".to_owned(),
+                    positions: vec![4.0],
+                    duration_sec: 0.5,
+                },
+            },
+        ];
+        let trajectory = joint_trajectory_from_records(&entries, "arm");
+        let points: Vec<_> = trajectory
+            .iter()
+            .map(|point| (point.positions.clone(), point.time_from_start.as_secs_f64()))
+            .collect();
+        assert_eq!(
+            points,
+            vec![(vec![1.0], 1.0), (vec![2.0], 3.0), (vec![4.0], 4.0)]
+        );
+        assert!(joint_trajectory_from_records(&entries, "base").is_empty());
+    }
+
+    #[test]
+    fn test_load_records() {
+        let path = std::env::temp_dir().join("openrr_client_test_load_records.jsonl");
+        let _ = std::fs::remove_file(&path);
+        for _ in 0..2 {
+            let recorder = Recorder::create(&path).unwrap();
+            recorder.record(RecordEvent::SendVelocity {
+                x: 1.0,
+                y: 0.0,
+                theta: 0.0,
+            });
+        }
+        // The second recorder appends the record.
+        let entries = load_records(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].event.is_command());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(load_records(&path).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use crate::{Error, RecordEntry, RecordEvent};
+use arci::{BaseVelocity, JointTrajectoryClient, MoveBase, TrajectoryPoint};
+use futures::{
+    future::{select, BoxFuture, Either},
+    stream::{FuturesUnordered, StreamExt},
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+type PendingCommands<'a> = FuturesUnordered<BoxFuture<'a, Result<(), arci::Error>>>;
+
+/// Re-sends the commands recorded by `Recorder`.
+pub struct Replayer {
+    entries: Vec<RecordEntry>,
+    speed: f64,
+}
+
+impl Replayer {
+    /// Only the commands in `entries` are replayed.
+    ///
+    /// `speed` scales the time, e.g. `2.0` replays twice as fast. It must be positive.
+    pub fn try_new(entries: Vec<RecordEntry>, speed: f64) -> Result<Self, Error> {
+        if speed.is_nan() || speed <= 0.0 {
+            return Err(Error::InvalidSpeed(speed));
+        }
+        let entries = entries
+            .into_iter()
+            .filter(|entry| entry.event.is_command())
+            .collect();
+        Ok(Self { entries, speed })
+    }
+
+    pub fn entries(&self) -> &[RecordEntry] {
+        &self.entries
+    }
+
+    /// Duration of the replay.
+    pub fn duration(&self) -> Duration {
+        match (self.entries.first(), self.entries.last()) {
+            (Some(first), Some(last)) => {
+                Duration::from_secs_f64((last.stamp - first.stamp).max(0.0) / self.speed)
+            }
+            _ => Duration::default(),
+        }
+    }
+
+    /// Sends the commands at the recorded timings.
+    ///
+    /// The commands are sent without waiting for the completion of the previous ones, so that
+    /// the commands recorded at the same time (e.g. the base velocity during an arm trajectory)
+    /// are replayed at the same time. The replay ends when all the commands are completed.
+    ///
+    /// Clients are looked up by the recorded client name. The commands of the move base are
+    /// skipped if `move_base` is None, and the move base is stopped at the end of the replay even
+    /// if it fails on the way.
+    pub async fn replay(
+        &self,
+        joint_trajectory_clients: &HashMap<String, Arc<dyn JointTrajectoryClient>>,
+        move_base: Option<&dyn MoveBase>,
+    ) -> Result<(), Error> {
+        // Check all clients before moving the robot.
+        let mut has_base_commands = false;
+        for entry in &self.entries {
+            match &entry.event {
+                RecordEvent::SendJointPositions { client, .. }
+                | RecordEvent::SendJointTrajectory { client, .. }
+                    if !joint_trajectory_clients.contains_key(client) =>
+                {
+                    return Err(Error::NoJointTrajectoryClient(client.to_owned()));
+                }
+                RecordEvent::SendVelocity { .. } => has_base_commands = true,
+                _ => {}
+            }
+        }
+        if has_base_commands && move_base.is_none() {
+            warn!("No move base is given, the commands of the move base are skipped");
+        }
+
+        let result = self
+            .send_commands(joint_trajectory_clients, move_base)
+            .await;
+        match move_base {
+            // Stops the move base even if the replay failed on the way.
+            Some(move_base) if has_base_commands => result.and(
+                move_base
+                    .send_velocity(&BaseVelocity::default())
+                    .map_err(Error::from),
+            ),
+            _ => result,
+        }
+    }
+
+    async fn send_commands(
+        &self,
+        joint_trajectory_clients: &HashMap<String, Arc<dyn JointTrajectoryClient>>,
+        move_base: Option<&dyn MoveBase>,
+    ) -> Result<(), Error> {
+        let first_stamp = match self.entries.first() {
+            Some(entry) => entry.stamp,
+            None => return Ok(()),
+        };
+        let start = Instant::now();
+        let mut pending = PendingCommands::new();
+        for entry in &self.entries {
+            let target = Duration::from_secs_f64((entry.stamp - first_stamp).max(0.0) / self.speed);
+            wait_until(&mut pending, start + target).await?;
+            debug!("replay {:?}", entry);
+            match &entry.event {
+                RecordEvent::SendJointPositions {
+                    client,
+                    positions,
+                    duration_sec,
+                } => {
+                    pending.push(joint_trajectory_clients[client].send_joint_positions(
+                        positions.clone(),
+                        Duration::from_secs_f64(duration_sec / self.speed),
+                    ));
+                }
+                RecordEvent::SendJointTrajectory { client, trajectory } => {
+                    let trajectory = trajectory
+                        .iter()
+                        .map(|point| {
+                            let mut point = TrajectoryPoint::from(point);
+                            point.time_from_start = point.time_from_start.div_f64(self.speed);
+                            point.velocities = point.velocities.map(|velocities| {
+                                velocities.iter().map(|v| v * self.speed).collect()
+                            });
+                            point
+                        })
+                        .collect();
+                    pending
+                        .push(joint_trajectory_clients[client].send_joint_trajectory(trajectory));
+                }
+                RecordEvent::SendVelocity { x, y, theta } => {
+                    if let Some(move_base) = move_base {
+                        move_base
+                            .send_velocity(&(BaseVelocity::new(*x, *y, *theta) * self.speed))?;
+                    }
+                }
+                _ => unreachable!(),
+            }
+        }
+        while let Some(result) = pending.next().await {
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Waits until `deadline` while driving the pending commands, and returns the first error of them.
+async fn wait_until(pending: &mut PendingCommands<'_>, deadline: Instant) -> Result<(), Error> {
+    let sleep = tokio::time::sleep_until(deadline);
+    tokio::pin!(sleep);
+    loop {
+        if pending.is_empty() {
+            sleep.await;
+            return Ok(());
+        }
+        match select(sleep.as_mut(), pending.next()).await {
+            Either::Left(_) => return Ok(()),
+            Either::Right((Some(result), _)) => result?,
+            Either::Right((None, _)) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RecordedTrajectoryPoint;
+    use arci::{DummyJointTrajectoryClient, DummyMoveBase};
+
+    fn entry(stamp: f64, event: RecordEvent) -> RecordEntry {
+        RecordEntry { stamp, event }
+    }
+
+    fn entries() -> Vec<RecordEntry> {
+        vec![
+            entry(
+                100.0,
+                RecordEvent::SendJointPositions {
+                    client: "arm".to_owned(),
+                    positions: vec![1.0],
+                    duration_sec: 0.1,
+                },
+            ),
+            entry(
+                100.05,
+                RecordEvent::JointState {
+                    client: "arm".to_owned(),
+                    positions: vec![1.0],
+                },
+            ),
+            entry(
+                100.1,
+                RecordEvent::SendVelocity {
+                    x: 0.5,
+                    y: 0.0,
+                    theta: 0.0,
+                },
+            ),
+            entry(
+                100.2,
+                RecordEvent::SendJointTrajectory {
+                    client: "arm".to_owned(),
+                    trajectory: vec![RecordedTrajectoryPoint {
+                        positions: vec![2.0],
+                        velocities: Some(vec![1.0]),
+                        time_from_start_sec: 0.2,
+                    }],
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_replay() {
+        let dummy = Arc::new(DummyJointTrajectoryClient::new(vec!["j".to_owned()]));
+        let mut clients = HashMap::new();
+        clients.insert(
+            "arm".to_owned(),
+            dummy.clone() as Arc<dyn JointTrajectoryClient>,
+        );
+        let move_base = DummyMoveBase::new();
+
+        let replayer = Replayer::try_new(entries(), 2.0).unwrap();
+        assert_eq!(replayer.entries().len(), 3);
+        assert!((replayer.duration().as_secs_f64() - 0.1).abs() < 1e-6);
+
+        let start = Instant::now();
+        tokio_test::block_on(replayer.replay(&clients, Some(&move_base))).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        assert_eq!(dummy.current_joint_positions().unwrap(), vec![2.0]);
+        let last_trajectory = dummy.last_trajectory.lock().unwrap();
+        assert!((last_trajectory[0].time_from_start.as_secs_f64() - 0.1).abs() < 1e-6);
+        assert_eq!(last_trajectory[0].velocities, Some(vec![2.0]));
+        // The move base is stopped at the end.
+        assert_eq!(move_base.current_velocity().unwrap().x, 0.0);
+    }
+
+    #[test]
+    fn test_invalid_speed() {
+        for speed in &[0.0, -1.0, f64::NAN] {
+            assert!(matches!(
+                Replayer::try_new(entries(), *speed),
+                Err(Error::InvalidSpeed(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_replay_without_client() {
+        let replayer = Replayer::try_new(entries(), 1.0).unwrap();
+        let result = tokio_test::block_on(replayer.replay(&HashMap::new(), None));
+        assert!(matches!(result, Err(Error::NoJointTrajectoryClient(name)) if name == "arm"));
+    }
+
+    /// JointTrajectoryClient which fails to send the trajectory.
+    struct FailingJointTrajectoryClient(Vec<String>);
+
+    #[async_trait::async_trait]
+    impl JointTrajectoryClient for FailingJointTrajectoryClient {
+        fn joint_names(&self) -> &[String] {
+            &self.0
+        }
+        fn current_joint_positions(&self) -> Result<Vec<f64>, arci::Error> {
+            Ok(vec![0.0; self.0.len()])
+        }
+        async fn send_joint_positions(
+            &self,
+            _positions: Vec<f64>,
+            _duration: Duration,
+        ) -> Result<(), arci::Error> {
+            Ok(())
+        }
+        async fn send_joint_trajectory(
+            &self,
+            _trajectory: Vec<TrajectoryPoint>,
+        ) -> Result<(), arci::Error> {
+            Err(arci::Error::Other(anyhow::format_err!("failed")))
+        }
+    }
+
+    #[test]
+    fn test_replay_stops_base_on_error() {
+        let mut clients = HashMap::new();
+        clients.insert(
+            "arm".to_owned(),
+            Arc::new(FailingJointTrajectoryClient(vec!["j".to_owned()]))
+                as Arc<dyn JointTrajectoryClient>,
+        );
+        let move_base = DummyMoveBase::new();
+        let replayer = Replayer::try_new(entries(), 10.0).unwrap();
+        let result = tokio_test::block_on(replayer.replay(&clients, Some(&move_base)));
+        assert!(result.is_err());
+        // The velocity is sent before the failed trajectory, and the base is stopped.
+        assert_eq!(move_base.current_velocity().unwrap().x, 0.0);
+    }
+
+    /// JointTrajectoryClient which takes 0.5 seconds to complete the commands.
+    struct SlowJointTrajectoryClient {
+        joint_names: Vec<String>,
+        start_times: std::sync::Mutex<Vec<Instant>>,
+    }
+
+    #[async_trait::async_trait]
+    impl JointTrajectoryClient for SlowJointTrajectoryClient {
+        fn joint_names(&self) -> &[String] {
+            &self.joint_names
+        }
+        fn current_joint_positions(&self) -> Result<Vec<f64>, arci::Error> {
+            Ok(vec![0.0; self.joint_names.len()])
+        }
+        async fn send_joint_positions(
+            &self,
+            _positions: Vec<f64>,
+            _duration: Duration,
+        ) -> Result<(), arci::Error> {
+            self.send_joint_trajectory(vec![]).await
+        }
+        async fn send_joint_trajectory(
+            &self,
+            _trajectory: Vec<TrajectoryPoint>,
+        ) -> Result<(), arci::Error> {
+            self.start_times.lock().unwrap().push(Instant::now());
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_replay_concurrent_commands() {
+        let slow = Arc::new(SlowJointTrajectoryClient {
+            joint_names: vec!["j".to_owned()],
+            start_times: Default::default(),
+        });
+        let mut clients = HashMap::new();
+        clients.insert(
+            "arm".to_owned(),
+            slow.clone() as Arc<dyn JointTrajectoryClient>,
+        );
+        let replayer = Replayer::try_new(entries(), 1.0).unwrap();
+        let start = Instant::now();
+        tokio_test::block_on(replayer.replay(&clients, None)).unwrap();
+        // The trajectory is sent at the recorded time without waiting for the previous command,
+        // and the replay waits for the completion of it.
+        let start_times = slow.start_times.lock().unwrap();
+        assert_eq!(start_times.len(), 2);
+        let interval = start_times[1] - start_times[0];
+        assert!(interval < Duration::from_millis(350), "{:?}", interval);
+        assert!(start.elapsed() >= Duration::from_millis(700));
+    }
+}
@@ -7,10 +7,20 @@ use urdf_rs::UrdfError;
 pub enum Error {
     #[error("openrr-client: arci: {:?}", .0)]
     Arci(#[from] arci::Error),
+    #[error("openrr-client: Failed to write {:?} ({}).", .0, .1)]
+    FileWriteFailure(PathBuf, #[source] std::io::Error),
+    #[error("openrr-client: Failed to parse {:?} line {} as json ({}).", .0, .1, .2)]
+    JsonParseFailure(PathBuf, usize, #[source] serde_json::Error),
+    #[error("openrr-client: Invalid map {:?} ({}).", .0, .1)]
+    InvalidMap(PathBuf, String),
+    #[error("openrr-client: Invalid speed {} (must be positive).", .0)]
+    InvalidSpeed(f64),
     #[error("openrr-client: MismatchedLength {} != {}.", .0, .1)]
     MismatchedLength(usize, usize),
     #[error("openrr-client: No File {:?} is found ({}).", .0, .1)]
     NoFile(PathBuf, #[source] std::io::Error),
+    #[error("openrr-client: No Link={} fixed to the end link of IK is found.", .0)]
+    NoFixedLink(String),
     #[error("openrr-client: No IkClient={} is found.", .0)]
     NoIkClient(String),
     #[error("openrr-client: No Joint={} is found.", .0)]
@@ -19,12 +29,20 @@ pub enum Error {
     NoJointsPose(String, String),
     #[error("openrr-client: No JointTrajectoryClient={} is found.", .0)]
     NoJointTrajectoryClient(String),
+    #[error("openrr-client: No Link={} is found.", .0)]
+    NoLink(String),
     #[error("openrr-client: No ParentDirectory {:?} is found.", .0)]
     NoParentDirectory(PathBuf),
+    #[error("openrr-client: No ToolFrame={} is found.", .0)]
+    NoToolFrame(String),
     #[error("openrr-client: No UrdfPath is specified.")]
     NoUrdfPath,
     #[error("openrr-client: Failed to parse {:?} as toml ({}).", .0, .1)]
     TomlParseFailure(PathBuf, #[source] toml::de::Error),
+    #[error("openrr-client: Failed to serialize {:?} as toml ({}).", .0, .1)]
+    TomlSerializeFailure(PathBuf, #[source] toml::ser::Error),
+    #[error("openrr-client: Failed to parse {:?} as yaml ({}).", .0, .1)]
+    YamlParseFailure(PathBuf, #[source] serde_yaml::Error),
     #[error("openrr-client: urdf-rs: {:?}", .0)]
     UrdfRs(#[from] UrdfError),
 }
@@ -0,0 +1,96 @@
+use crate::{Error, JointsPose};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Contents of `OpenrrClientsConfig::joints_poses_path`.
+///
+/// The format is the same as `joints_poses` of `OpenrrClientsConfig`.
+///
+/// ```toml
+/// [[joints_poses]]
+/// pose_name = "zero"
+/// client_name = "arm"
+/// positions = [0.0, 0.0]
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct JointsPosesFile {
+    #[serde(default)]
+    pub joints_poses: Vec<JointsPose>,
+}
+
+/// Loads the poses saved by `save_joints_pose`. Returns an empty vector if the file does not exist.
+pub fn load_joints_poses<P: AsRef<Path>>(path: P) -> Result<Vec<JointsPose>, Error> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let file: JointsPosesFile = toml::from_str(
+        &std::fs::read_to_string(path).map_err(|e| Error::NoFile(path.to_owned(), e))?,
+    )
+    .map_err(|e| Error::TomlParseFailure(path.to_owned(), e))?;
+    Ok(file.joints_poses)
+}
+
+/// Adds `joints_pose` to the file. The pose which has the same name is overwritten.
+pub fn save_joints_pose<P: AsRef<Path>>(path: P, joints_pose: JointsPose) -> Result<(), Error> {
+    let path = path.as_ref();
+    let mut joints_poses = load_joints_poses(path)?;
+    merge_joints_poses(&mut joints_poses, vec![joints_pose]);
+    let contents = toml::to_string(&JointsPosesFile { joints_poses })
+        .map_err(|e| Error::TomlSerializeFailure(path.to_owned(), e))?;
+    std::fs::write(path, contents).map_err(|e| Error::FileWriteFailure(path.to_owned(), e))
+}
+
+/// Adds `new_poses` to `joints_poses`, overwriting the poses which have the same client and pose name.
+pub fn merge_joints_poses(joints_poses: &mut Vec<JointsPose>, new_poses: Vec<JointsPose>) {
+    for new_pose in new_poses {
+        match joints_poses.iter_mut().find(|pose| {
+            pose.client_name == new_pose.client_name && pose.pose_name == new_pose.pose_name
+        }) {
+            Some(pose) => *pose = new_pose,
+            None => joints_poses.push(new_pose),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pose(client_name: &str, pose_name: &str, positions: Vec<f64>) -> JointsPose {
+        JointsPose {
+            pose_name: pose_name.to_owned(),
+            client_name: client_name.to_owned(),
+            positions,
+        }
+    }
+
+    #[test]
+    fn test_merge_joints_poses() {
+        let mut poses = vec![pose("arm", "zero", vec![0.0]), pose("arm", "up", vec![1.0])];
+        merge_joints_poses(
+            &mut poses,
+            vec![pose("arm", "up", vec![2.0]), pose("hand", "up", vec![3.0])],
+        );
+        assert_eq!(poses.len(), 3);
+        assert_eq!(poses[1].positions, vec![2.0]);
+        assert_eq!(poses[2].client_name, "hand");
+    }
+
+    #[test]
+    fn test_save_and_load_joints_poses() {
+        let path = std::env::temp_dir().join("openrr_client_test_save_joints_pose.toml");
+        let _ = std::fs::remove_file(&path);
+        assert!(load_joints_poses(&path).unwrap().is_empty());
+
+        save_joints_pose(&path, pose("arm", "a", vec![0.1, 0.2])).unwrap();
+        save_joints_pose(&path, pose("arm", "b", vec![0.3, 0.4])).unwrap();
+        save_joints_pose(&path, pose("arm", "a", vec![0.5, 0.6])).unwrap();
+        let poses = load_joints_poses(&path).unwrap();
+        assert_eq!(poses.len(), 2);
+        assert_eq!(poses[0].pose_name, "a");
+        assert_eq!(poses[0].positions, vec![0.5, 0.6]);
+        assert_eq!(poses[1].pose_name, "b");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
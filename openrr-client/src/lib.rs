@@ -1,9 +1,15 @@
 mod clients;
 mod error;
+mod joints_poses;
+mod navigation;
 mod robot_client;
+#[cfg(test)]
+mod test_util;
 
 pub mod utils;
 
 pub use clients::*;
 pub use error::*;
+pub use joints_poses::*;
+pub use navigation::*;
 pub use robot_client::*;
@@ -0,0 +1,9 @@
+mod grid_navigation;
+mod grid_planner;
+mod occupancy_grid;
+mod pure_pursuit;
+
+pub use grid_navigation::*;
+pub use grid_planner::*;
+pub use occupancy_grid::*;
+pub use pure_pursuit::*;
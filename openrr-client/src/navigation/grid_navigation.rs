@@ -0,0 +1,328 @@
+use arci::{
+    BaseVelocity, Localization, MoveBase, Navigation, NavigationFeedback, NavigationStatus,
+};
+use async_trait::async_trait;
+use k::nalgebra as na;
+use na::{Isometry2, Point2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+use super::{GridPlanner, GridPlannerConfig, OccupancyGrid, PurePursuit, PurePursuitConfig};
+use crate::{Error, LocalMoveConfig};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GridNavigationConfig {
+    /// Path to the map file of ROS map_server (YAML).
+    pub map_path: PathBuf,
+    /// Obstacles in the map are expanded by this radius [m] before planning.
+    #[serde(default)]
+    pub inflation_radius: f64,
+    #[serde(default)]
+    pub planner_config: GridPlannerConfig,
+    pub pure_pursuit_config: PurePursuitConfig,
+    /// Used to approach the goal pose after the robot comes within the lookahead distance.
+    /// `control_frequency` is also used for the path tracking.
+    pub local_move_config: LocalMoveConfig,
+}
+
+/// Navigation on the occupancy grid map without ROS.
+///
+/// The global path is planned by `GridPlanner` on the inflated map, and it is tracked by
+/// `PurePursuit`. Once the robot comes within the lookahead distance of the goal,
+/// it moves to the goal pose by the same control law as `LocalMove`.
+pub struct GridNavigation<L, M>
+where
+    L: Localization,
+    M: MoveBase,
+{
+    localization: L,
+    move_base: M,
+    grid: OccupancyGrid,
+    planner: GridPlanner,
+    pure_pursuit: PurePursuit,
+    local_move_config: LocalMoveConfig,
+    status: Mutex<NavigationStatus>,
+    feedback: Mutex<Option<NavigationFeedback>>,
+    is_canceled: AtomicBool,
+}
+
+impl<L, M> GridNavigation<L, M>
+where
+    L: Localization,
+    M: MoveBase,
+{
+    /// `grid` is used as is, so it must be inflated if needed.
+    pub fn new(
+        localization: L,
+        move_base: M,
+        grid: OccupancyGrid,
+        planner: GridPlanner,
+        pure_pursuit: PurePursuit,
+        local_move_config: LocalMoveConfig,
+    ) -> Self {
+        Self {
+            localization,
+            move_base,
+            grid,
+            planner,
+            pure_pursuit,
+            local_move_config,
+            status: Mutex::new(NavigationStatus::Idle),
+            feedback: Mutex::new(None),
+            is_canceled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn new_from_config(
+        config: GridNavigationConfig,
+        localization: L,
+        move_base: M,
+    ) -> Result<Self, Error> {
+        let grid = OccupancyGrid::load_map_server_yaml(&config.map_path)?;
+        Ok(Self::new(
+            localization,
+            move_base,
+            grid.inflate(config.inflation_radius),
+            GridPlanner::new(config.planner_config),
+            PurePursuit::new(config.pure_pursuit_config),
+            config.local_move_config,
+        ))
+    }
+
+    pub fn grid(&self) -> &OccupancyGrid {
+        &self.grid
+    }
+
+    fn plan(
+        &self,
+        start: &Isometry2<f64>,
+        goal: &Isometry2<f64>,
+    ) -> Result<Vec<Point2<f64>>, arci::Error> {
+        self.planner
+            .plan(
+                &self.grid,
+                &Point2::from(start.translation.vector),
+                &Point2::from(goal.translation.vector),
+            )
+            .ok_or_else(|| anyhow::anyhow!("No path is found from {} to {}", start, goal).into())
+    }
+
+    async fn follow_path(
+        &self,
+        path: &[Point2<f64>],
+        goal: &Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        let period = Duration::from_secs_f64(1.0 / self.local_move_config.control_frequency);
+        let start_time = Instant::now();
+        loop {
+            if self.is_canceled.load(Ordering::Relaxed) {
+                return Err(anyhow::anyhow!("Navigation is canceled").into());
+            }
+            if start_time.elapsed() > timeout {
+                return Err(anyhow::anyhow!("Navigation timeout ({:?})", timeout).into());
+            }
+            let pose = self.localization.current_pose(frame_id)?;
+            let distance_remaining = (goal.translation.vector - pose.translation.vector).norm();
+            *self.feedback.lock().unwrap() = Some(NavigationFeedback {
+                current_pose: pose,
+                distance_remaining,
+            });
+            let velocity = if distance_remaining < self.pure_pursuit.config().lookahead_distance {
+                let pose_error = pose.inverse() * goal;
+                if self.local_move_config.is_reached(pose_error) {
+                    return Ok(());
+                }
+                self.local_move_config
+                    .control_velocity_from_pose_error(pose_error)
+            } else {
+                self.pure_pursuit.velocity(&pose, path)
+            };
+            debug!("velocity: {:?}", velocity);
+            self.move_base.send_velocity(&velocity)?;
+            tokio::time::sleep(period).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<L, M> Navigation for GridNavigation<L, M>
+where
+    L: Localization,
+    M: MoveBase,
+{
+    async fn move_to(
+        &self,
+        goal: Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), arci::Error> {
+        self.is_canceled.store(false, Ordering::Relaxed);
+        *self.status.lock().unwrap() = NavigationStatus::Pending;
+        let path = match self
+            .localization
+            .current_pose(frame_id)
+            .and_then(|start| self.plan(&start, &goal))
+        {
+            Ok(path) => path,
+            Err(e) => {
+                *self.status.lock().unwrap() = NavigationStatus::Aborted;
+                return Err(e);
+            }
+        };
+        *self.status.lock().unwrap() = NavigationStatus::Active;
+        let result = self.follow_path(&path, &goal, frame_id, timeout).await;
+        let stop_result = self.move_base.send_velocity(&BaseVelocity::default());
+        *self.feedback.lock().unwrap() = None;
+        *self.status.lock().unwrap() = match result {
+            Ok(_) => NavigationStatus::Succeeded,
+            Err(_) if self.is_canceled.load(Ordering::Relaxed) => NavigationStatus::Canceled,
+            Err(_) => NavigationStatus::Aborted,
+        };
+        result.and(stop_result)
+    }
+
+    fn cancel(&self) -> Result<(), arci::Error> {
+        self.is_canceled.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn status(&self) -> Result<NavigationStatus, arci::Error> {
+        Ok(*self.status.lock().unwrap())
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, arci::Error> {
+        Ok(self.feedback.lock().unwrap().clone())
+    }
+
+    fn plan_path(
+        &self,
+        goal: Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Vec<Isometry2<f64>>, arci::Error> {
+        let start = self.localization.current_pose(frame_id)?;
+        let path = self.plan(&start, &goal)?;
+        let mut poses = path
+            .windows(2)
+            .map(|points| {
+                let direction = points[1] - points[0];
+                Isometry2::new(points[0].coords, direction.y.atan2(direction.x))
+            })
+            .collect::<Vec<_>>();
+        poses.push(Isometry2::new(
+            Vector2::new(goal.translation.x, goal.translation.y),
+            goal.rotation.angle(),
+        ));
+        Ok(poses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::SimulatedBase, Cell};
+    use assert_approx_eq::assert_approx_eq;
+    use std::sync::Arc;
+
+    fn navigation() -> GridNavigation<Arc<SimulatedBase>, Arc<SimulatedBase>> {
+        // 10m x 10m map with a wall at x = 5 except y < 1.
+        let mut grid = OccupancyGrid::new(100, 100, 0.1, Isometry2::identity());
+        for y in 10..100 {
+            grid.set_cell(50, y, Cell::Occupied);
+        }
+        let base = Arc::new(SimulatedBase::new(
+            Isometry2::new(Vector2::new(2.0, 8.0), 0.0),
+            0.1,
+        ));
+        GridNavigation::new(
+            base.clone(),
+            base,
+            grid.inflate(0.2),
+            GridPlanner::new(GridPlannerConfig::default()),
+            PurePursuit::new(PurePursuitConfig {
+                lookahead_distance: 0.5,
+                max_linear_vel: 0.5,
+                max_angular_vel: 2.0,
+                rotate_in_place_angle: 1.0,
+            }),
+            LocalMoveConfig {
+                reach_distance_threshold: 0.05,
+                reach_angle_threshold: 0.05,
+                control_frequency: 1000.0,
+                linear_gain: 1.0,
+                angular_gain: 1.0,
+                max_linear_vel: 0.5,
+                max_angular_vel: 1.0,
+                max_linear_acc: f64::INFINITY,
+                max_angular_acc: f64::INFINITY,
+            },
+        )
+    }
+
+    #[test]
+    fn test_plan_path() {
+        let nav = navigation();
+        let goal = Isometry2::new(Vector2::new(8.0, 8.0), 1.0);
+        let path = nav.plan_path(goal, "map").unwrap();
+        assert!(path.len() > 2);
+        assert_approx_eq!(path[0].translation.x, 2.0);
+        assert_approx_eq!(path.last().unwrap().translation.x, 8.0);
+        assert_approx_eq!(path.last().unwrap().rotation.angle(), 1.0);
+        // The path goes through the gap of the wall.
+        assert!(path.iter().any(|p| p.translation.y < 1.0));
+
+        assert!(nav
+            .plan_path(Isometry2::new(Vector2::new(5.0, 5.0), 0.0), "map")
+            .is_err());
+    }
+
+    #[test]
+    fn test_move_to() {
+        let nav = navigation();
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Idle);
+        let goal = Isometry2::new(Vector2::new(8.0, 8.0), 1.0);
+        tokio_test::block_on(nav.move_to(goal, "map", Duration::from_secs(10))).unwrap();
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Succeeded);
+        assert!(nav.feedback().unwrap().is_none());
+        let pose = nav.localization.current_pose("map").unwrap();
+        assert!((pose.translation.vector - goal.translation.vector).norm() < 0.05);
+        assert!((pose.rotation.angle() - 1.0).abs() < 0.05);
+
+        // Goal in the wall
+        let result = tokio_test::block_on(nav.move_to(
+            Isometry2::new(Vector2::new(5.0, 5.0), 0.0),
+            "map",
+            Duration::from_secs(10),
+        ));
+        assert!(result.is_err());
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Aborted);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let nav = Arc::new(navigation());
+        let nav_cloned = nav.clone();
+        let handle = std::thread::spawn(move || {
+            tokio_test::block_on(nav_cloned.move_to(
+                Isometry2::new(Vector2::new(8.0, 8.0), 0.0),
+                "map",
+                Duration::from_secs(10),
+            ))
+        });
+        while nav.status().unwrap() != NavigationStatus::Active {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        nav.cancel().unwrap();
+        assert!(handle.join().unwrap().is_err());
+        assert_eq!(nav.status().unwrap(), NavigationStatus::Canceled);
+    }
+}
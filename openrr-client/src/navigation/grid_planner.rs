@@ -0,0 +1,244 @@
+use k::nalgebra as na;
+use na::Point2;
+use serde::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use super::{Cell, OccupancyGrid};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GridPlannerAlgorithm {
+    #[default]
+    AStar,
+    Dijkstra,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GridPlannerConfig {
+    #[serde(default)]
+    pub algorithm: GridPlannerAlgorithm,
+    /// Regard unknown cells as free.
+    #[serde(default)]
+    pub allow_unknown: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Node {
+    cost: f64,
+    index: usize,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed to make BinaryHeap a min-heap.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Global path planner on 8-connected occupancy grid.
+#[derive(Debug, Clone)]
+pub struct GridPlanner {
+    config: GridPlannerConfig,
+}
+
+impl GridPlanner {
+    pub fn new(config: GridPlannerConfig) -> Self {
+        Self { config }
+    }
+
+    fn is_traversable(&self, grid: &OccupancyGrid, x: usize, y: usize) -> bool {
+        match grid.cell(x, y) {
+            Some(Cell::Free) => true,
+            Some(Cell::Unknown) => self.config.allow_unknown,
+            _ => false,
+        }
+    }
+
+    /// Plan the path in cell indices. Returns `None` if there is no path.
+    pub fn plan_cells(
+        &self,
+        grid: &OccupancyGrid,
+        start: (usize, usize),
+        goal: (usize, usize),
+    ) -> Option<Vec<(usize, usize)>> {
+        if !self.is_traversable(grid, start.0, start.1)
+            || !self.is_traversable(grid, goal.0, goal.1)
+        {
+            return None;
+        }
+        let width = grid.width();
+        let heuristic = |x: usize, y: usize| match self.config.algorithm {
+            GridPlannerAlgorithm::AStar => {
+                let dx = x as f64 - goal.0 as f64;
+                let dy = y as f64 - goal.1 as f64;
+                (dx * dx + dy * dy).sqrt()
+            }
+            GridPlannerAlgorithm::Dijkstra => 0.0,
+        };
+        let num_cells = width * grid.height();
+        let mut costs = vec![f64::INFINITY; num_cells];
+        let mut parents = vec![usize::MAX; num_cells];
+        let mut closed = vec![false; num_cells];
+        let mut open = BinaryHeap::new();
+
+        let start_index = start.1 * width + start.0;
+        let goal_index = goal.1 * width + goal.0;
+        costs[start_index] = 0.0;
+        open.push(Node {
+            cost: heuristic(start.0, start.1),
+            index: start_index,
+        });
+
+        while let Some(Node { index, .. }) = open.pop() {
+            if index == goal_index {
+                let mut path = vec![goal];
+                let mut current = goal_index;
+                while current != start_index {
+                    current = parents[current];
+                    path.push((current % width, current / width));
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if closed[index] {
+                continue;
+            }
+            closed[index] = true;
+            let (x, y) = (index % width, index / width);
+            for dy in -1isize..=1 {
+                for dx in -1isize..=1 {
+                    if dx == 0 && dy == 0 {
+                        continue;
+                    }
+                    let nx = x as isize + dx;
+                    let ny = y as isize + dy;
+                    if nx < 0 || ny < 0 {
+                        continue;
+                    }
+                    let (nx, ny) = (nx as usize, ny as usize);
+                    if !self.is_traversable(grid, nx, ny) {
+                        continue;
+                    }
+                    // Do not cut corners of obstacles.
+                    if dx != 0
+                        && dy != 0
+                        && (!self.is_traversable(grid, nx, y) || !self.is_traversable(grid, x, ny))
+                    {
+                        continue;
+                    }
+                    let next_index = ny * width + nx;
+                    let cost = costs[index] + ((dx * dx + dy * dy) as f64).sqrt();
+                    if cost < costs[next_index] {
+                        costs[next_index] = cost;
+                        parents[next_index] = index;
+                        open.push(Node {
+                            cost: cost + heuristic(nx, ny),
+                            index: next_index,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Plan the path in world coordinates. The first point is `start` and the last one is `goal`.
+    pub fn plan(
+        &self,
+        grid: &OccupancyGrid,
+        start: &Point2<f64>,
+        goal: &Point2<f64>,
+    ) -> Option<Vec<Point2<f64>>> {
+        let start_cell = grid.world_to_cell(start)?;
+        let goal_cell = grid.world_to_cell(goal)?;
+        let cells = self.plan_cells(grid, start_cell, goal_cell)?;
+        let mut path = vec![*start];
+        if cells.len() > 2 {
+            path.extend(
+                cells[1..cells.len() - 1]
+                    .iter()
+                    .map(|(x, y)| grid.cell_to_world(*x, *y)),
+            );
+        }
+        path.push(*goal);
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use na::Isometry2;
+
+    fn grid_with_wall() -> OccupancyGrid {
+        // 10x10 grid with a wall at x = 5 except y = 0.
+        let mut grid = OccupancyGrid::new(10, 10, 1.0, Isometry2::identity());
+        for y in 1..10 {
+            grid.set_cell(5, y, Cell::Occupied);
+        }
+        grid
+    }
+
+    #[test]
+    fn test_plan_cells() {
+        let grid = grid_with_wall();
+        for algorithm in &[GridPlannerAlgorithm::AStar, GridPlannerAlgorithm::Dijkstra] {
+            let planner = GridPlanner::new(GridPlannerConfig {
+                algorithm: *algorithm,
+                allow_unknown: false,
+            });
+            let path = planner.plan_cells(&grid, (2, 8), (8, 8)).unwrap();
+            assert_eq!(path[0], (2, 8));
+            assert_eq!(*path.last().unwrap(), (8, 8));
+            assert!(path.contains(&(5, 0)));
+            for (x, y) in &path {
+                assert_eq!(grid.cell(*x, *y), Some(Cell::Free));
+            }
+            for pair in path.windows(2) {
+                let dx = pair[0].0 as isize - pair[1].0 as isize;
+                let dy = pair[0].1 as isize - pair[1].1 as isize;
+                assert!(dx.abs() <= 1 && dy.abs() <= 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_path() {
+        let mut grid = grid_with_wall();
+        grid.set_cell(5, 0, Cell::Unknown);
+        let planner = GridPlanner::new(GridPlannerConfig::default());
+        assert!(planner.plan_cells(&grid, (2, 8), (8, 8)).is_none());
+        assert!(planner.plan_cells(&grid, (2, 8), (5, 5)).is_none());
+
+        let planner = GridPlanner::new(GridPlannerConfig {
+            algorithm: GridPlannerAlgorithm::AStar,
+            allow_unknown: true,
+        });
+        assert!(planner.plan_cells(&grid, (2, 8), (8, 8)).is_some());
+    }
+
+    #[test]
+    fn test_plan() {
+        let grid = grid_with_wall();
+        let planner = GridPlanner::new(GridPlannerConfig::default());
+        let start = Point2::new(2.2, 8.3);
+        let goal = Point2::new(8.7, 8.1);
+        let path = planner.plan(&grid, &start, &goal).unwrap();
+        assert_eq!(path[0], start);
+        assert_eq!(*path.last().unwrap(), goal);
+        assert!(planner
+            .plan(&grid, &start, &Point2::new(10.5, 8.0))
+            .is_none());
+    }
+}
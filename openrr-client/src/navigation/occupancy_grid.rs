@@ -0,0 +1,334 @@
+use k::nalgebra as na;
+use na::{Isometry2, Point2, Vector2};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::{resolve_relative_path, Error};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Free,
+    Occupied,
+    Unknown,
+}
+
+/// Map metadata file format of ROS map_server.
+///
+/// See <http://wiki.ros.org/map_server#YAML_format>.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MapServerConfig {
+    pub image: PathBuf,
+    pub resolution: f64,
+    /// `[x, y, yaw]` of the lower-left pixel in the map.
+    pub origin: [f64; 3],
+    #[serde(default)]
+    pub negate: i32,
+    pub occupied_thresh: f64,
+    pub free_thresh: f64,
+}
+
+/// 2D occupancy grid map.
+///
+/// Cell `(0, 0)` is the lower-left cell of the map, and `x` / `y` increase along the
+/// x / y axes of the map origin.
+#[derive(Debug, Clone)]
+pub struct OccupancyGrid {
+    width: usize,
+    height: usize,
+    resolution: f64,
+    origin: Isometry2<f64>,
+    cells: Vec<Cell>,
+}
+
+impl OccupancyGrid {
+    pub fn new(width: usize, height: usize, resolution: f64, origin: Isometry2<f64>) -> Self {
+        Self {
+            width,
+            height,
+            resolution,
+            origin,
+            cells: vec![Cell::Free; width * height],
+        }
+    }
+
+    /// Load map from ROS map_server YAML file and its PGM image.
+    pub fn load_map_server_yaml<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let config: MapServerConfig = serde_yaml::from_str(
+            &std::fs::read_to_string(path).map_err(|e| Error::NoFile(path.to_owned(), e))?,
+        )
+        .map_err(|e| Error::YamlParseFailure(path.to_owned(), e))?;
+        let image_path = if config.image.is_absolute() {
+            config.image.clone()
+        } else {
+            resolve_relative_path(path, &config.image)?
+        };
+        let image = std::fs::read(&image_path).map_err(|e| Error::NoFile(image_path.clone(), e))?;
+        let pgm = Pgm::parse(&image).map_err(|e| Error::InvalidMap(image_path, e))?;
+        Ok(Self::from_map_server_image(&config, &pgm))
+    }
+
+    fn from_map_server_image(config: &MapServerConfig, pgm: &Pgm) -> Self {
+        let origin = Isometry2::new(
+            Vector2::new(config.origin[0], config.origin[1]),
+            config.origin[2],
+        );
+        let mut grid = Self::new(pgm.width, pgm.height, config.resolution, origin);
+        for row in 0..pgm.height {
+            for col in 0..pgm.width {
+                let value = pgm.pixels[row * pgm.width + col] as f64 / pgm.max_value as f64;
+                let occupancy = if config.negate == 0 {
+                    1.0 - value
+                } else {
+                    value
+                };
+                let cell = if occupancy > config.occupied_thresh {
+                    Cell::Occupied
+                } else if occupancy < config.free_thresh {
+                    Cell::Free
+                } else {
+                    Cell::Unknown
+                };
+                // The first row of the image is the top of the map.
+                grid.set_cell(col, pgm.height - 1 - row, cell);
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+    pub fn origin(&self) -> &Isometry2<f64> {
+        &self.origin
+    }
+
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        if x < self.width && y < self.height {
+            Some(self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) {
+        assert!(x < self.width && y < self.height);
+        self.cells[y * self.width + x] = cell;
+    }
+
+    /// Returns the index of the cell which contains the point, or `None` if the point is out of the map.
+    pub fn world_to_cell(&self, point: &Point2<f64>) -> Option<(usize, usize)> {
+        let local = self.origin.inverse_transform_point(point);
+        let x = (local.x / self.resolution).floor();
+        let y = (local.y / self.resolution).floor();
+        if x < 0.0 || y < 0.0 || x >= self.width as f64 || y >= self.height as f64 {
+            None
+        } else {
+            Some((x as usize, y as usize))
+        }
+    }
+
+    /// Returns the center point of the cell.
+    pub fn cell_to_world(&self, x: usize, y: usize) -> Point2<f64> {
+        self.origin.transform_point(&Point2::new(
+            (x as f64 + 0.5) * self.resolution,
+            (y as f64 + 0.5) * self.resolution,
+        ))
+    }
+
+    /// Returns a new grid whose obstacles are expanded by `radius` meters.
+    pub fn inflate(&self, radius: f64) -> Self {
+        let cells_radius = (radius / self.resolution).ceil() as isize;
+        let mut inflated = self.clone();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.cells[y * self.width + x] != Cell::Occupied {
+                    continue;
+                }
+                for dy in -cells_radius..=cells_radius {
+                    for dx in -cells_radius..=cells_radius {
+                        if ((dx * dx + dy * dy) as f64).sqrt() > cells_radius as f64 {
+                            continue;
+                        }
+                        let nx = x as isize + dx;
+                        let ny = y as isize + dy;
+                        if nx >= 0
+                            && ny >= 0
+                            && (nx as usize) < self.width
+                            && (ny as usize) < self.height
+                        {
+                            inflated.set_cell(nx as usize, ny as usize, Cell::Occupied);
+                        }
+                    }
+                }
+            }
+        }
+        inflated
+    }
+}
+
+/// Minimal PGM (P2 / P5) image.
+struct Pgm {
+    width: usize,
+    height: usize,
+    max_value: u16,
+    pixels: Vec<u16>,
+}
+
+impl Pgm {
+    fn parse(data: &[u8]) -> Result<Self, String> {
+        let mut pos = 0;
+        let mut next_token = || -> Result<String, String> {
+            // Skip whitespaces and comments.
+            loop {
+                match data.get(pos) {
+                    Some(b'#') => {
+                        while !matches!(data.get(pos), Some(b'\n') | None) {
+                            pos += 1;
+                        }
+                    }
+                    Some(c) if c.is_ascii_whitespace() => pos += 1,
+                    Some(_) => break,
+                    None => return Err("unexpected end of PGM header".to_owned()),
+                }
+            }
+            let start = pos;
+            while matches!(data.get(pos), Some(c) if !c.is_ascii_whitespace()) {
+                pos += 1;
+            }
+            Ok(String::from_utf8_lossy(&data[start..pos]).into_owned())
+        };
+        let magic = next_token()?;
+        let mut parse_number = |name: &str| -> Result<usize, String> {
+            next_token()?
+                .parse()
+                .map_err(|e| format!("invalid PGM {}: {}", name, e))
+        };
+        let width = parse_number("width")?;
+        let height = parse_number("height")?;
+        let max_value = parse_number("max value")?;
+        if max_value == 0 || max_value > u16::MAX as usize {
+            return Err(format!("invalid PGM max value: {}", max_value));
+        }
+        let num_pixels = width * height;
+        let pixels = match magic.as_str() {
+            "P5" => {
+                // Exactly one whitespace separates the header and the raster.
+                let raster = &data[(pos + 1).min(data.len())..];
+                if max_value < 256 {
+                    raster.iter().take(num_pixels).map(|v| *v as u16).collect()
+                } else {
+                    raster
+                        .chunks_exact(2)
+                        .take(num_pixels)
+                        .map(|v| u16::from_be_bytes([v[0], v[1]]))
+                        .collect()
+                }
+            }
+            "P2" => String::from_utf8_lossy(&data[pos..])
+                .split_ascii_whitespace()
+                .take(num_pixels)
+                .map(|v| v.parse::<u16>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("invalid PGM pixel: {}", e))?,
+            _ => return Err(format!("unsupported image format: {:?}", magic)),
+        };
+        if pixels.len() != num_pixels {
+            return Err(format!(
+                "PGM size mismatch: expected {} pixels, but got {}",
+                num_pixels,
+                pixels.len()
+            ));
+        }
+        Ok(Self {
+            width,
+            height,
+            max_value: max_value as u16,
+            pixels,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_parse_pgm() {
+        let pgm = Pgm::parse(b"P2\n# comment\n3 2\n255\n0 128 255\n255 255 0\n").unwrap();
+        assert_eq!(pgm.width, 3);
+        assert_eq!(pgm.height, 2);
+        assert_eq!(pgm.pixels, vec![0, 128, 255, 255, 255, 0]);
+
+        let mut data = b"P5 2 1 255\n".to_vec();
+        data.extend_from_slice(&[10, 20]);
+        let pgm = Pgm::parse(&data).unwrap();
+        assert_eq!(pgm.pixels, vec![10, 20]);
+
+        assert!(Pgm::parse(b"P6 1 1 255\n\0\0\0").is_err());
+        assert!(Pgm::parse(b"P2 2 2 255\n0 0 0").is_err());
+    }
+
+    #[test]
+    fn test_from_map_server_image() {
+        let config = MapServerConfig {
+            image: PathBuf::from("map.pgm"),
+            resolution: 0.5,
+            origin: [-1.0, -2.0, 0.0],
+            negate: 0,
+            occupied_thresh: 0.65,
+            free_thresh: 0.196,
+        };
+        let pgm = Pgm::parse(b"P2 3 2 255\n0 205 254\n254 254 254\n").unwrap();
+        let grid = OccupancyGrid::from_map_server_image(&config, &pgm);
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        // The top row of the image is y = 1.
+        assert_eq!(grid.cell(0, 1), Some(Cell::Occupied));
+        assert_eq!(grid.cell(1, 1), Some(Cell::Unknown));
+        assert_eq!(grid.cell(2, 1), Some(Cell::Free));
+        assert_eq!(grid.cell(0, 0), Some(Cell::Free));
+        assert_eq!(grid.cell(3, 0), None);
+
+        assert_eq!(grid.world_to_cell(&Point2::new(-0.9, -1.9)), Some((0, 0)));
+        assert_eq!(grid.world_to_cell(&Point2::new(0.4, -1.4)), Some((2, 1)));
+        assert_eq!(grid.world_to_cell(&Point2::new(-1.1, -1.9)), None);
+        let center = grid.cell_to_world(2, 1);
+        assert_approx_eq!(center.x, 0.25);
+        assert_approx_eq!(center.y, -1.25);
+    }
+
+    #[test]
+    fn test_load_map_server_yaml() {
+        let grid = OccupancyGrid::load_map_server_yaml("tests/map_sample.yaml").unwrap();
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.height(), 3);
+        assert_approx_eq!(grid.resolution(), 0.05);
+        assert_approx_eq!(grid.origin().translation.x, -0.1);
+        assert_eq!(grid.cell(0, 0), Some(Cell::Free));
+        assert_eq!(grid.cell(1, 1), Some(Cell::Unknown));
+        for y in 0..3 {
+            assert_eq!(grid.cell(3, y), Some(Cell::Occupied));
+        }
+        assert!(OccupancyGrid::load_map_server_yaml("tests/no_such_map.yaml").is_err());
+    }
+
+    #[test]
+    fn test_inflate() {
+        let mut grid = OccupancyGrid::new(5, 5, 0.1, Isometry2::identity());
+        grid.set_cell(2, 2, Cell::Occupied);
+        let inflated = grid.inflate(0.1);
+        assert_eq!(inflated.cell(2, 2), Some(Cell::Occupied));
+        assert_eq!(inflated.cell(1, 2), Some(Cell::Occupied));
+        assert_eq!(inflated.cell(2, 3), Some(Cell::Occupied));
+        assert_eq!(inflated.cell(1, 1), Some(Cell::Free));
+        assert_eq!(inflated.cell(0, 2), Some(Cell::Free));
+    }
+}
@@ -0,0 +1,145 @@
+use arci::BaseVelocity;
+use k::nalgebra as na;
+use na::{Isometry2, Point2};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurePursuitConfig {
+    pub lookahead_distance: f64,
+    pub max_linear_vel: f64,
+    pub max_angular_vel: f64,
+    /// Rotate in place if the angle to the lookahead point is larger than this value.
+    #[serde(default = "default_rotate_in_place_angle")]
+    pub rotate_in_place_angle: f64,
+}
+
+fn default_rotate_in_place_angle() -> f64 {
+    std::f64::consts::FRAC_PI_2
+}
+
+/// Pure pursuit path tracking controller for differential drive (or holonomic) bases.
+#[derive(Debug, Clone)]
+pub struct PurePursuit {
+    config: PurePursuitConfig,
+}
+
+impl PurePursuit {
+    pub fn new(config: PurePursuitConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PurePursuitConfig {
+        &self.config
+    }
+
+    /// Returns the first point on the path which is farther than the lookahead distance
+    /// from the closest point to the robot, or the last point of the path.
+    /// Returns `None` if the path is empty or no distance to it is a number.
+    pub fn lookahead_point(
+        &self,
+        pose: &Isometry2<f64>,
+        path: &[Point2<f64>],
+    ) -> Option<Point2<f64>> {
+        let position = Point2::from(pose.translation.vector);
+        // NaN distances (e.g. from a broken localization) are ignored.
+        let closest_index = path
+            .iter()
+            .map(|p| na::distance(&position, p))
+            .enumerate()
+            .filter(|(_, distance)| !distance.is_nan())
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(i, _)| i)?;
+        Some(
+            path[closest_index..]
+                .iter()
+                .find(|p| na::distance(&position, p) >= self.config.lookahead_distance)
+                .copied()
+                .unwrap_or_else(|| *path.last().unwrap()),
+        )
+    }
+
+    /// Calculate the velocity in the robot frame to follow the path.
+    pub fn velocity(&self, pose: &Isometry2<f64>, path: &[Point2<f64>]) -> BaseVelocity {
+        let target = match self.lookahead_point(pose, path) {
+            Some(target) => pose.inverse_transform_point(&target),
+            None => return BaseVelocity::default(),
+        };
+        let angle = target.y.atan2(target.x);
+        if angle.abs() > self.config.rotate_in_place_angle {
+            return BaseVelocity::new(0.0, 0.0, self.config.max_angular_vel.copysign(angle));
+        }
+        let distance_squared = target.coords.norm_squared();
+        if distance_squared == 0.0 {
+            return BaseVelocity::default();
+        }
+        let curvature = 2.0 * target.y / distance_squared;
+        let mut linear_vel = self.config.max_linear_vel;
+        if (linear_vel * curvature).abs() > self.config.max_angular_vel {
+            linear_vel = self.config.max_angular_vel / curvature.abs();
+        }
+        BaseVelocity::new(linear_vel, 0.0, linear_vel * curvature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use na::Vector2;
+
+    fn pure_pursuit() -> PurePursuit {
+        PurePursuit::new(PurePursuitConfig {
+            lookahead_distance: 1.0,
+            max_linear_vel: 0.5,
+            max_angular_vel: 1.0,
+            rotate_in_place_angle: std::f64::consts::FRAC_PI_2,
+        })
+    }
+
+    #[test]
+    fn test_lookahead_point() {
+        let pp = pure_pursuit();
+        let path = vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(0.5, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.5, 0.0),
+        ];
+        let pose = Isometry2::new(Vector2::new(0.4, 0.0), 0.0);
+        assert_eq!(
+            pp.lookahead_point(&pose, &path),
+            Some(Point2::new(1.5, 0.0))
+        );
+        let pose = Isometry2::new(Vector2::new(1.4, 0.0), 0.0);
+        assert_eq!(
+            pp.lookahead_point(&pose, &path),
+            Some(Point2::new(1.5, 0.0))
+        );
+        assert_eq!(pp.lookahead_point(&pose, &[]), None);
+        let pose = Isometry2::new(Vector2::new(f64::NAN, 0.0), 0.0);
+        assert_eq!(pp.lookahead_point(&pose, &path), None);
+        let vel = pp.velocity(&pose, &path);
+        assert_eq!((vel.x, vel.y, vel.theta), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_velocity() {
+        let pp = pure_pursuit();
+        // Straight
+        let path = vec![Point2::new(0.0, 0.0), Point2::new(2.0, 0.0)];
+        let vel = pp.velocity(&Isometry2::identity(), &path);
+        assert_approx_eq!(vel.x, 0.5);
+        assert_approx_eq!(vel.theta, 0.0);
+        // Turn left
+        let path = vec![Point2::new(0.0, 0.0), Point2::new(1.0, 1.0)];
+        let vel = pp.velocity(&Isometry2::identity(), &path);
+        assert!(vel.x > 0.0);
+        assert!(vel.theta > 0.0);
+        assert!(vel.theta <= 1.0);
+        // Behind the robot
+        let path = vec![Point2::new(0.0, 0.0), Point2::new(-2.0, -0.1)];
+        let vel = pp.velocity(&Isometry2::identity(), &path);
+        assert_approx_eq!(vel.x, 0.0);
+        assert_approx_eq!(vel.theta, -1.0);
+    }
+}
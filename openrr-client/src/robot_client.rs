@@ -1,16 +1,31 @@
 use crate::{
-    create_collision_check_client, create_ik_solver_with_chain, CollisionCheckClient, Error,
-    IkClient, IkSolverConfig, IkSolverWithChain, SelfCollisionChecker, SelfCollisionCheckerConfig,
+    create_collision_check_client, create_ik_solver_with_chain, isometry, load_joints_poses,
+    merge_joints_poses, save_joints_pose, CollisionCheckClient, Error, IkClient, IkSolverConfig,
+    IkSolverWithChain, LocalMove, LocalMoveConfig, SelfCollisionChecker,
+    SelfCollisionCheckerConfig, ToolFrame, ToolFrameConfig,
 };
 use arci::{
-    BaseVelocity, Error as ArciError, JointTrajectoryClient, JointTrajectoryClientsContainer,
-    Localization, MoveBase, Navigation, Speaker,
+    BaseVelocity, BatteryState, ControllerMode, Error as ArciError, Fault, JointTrajectoryClient,
+    JointTrajectoryClientsContainer, Localization, MoveBase, Navigation, NavigationFeedback,
+    NavigationStatus, RobotStatus, Speaker, TransformBuffer, TransformResolver, Waypoint,
 };
 use async_trait::async_trait;
-use k::{nalgebra::Isometry2, Chain, Isometry3};
+use k::{
+    nalgebra::{Isometry2, Translation3, UnitQuaternion},
+    Chain, Isometry3,
+};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path, path::PathBuf, sync::Arc, time::Duration};
-use tracing::{debug, error};
+use std::{
+    collections::HashMap,
+    path::Path,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime},
+};
+use tracing::{debug, error, warn};
 
 type ArcIkClient = Arc<IkClient<Arc<dyn JointTrajectoryClient>>>;
 pub type ArcRobotClient =
@@ -34,11 +49,18 @@ where
     ik_clients: HashMap<String, ArcIkClient>,
     self_collision_checkers: HashMap<String, Arc<SelfCollisionChecker>>,
     ik_solvers: HashMap<String, Arc<IkSolverWithChain>>,
+    tool_frames: HashMap<String, ToolFrameConfig>,
     speakers: HashMap<String, Arc<dyn Speaker>>,
     localization: Option<L>,
     move_base: Option<M>,
     navigation: Option<N>,
-    joints_poses: HashMap<String, HashMap<String, Vec<f64>>>,
+    robot_status: Option<Arc<dyn RobotStatus>>,
+    local_move_config: Option<LocalMoveConfig>,
+    is_local_move_canceled: Arc<AtomicBool>,
+    transform_buffer_config: TransformBufferConfig,
+    transform_buffer: TransformBuffer,
+    joints_poses: RwLock<HashMap<String, HashMap<String, Vec<f64>>>>,
+    joints_poses_path: Option<PathBuf>,
 }
 
 impl<L, M, N> RobotClient<L, M, N>
@@ -54,6 +76,7 @@ where
         localization: Option<L>,
         move_base: Option<M>,
         navigation: Option<N>,
+        robot_status: Option<Arc<dyn RobotStatus>>,
     ) -> Result<Self, Error> {
         debug!("{:?}", config);
 
@@ -144,7 +167,23 @@ where
                     joints_pose.positions.to_owned(),
                 );
         }
-        Ok(Self {
+        let transform_buffer = TransformBuffer::new(
+            Duration::from_secs_f64(config.transform_buffer_config.cache_duration_secs),
+            Duration::from_secs_f64(config.transform_buffer_config.extrapolation_limit_secs),
+        );
+        for static_transform in &config.transform_buffer_config.static_transforms {
+            transform_buffer.set_static_transform(
+                &static_transform.parent,
+                &static_transform.child,
+                static_transform.transform(),
+            )?;
+        }
+        let tool_frames = config
+            .tool_frames_configs
+            .iter()
+            .map(|tool_frame| (tool_frame.name.to_owned(), tool_frame.clone()))
+            .collect();
+        let robot_client = Self {
             full_chain_for_collision_checker,
             raw_joint_trajectory_clients,
             all_joint_trajectory_clients,
@@ -152,12 +191,26 @@ where
             ik_clients,
             self_collision_checkers,
             ik_solvers,
+            tool_frames,
             speakers,
             localization,
             move_base,
             navigation,
-            joints_poses,
-        })
+            robot_status,
+            local_move_config: config.local_move_config.clone(),
+            is_local_move_canceled: Arc::new(AtomicBool::new(false)),
+            transform_buffer_config: config.transform_buffer_config.clone(),
+            transform_buffer,
+            joints_poses: RwLock::new(joints_poses),
+            joints_poses_path: config.joints_poses_path.clone(),
+        };
+        for ik_client_config in &config.ik_clients_configs {
+            if let Some(tool_name) = &ik_client_config.tool_name {
+                let tool = robot_client.tool_frame(&ik_client_config.name, tool_name)?;
+                robot_client.ik_clients[&ik_client_config.name].set_tool(Some(tool));
+            }
+        }
+        Ok(robot_client)
     }
     pub fn set_raw_clients_joint_positions_to_full_chain_for_collision_checker(
         &self,
@@ -267,13 +320,80 @@ where
         pose_name: &str,
         duration_sec: f64,
     ) -> Result<(), Error> {
-        if self.joints_poses.contains_key(name) && self.joints_poses[name].contains_key(pose_name) {
-            Ok(self
-                .send_joint_positions(name, &self.joints_poses[name][pose_name], duration_sec)
-                .await?)
-        } else {
-            Err(Error::NoJointsPose(name.to_owned(), pose_name.to_owned()))
+        let positions = self
+            .joints_poses
+            .read()
+            .unwrap()
+            .get(name)
+            .and_then(|poses| poses.get(pose_name))
+            .cloned()
+            .ok_or_else(|| Error::NoJointsPose(name.to_owned(), pose_name.to_owned()))?;
+        self.send_joint_positions(name, &positions, duration_sec)
+            .await
+    }
+    /// Registers `positions` as `pose_name` of the client, and writes it to `joints_poses_path`.
+    ///
+    /// If `joints_poses_path` is not specified, the pose is only available until this client is dropped.
+    pub fn save_joints_pose(
+        &self,
+        name: &str,
+        pose_name: &str,
+        positions: Vec<f64>,
+    ) -> Result<(), Error> {
+        let dof = self.joint_trajectory_client(name)?.joint_names().len();
+        if dof != positions.len() {
+            return Err(Error::MismatchedLength(dof, positions.len()));
+        }
+        let joints_pose = JointsPose {
+            pose_name: pose_name.to_owned(),
+            client_name: name.to_owned(),
+            positions,
+        };
+        match &self.joints_poses_path {
+            Some(path) => save_joints_pose(path, joints_pose.clone())?,
+            None => warn!(
+                "joints_poses_path is not specified, {} {} is not saved to file",
+                name, pose_name
+            ),
         }
+        self.joints_poses
+            .write()
+            .unwrap()
+            .entry(joints_pose.client_name)
+            .or_default()
+            .insert(joints_pose.pose_name, joints_pose.positions);
+        Ok(())
+    }
+    /// All the poses including the saved ones, sorted by the client name and the pose name.
+    pub fn joints_poses(&self) -> Vec<JointsPose> {
+        let mut poses: Vec<_> = self
+            .joints_poses
+            .read()
+            .unwrap()
+            .iter()
+            .flat_map(|(client_name, poses)| {
+                poses.iter().map(move |(pose_name, positions)| JointsPose {
+                    pose_name: pose_name.to_owned(),
+                    client_name: client_name.to_owned(),
+                    positions: positions.to_owned(),
+                })
+            })
+            .collect();
+        poses.sort_unstable_by(|a, b| {
+            (&a.client_name, &a.pose_name).cmp(&(&b.client_name, &b.pose_name))
+        });
+        poses
+    }
+    pub fn joints_poses_names(&self, name: &str) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .joints_poses
+            .read()
+            .unwrap()
+            .get(name)
+            .map(|poses| poses.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort_unstable();
+        names
     }
     pub async fn current_end_transform(&self, name: &str) -> Result<Isometry3<f64>, Error> {
         self.set_raw_clients_joint_positions_to_full_chain_for_collision_checker()?;
@@ -311,6 +431,81 @@ where
             .move_ik_with_interpolation(target_pose, duration_sec)
             .await?)
     }
+    /// Name of the frame in which target poses of `move_ik` are expressed.
+    pub fn ik_root_frame(&self, name: &str) -> Result<String, Error> {
+        self.ik_client(name)?
+            .ik_solver_with_chain
+            .root_frame()
+            .ok_or_else(|| {
+                Error::Arci(arci::Error::Uninitialized {
+                    message: format!("root frame of IkClient={}", name),
+                })
+            })
+    }
+    /// Move with IK to the target pose in `frame_id`, which can be any frame known to
+    /// `resolve_transformation` (links, map, odom and frames inserted to the transform buffer).
+    pub async fn move_ik_in_frame(
+        &self,
+        name: &str,
+        target_pose: &Isometry3<f64>,
+        frame_id: &str,
+        duration_sec: f64,
+    ) -> Result<(), Error> {
+        let target_pose = self.pose_in_ik_root_frame(name, target_pose, frame_id)?;
+        self.move_ik(name, &target_pose, duration_sec).await
+    }
+    pub async fn move_ik_with_interpolation_in_frame(
+        &self,
+        name: &str,
+        target_pose: &Isometry3<f64>,
+        frame_id: &str,
+        duration_sec: f64,
+    ) -> Result<(), Error> {
+        let target_pose = self.pose_in_ik_root_frame(name, target_pose, frame_id)?;
+        self.move_ik_with_interpolation(name, &target_pose, duration_sec)
+            .await
+    }
+    /// Convert the pose in `frame_id` into the pose in the IK root frame of `name`.
+    pub fn pose_in_ik_root_frame(
+        &self,
+        name: &str,
+        pose: &Isometry3<f64>,
+        frame_id: &str,
+    ) -> Result<Isometry3<f64>, Error> {
+        Ok(self
+            .ik_client(name)?
+            .pose_in_root_frame(self, pose, frame_id)?)
+    }
+    pub fn tool_frames_names(&self) -> Vec<String> {
+        self.tool_frames
+            .keys()
+            .map(|k| k.to_owned())
+            .collect::<Vec<String>>()
+    }
+    /// Name of the active tool of the IK client `name`.
+    pub fn ik_tool_name(&self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.ik_client(name)?.tool().map(|tool| tool.name))
+    }
+    /// Switch the active tool of the IK client `name`.
+    /// If `tool_name` is `None`, the IK target link is used as before.
+    pub fn set_ik_tool(&self, name: &str, tool_name: Option<&str>) -> Result<(), Error> {
+        let tool = match tool_name {
+            Some(tool_name) => Some(self.tool_frame(name, tool_name)?),
+            None => None,
+        };
+        self.ik_client(name)?.set_tool(tool);
+        Ok(())
+    }
+    fn tool_frame(&self, name: &str, tool_name: &str) -> Result<ToolFrame, Error> {
+        let config = self
+            .tool_frames
+            .get(tool_name)
+            .ok_or_else(|| Error::NoToolFrame(tool_name.to_owned()))?;
+        self.ik_client(name)?
+            .ik_solver_with_chain
+            .tool_frame(config)
+            .ok_or_else(|| Error::NoFixedLink(config.link.to_owned()))
+    }
     pub async fn send_joint_positions_with_pose_interpolation(
         &self,
         name: &str,
@@ -321,7 +516,7 @@ where
         let target_pose = {
             let ik_client = self.ik_client(name)?;
             ik_client.set_joint_positions_clamped(positions);
-            ik_client.ik_solver_with_chain.end_transform()
+            ik_client.end_transform()
         };
         Ok(self
             .move_ik_with_interpolation(name, &target_pose, duration_sec)
@@ -369,6 +564,194 @@ where
             }
         }
     }
+
+    pub fn localization(&self) -> &Option<L> {
+        &self.localization
+    }
+    pub fn move_base(&self) -> &Option<M> {
+        &self.move_base
+    }
+    pub fn navigation(&self) -> &Option<N> {
+        &self.navigation
+    }
+    pub fn robot_status(&self) -> &Option<Arc<dyn RobotStatus>> {
+        &self.robot_status
+    }
+    fn robot_status_or_err(&self) -> Result<&Arc<dyn RobotStatus>, ArciError> {
+        self.robot_status
+            .as_ref()
+            .ok_or_else(|| ArciError::Uninitialized {
+                message: "robot_status".to_owned(),
+            })
+    }
+
+    fn local_move(&self) -> Result<LocalMove<&L, &M>, Error> {
+        let uninitialized = |name: &str| ArciError::Uninitialized {
+            message: name.to_owned(),
+        };
+        let config = self
+            .local_move_config
+            .clone()
+            .ok_or_else(|| uninitialized("local_move_config"))?;
+        let localization = self
+            .localization
+            .as_ref()
+            .ok_or_else(|| uninitialized("localization"))?;
+        let move_base = self
+            .move_base
+            .as_ref()
+            .ok_or_else(|| uninitialized("move_base"))?;
+        Ok(LocalMove::new(localization, move_base, config)
+            .with_cancel_flag(self.is_local_move_canceled.clone()))
+    }
+    /// Move the base to the goal pose precisely by feedback control on the current pose.
+    pub async fn local_move_to_pose(
+        &self,
+        goal: Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        Ok(self
+            .local_move()?
+            .move_to_pose(goal, frame_id, timeout)
+            .await?)
+    }
+    /// Move the base to the goal pose relative to the current pose by feedback control.
+    pub async fn local_move_to_relative(
+        &self,
+        relative_goal: Isometry2<f64>,
+        frame_id: &str,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        Ok(self
+            .local_move()?
+            .move_to_relative(relative_goal, frame_id, timeout)
+            .await?)
+    }
+    pub fn cancel_local_move(&self) {
+        self.is_local_move_canceled.store(true, Ordering::Relaxed);
+    }
+
+    /// The transform tree used by `resolve_transformation`. Transforms can be inserted by users.
+    pub fn transform_buffer(&self) -> &TransformBuffer {
+        &self.transform_buffer
+    }
+    /// Name of the frame whose pose is given by the localization.
+    /// The root link of URDF is used if it is not specified in the config.
+    pub fn base_frame(&self) -> Option<String> {
+        if let Some(base_frame) = &self.transform_buffer_config.base_frame {
+            return Some(base_frame.to_owned());
+        }
+        let chain = self.full_chain_for_collision_checker.as_ref()?;
+        let root = chain.iter().next()?;
+        let link = root.link();
+        link.as_ref().map(|link| link.name.to_owned())
+    }
+    /// Insert the current transforms of links (by forward kinematics of the current joint positions)
+    /// and the current pose of the base (by the localization) to the transform buffer.
+    pub fn update_transform_buffer(&self) -> Result<(), Error> {
+        let time = SystemTime::now();
+        self.update_link_transforms(time)?;
+        self.update_base_transforms(time)
+    }
+
+    fn update_link_transforms(&self, time: SystemTime) -> Result<(), Error> {
+        if let Some(chain) = self.full_chain_for_collision_checker.as_ref() {
+            self.set_raw_clients_joint_positions_to_full_chain_for_collision_checker()?;
+            let mut nodes = chain.iter();
+            if let Some(root) = nodes.next() {
+                let root_name = root.link().as_ref().map(|link| link.name.to_owned());
+                let root_inverse = root
+                    .world_transform()
+                    .unwrap_or_else(Isometry3::identity)
+                    .inverse();
+                for node in nodes {
+                    let (root_name, link_name) = match (&root_name, node.link().as_ref()) {
+                        (Some(root_name), Some(link)) => (root_name, link.name.to_owned()),
+                        _ => continue,
+                    };
+                    if let Some(world_transform) = node.world_transform() {
+                        self.transform_buffer.set_transform(
+                            root_name,
+                            &link_name,
+                            root_inverse * world_transform,
+                            time,
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn update_base_transforms(&self, time: SystemTime) -> Result<(), Error> {
+        if let (Some(localization), Some(base_frame)) = (&self.localization, self.base_frame()) {
+            let config = &self.transform_buffer_config;
+            let map_to_base =
+                isometry2_to_isometry3(&localization.current_pose(&config.map_frame)?);
+            self.transform_buffer.set_transform(
+                &config.map_frame,
+                &base_frame,
+                map_to_base,
+                time,
+            )?;
+            if let Some(odom_frame) = &config.odom_frame {
+                // map -> odom -> base like the tf tree of ROS navigation.
+                let odom_to_base = isometry2_to_isometry3(&localization.current_pose(odom_frame)?);
+                self.transform_buffer.set_transform(
+                    &config.map_frame,
+                    odom_frame,
+                    map_to_base * odom_to_base.inverse(),
+                    time,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the frame is updated by the localization.
+    fn is_localization_frame(&self, frame: &str) -> bool {
+        let config = &self.transform_buffer_config;
+        frame == config.map_frame || config.odom_frame.as_deref() == Some(frame)
+    }
+}
+
+fn isometry2_to_isometry3(pose: &Isometry2<f64>) -> Isometry3<f64> {
+    Isometry3::from_parts(
+        Translation3::new(pose.translation.x, pose.translation.y, 0.0),
+        UnitQuaternion::from_euler_angles(0.0, 0.0, pose.rotation.angle()),
+    )
+}
+
+impl<L, M, N> TransformResolver for RobotClient<L, M, N>
+where
+    L: Localization,
+    M: MoveBase,
+    N: Navigation,
+{
+    /// Returns the transform of `to` frame in `from` frame after updating the transform buffer.
+    ///
+    /// The localization is required only if `from` or `to` is the map or the odom frame.
+    fn resolve_transformation(
+        &self,
+        from: &str,
+        to: &str,
+        time: SystemTime,
+    ) -> Result<Isometry3<f64>, ArciError> {
+        let to_arci_error = |e| match e {
+            Error::Arci(e) => e,
+            e => anyhow::anyhow!("Failed to update transform buffer: {}", e).into(),
+        };
+        let now = SystemTime::now();
+        self.update_link_transforms(now).map_err(to_arci_error)?;
+        if let Err(e) = self.update_base_transforms(now) {
+            if self.is_localization_frame(from) || self.is_localization_frame(to) {
+                return Err(to_arci_error(e));
+            }
+            debug!("Skip updating the pose of the base: {}", e);
+        }
+        self.transform_buffer.resolve_transformation(from, to, time)
+    }
 }
 
 impl<L, M, N> Localization for RobotClient<L, M, N>
@@ -406,6 +789,35 @@ where
     fn cancel(&self) -> Result<(), ArciError> {
         self.navigation.as_ref().unwrap().cancel()
     }
+
+    fn status(&self) -> Result<NavigationStatus, ArciError> {
+        self.navigation.as_ref().unwrap().status()
+    }
+
+    fn feedback(&self) -> Result<Option<NavigationFeedback>, ArciError> {
+        self.navigation.as_ref().unwrap().feedback()
+    }
+
+    fn plan_path(
+        &self,
+        goal: Isometry2<f64>,
+        frame_id: &str,
+    ) -> Result<Vec<Isometry2<f64>>, ArciError> {
+        self.navigation.as_ref().unwrap().plan_path(goal, frame_id)
+    }
+
+    async fn follow_waypoints(
+        &self,
+        waypoints: &[Waypoint],
+        frame_id: &str,
+        timeout: std::time::Duration,
+    ) -> Result<(), ArciError> {
+        self.navigation
+            .as_ref()
+            .unwrap()
+            .follow_waypoints(waypoints, frame_id, timeout)
+            .await
+    }
 }
 
 impl<L, M, N> MoveBase for RobotClient<L, M, N>
@@ -422,6 +834,26 @@ where
     }
 }
 
+impl<L, M, N> RobotStatus for RobotClient<L, M, N>
+where
+    L: Localization,
+    M: MoveBase,
+    N: Navigation,
+{
+    fn battery_state(&self) -> Result<BatteryState, ArciError> {
+        self.robot_status_or_err()?.battery_state()
+    }
+    fn is_emergency_stopped(&self) -> Result<bool, ArciError> {
+        self.robot_status_or_err()?.is_emergency_stopped()
+    }
+    fn faults(&self) -> Result<Vec<Fault>, ArciError> {
+        self.robot_status_or_err()?.faults()
+    }
+    fn controller_mode(&self) -> Result<ControllerMode, ArciError> {
+        self.robot_status_or_err()?.controller_mode()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JointTrajectoryClientsContainerConfig {
     pub name: String,
@@ -443,9 +875,91 @@ pub struct OpenrrClientsConfig {
     pub ik_clients_configs: Vec<IkClientConfig>,
     #[serde(default)]
     pub ik_solvers_configs: HashMap<String, IkSolverConfig>,
+    /// Named tool frames which can be used as the end of IK clients instead of the IK target.
+    #[serde(default)]
+    pub tool_frames_configs: Vec<ToolFrameConfig>,
 
     #[serde(default)]
     pub joints_poses: Vec<JointsPose>,
+    /// TOML file to which `RobotClient::save_joints_pose` writes the poses.
+    /// The poses in this file are merged into `joints_poses` by `merge_joints_poses_file`.
+    #[serde(default)]
+    pub joints_poses_path: Option<PathBuf>,
+
+    /// Used by `RobotClient::local_move_to_pose` and `RobotClient::local_move_to_relative`.
+    #[serde(default)]
+    pub local_move_config: Option<LocalMoveConfig>,
+
+    #[serde(default)]
+    pub transform_buffer_config: TransformBufferConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StaticTransformConfig {
+    pub parent: String,
+    pub child: String,
+    /// `[x, y, z]`
+    #[serde(default)]
+    pub translation: [f64; 3],
+    /// `[roll, pitch, yaw]`
+    #[serde(default)]
+    pub rotation: [f64; 3],
+}
+
+impl StaticTransformConfig {
+    pub fn transform(&self) -> Isometry3<f64> {
+        isometry(
+            self.translation[0],
+            self.translation[1],
+            self.translation[2],
+            self.rotation[0],
+            self.rotation[1],
+            self.rotation[2],
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransformBufferConfig {
+    #[serde(default = "default_map_frame")]
+    pub map_frame: String,
+    /// If specified, the transform from map to odom is also inserted using the localization.
+    #[serde(default)]
+    pub odom_frame: Option<String>,
+    /// The frame whose pose is given by the localization. Defaults to the root link of URDF.
+    #[serde(default)]
+    pub base_frame: Option<String>,
+    #[serde(default = "default_cache_duration_secs")]
+    pub cache_duration_secs: f64,
+    #[serde(default = "default_extrapolation_limit_secs")]
+    pub extrapolation_limit_secs: f64,
+    #[serde(default)]
+    pub static_transforms: Vec<StaticTransformConfig>,
+}
+
+fn default_map_frame() -> String {
+    "map".to_owned()
+}
+
+fn default_cache_duration_secs() -> f64 {
+    10.0
+}
+
+fn default_extrapolation_limit_secs() -> f64 {
+    0.1
+}
+
+impl Default for TransformBufferConfig {
+    fn default() -> Self {
+        Self {
+            map_frame: default_map_frame(),
+            odom_frame: None,
+            base_frame: None,
+            cache_duration_secs: default_cache_duration_secs(),
+            extrapolation_limit_secs: default_extrapolation_limit_secs(),
+            static_transforms: Vec::new(),
+        }
+    }
 }
 
 /// Make relative path into absolute path from base file (not base dir).
@@ -479,6 +993,15 @@ impl OpenrrClientsConfig {
     pub fn urdf_full_path(&self) -> &Option<PathBuf> {
         &self.urdf_full_path
     }
+    /// Merges the poses in `joints_poses_path` into `joints_poses`.
+    /// The poses in the file take precedence.
+    pub fn merge_joints_poses_file(&mut self) -> Result<(), Error> {
+        if let Some(path) = &self.joints_poses_path {
+            let poses = load_joints_poses(path)?;
+            merge_joints_poses(&mut self.joints_poses, poses);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -492,6 +1015,9 @@ pub struct IkClientConfig {
     pub name: String,
     pub client_name: String,
     pub solver_name: String,
+    /// Name of the tool frame which is active at startup.
+    #[serde(default)]
+    pub tool_name: Option<String>,
 }
 
 pub fn create_ik_clients(
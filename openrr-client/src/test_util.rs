@@ -0,0 +1,39 @@
+use arci::{BaseVelocity, Localization, MoveBase};
+use k::nalgebra as na;
+use na::{Isometry2, Vector2};
+use std::sync::Mutex;
+
+/// Base which moves by `velocity * dt` every time velocity is sent.
+pub(crate) struct SimulatedBase {
+    pose: Mutex<Isometry2<f64>>,
+    dt: f64,
+}
+
+impl SimulatedBase {
+    pub(crate) fn new(pose: Isometry2<f64>, dt: f64) -> Self {
+        Self {
+            pose: Mutex::new(pose),
+            dt,
+        }
+    }
+}
+
+impl Localization for SimulatedBase {
+    fn current_pose(&self, _frame_id: &str) -> Result<Isometry2<f64>, arci::Error> {
+        Ok(*self.pose.lock().unwrap())
+    }
+}
+
+impl MoveBase for SimulatedBase {
+    fn send_velocity(&self, velocity: &BaseVelocity) -> Result<(), arci::Error> {
+        let mut pose = self.pose.lock().unwrap();
+        *pose *= Isometry2::new(
+            Vector2::new(velocity.x, velocity.y) * self.dt,
+            velocity.theta * self.dt,
+        );
+        Ok(())
+    }
+    fn current_velocity(&self) -> Result<BaseVelocity, arci::Error> {
+        Ok(BaseVelocity::default())
+    }
+}
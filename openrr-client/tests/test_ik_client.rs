@@ -1,3 +1,4 @@
+use arci::JointTrajectoryClient;
 use assert_approx_eq::assert_approx_eq;
 use std::sync::Arc;
 
@@ -258,3 +259,85 @@ fn test_ik_solver_with_chain_generate_trajectory_with_interpolation_and_constrai
         .unwrap();
     assert!(result.len() > 0);
 }
+
+fn servo_ik_solver_with_chain() -> IkSolverWithChain {
+    let chain = k::Chain::<f64>::from_urdf_file("../openrr-planner/sample.urdf").unwrap();
+    let end_link = chain.find("l_tool_fixed").unwrap();
+    let arm = k::SerialChain::from_end(end_link);
+    let positions = vec![0.1, 0.2, 0.0, -0.5, 0.0, -0.3];
+    arm.set_joint_positions(&positions).unwrap();
+    let params = ik_solver_parameters(0.01, 0.02, 0.1, 100);
+    let ik_solver = create_random_jacobian_ik_solver(&params);
+    IkSolverWithChain::new(arm, Arc::new(ik_solver), k::Constraints::default())
+}
+
+#[test]
+fn test_ik_solver_with_chain_servo_trajectory_point() {
+    let ik_solver_with_chain = servo_ik_solver_with_chain();
+    let current_positions = ik_solver_with_chain.joint_positions();
+    let start = ik_solver_with_chain.end_transform();
+
+    let twist = Twist::new(k::Vector3::new(0.0, 0.0, 0.05), k::Vector3::zeros());
+    let point = ik_solver_with_chain.servo_trajectory_point(
+        &current_positions,
+        &k::Isometry3::identity(),
+        &twist,
+        &ServoConfig::default(),
+        std::time::Duration::from_millis(100),
+    );
+    assert_eq!(point.velocities.as_ref().unwrap().len(), 6);
+    ik_solver_with_chain.set_joint_positions_clamped(&point.positions);
+    let end = ik_solver_with_chain.end_transform();
+    let diff = end.translation.vector - start.translation.vector;
+    assert_approx_eq!(diff.x, 0.0, 1e-3);
+    assert_approx_eq!(diff.y, 0.0, 1e-3);
+    assert_approx_eq!(diff.z, 0.005, 1e-3);
+    assert_approx_eq!(end.rotation.angle_to(&start.rotation), 0.0, 1e-2);
+}
+
+#[test]
+fn test_ik_solver_with_chain_servo_max_joint_velocity() {
+    let ik_solver_with_chain = servo_ik_solver_with_chain();
+    let config = ServoConfig {
+        max_joint_velocity: 0.1,
+        ..Default::default()
+    };
+    let twist = Twist::new(k::Vector3::new(0.0, 1.0, 1.0), k::Vector3::zeros());
+    let velocities = ik_solver_with_chain.servo_joint_velocities(&twist, &config);
+    let max = velocities.iter().fold(0.0_f64, |max, v| max.max(v.abs()));
+    assert!(max > 0.0);
+    assert!(max <= 0.1 + 1e-9, "{:?}", velocities);
+}
+
+#[test]
+fn test_ik_client_servo() {
+    let client = IkClient::new(
+        arci::DummyJointTrajectoryClient::new(
+            vec![
+                "l_shoulder_yaw",
+                "l_shoulder_pitch",
+                "l_shoulder_roll",
+                "l_elbow_pitch",
+                "l_wrist_yaw",
+                "l_wrist_pitch",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        ),
+        Arc::new(servo_ik_solver_with_chain()),
+    );
+    let positions = vec![0.1, 0.2, 0.0, -0.5, 0.0, -0.3];
+    tokio_test::block_on(client.send_joint_positions(positions, std::time::Duration::default()))
+        .unwrap();
+    let start = client.current_end_transform().unwrap();
+    let twist = Twist::new(k::Vector3::new(0.0, -0.05, 0.0), k::Vector3::zeros());
+    for _ in 0..3 {
+        tokio_test::block_on(client.servo(&twist, std::time::Duration::from_millis(100))).unwrap();
+    }
+    let end = client.current_end_transform().unwrap();
+    let diff = end.translation.vector - start.translation.vector;
+    assert_approx_eq!(diff.x, 0.0, 2e-3);
+    assert_approx_eq!(diff.y, -0.015, 2e-3);
+    assert_approx_eq!(diff.z, 0.0, 2e-3);
+}
@@ -0,0 +1,367 @@
+use arci::{
+    DummyJointTrajectoryClient, DummyLocalization, DummyMoveBase, DummyNavigation,
+    JointTrajectoryClient, Localization, MoveBase, Navigation, TransformResolver,
+};
+use assert_approx_eq::assert_approx_eq;
+use k::nalgebra::{Isometry2, Vector2};
+use std::{collections::HashMap, sync::Arc, time::SystemTime};
+
+use openrr_client::*;
+
+fn robot_client(config: OpenrrClientsConfig) -> BoxRobotClient {
+    let mut localization = DummyLocalization::new();
+    localization.current_pose = Isometry2::new(Vector2::new(1.0, 2.0), std::f64::consts::FRAC_PI_2);
+    robot_client_with_localization(config, Box::new(localization))
+}
+
+fn robot_client_with_localization(
+    config: OpenrrClientsConfig,
+    localization: Box<dyn Localization>,
+) -> BoxRobotClient {
+    let arm_joint_names: Vec<_> = vec![
+        "l_shoulder_yaw",
+        "l_shoulder_pitch",
+        "l_shoulder_roll",
+        "l_elbow_pitch",
+        "l_wrist_yaw",
+        "l_wrist_pitch",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    let mut raw_joint_trajectory_clients = HashMap::new();
+    // The arm without the first joint, for the IK chain from `root_node_name`.
+    raw_joint_trajectory_clients.insert(
+        "lower_arm".to_owned(),
+        Arc::new(DummyJointTrajectoryClient::new(
+            arm_joint_names[1..].to_vec(),
+        )) as Arc<dyn JointTrajectoryClient>,
+    );
+    raw_joint_trajectory_clients.insert(
+        "arm".to_owned(),
+        Arc::new(DummyJointTrajectoryClient::new(arm_joint_names))
+            as Arc<dyn JointTrajectoryClient>,
+    );
+    RobotClient::try_new(
+        config,
+        raw_joint_trajectory_clients,
+        HashMap::new(),
+        Some(localization),
+        Some(Box::new(DummyMoveBase::new()) as Box<dyn MoveBase>),
+        Some(Box::new(DummyNavigation::new()) as Box<dyn Navigation>),
+        None,
+    )
+    .unwrap()
+}
+
+/// Config with the sample URDF of openrr-planner.
+fn sample_urdf_config() -> OpenrrClientsConfig {
+    let mut config: OpenrrClientsConfig =
+        toml::from_str(r#"urdf_path = "../openrr-planner/sample.urdf""#).unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    config
+}
+
+#[test]
+fn test_resolve_transformation() {
+    let mut config = sample_urdf_config();
+    config
+        .transform_buffer_config
+        .static_transforms
+        .push(StaticTransformConfig {
+            parent: "l_shoulder1".to_owned(),
+            child: "camera".to_owned(),
+            translation: [0.0, 0.0, 0.1],
+            rotation: [0.0, 0.0, 0.0],
+        });
+    let client = robot_client(config);
+    assert_eq!(client.base_frame(), Some("root".to_owned()));
+
+    let now = SystemTime::now();
+    let t = client
+        .resolve_transformation("root", "l_shoulder2", now)
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.0);
+    assert_approx_eq!(t.translation.y, 0.3);
+    assert_approx_eq!(t.translation.z, 0.5);
+
+    let t = client
+        .resolve_transformation("map", "l_shoulder1", now)
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.8);
+    assert_approx_eq!(t.translation.y, 2.0);
+    assert_approx_eq!(t.translation.z, 0.5);
+
+    let t = client
+        .resolve_transformation("root", "camera", now)
+        .unwrap();
+    assert_approx_eq!(t.translation.y, 0.2);
+    assert_approx_eq!(t.translation.z, 0.6);
+
+    assert!(client
+        .resolve_transformation("map", "unknown", now)
+        .is_err());
+}
+
+/// Localization which is not available, e.g. before the map is loaded.
+struct UnavailableLocalization;
+
+impl Localization for UnavailableLocalization {
+    fn current_pose(&self, _frame_id: &str) -> Result<Isometry2<f64>, arci::Error> {
+        Err(arci::Error::Uninitialized {
+            message: "localization".to_owned(),
+        })
+    }
+}
+
+#[test]
+fn test_resolve_transformation_without_localization() {
+    let client =
+        robot_client_with_localization(sample_urdf_config(), Box::new(UnavailableLocalization));
+    let now = SystemTime::now();
+    // Links of the robot are resolved without the localization.
+    let t = client
+        .resolve_transformation("root", "l_shoulder2", now)
+        .unwrap();
+    assert_approx_eq!(t.translation.y, 0.3);
+    assert!(client
+        .resolve_transformation("map", "l_shoulder2", now)
+        .is_err());
+    assert!(client.update_transform_buffer().is_err());
+}
+
+#[test]
+fn test_resolve_transformation_without_urdf() {
+    let mut config = OpenrrClientsConfig::default();
+    config.transform_buffer_config.base_frame = Some("base_link".to_owned());
+    config.transform_buffer_config.odom_frame = Some("odom".to_owned());
+    let client = robot_client(config);
+    let t = client
+        .resolve_transformation("map", "base_link", SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 1.0);
+    assert_approx_eq!(t.translation.y, 2.0);
+    // DummyLocalization returns the same pose for any frame, so odom is identical to map.
+    let t = client
+        .resolve_transformation("map", "odom", SystemTime::UNIX_EPOCH)
+        .unwrap();
+    assert_approx_eq!(t.translation.vector.norm(), 0.0);
+}
+
+#[test]
+fn test_pose_in_ik_root_frame() {
+    let mut config: OpenrrClientsConfig = toml::from_str(
+        r#"
+urdf_path = "../openrr-planner/sample.urdf"
+
+[[ik_clients_configs]]
+name = "arm_ik"
+client_name = "arm"
+solver_name = "arm_ik_solver"
+
+[ik_solvers_configs.arm_ik_solver]
+ik_target = "l_tool_fixed"
+
+[[transform_buffer_config.static_transforms]]
+parent = "l_shoulder1"
+child = "camera"
+translation = [0.0, 0.0, 0.1]
+rotation = [0.0, 0.0, 0.0]
+"#,
+    )
+    .unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    let client = robot_client(config);
+    assert_eq!(client.ik_root_frame("arm_ik").unwrap(), "root");
+
+    let pose = k::Isometry3::translation(0.1, 0.0, 0.0);
+    let t = client
+        .pose_in_ik_root_frame("arm_ik", &pose, "root")
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.1);
+    let t = client
+        .pose_in_ik_root_frame("arm_ik", &pose, "camera")
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.1);
+    assert_approx_eq!(t.translation.y, 0.2);
+    assert_approx_eq!(t.translation.z, 0.6);
+
+    assert!(client
+        .pose_in_ik_root_frame("arm_ik", &pose, "unknown")
+        .is_err());
+}
+
+#[test]
+fn test_pose_in_ik_root_frame_with_root_node() {
+    let mut config: OpenrrClientsConfig = toml::from_str(
+        r#"
+urdf_path = "../openrr-planner/sample.urdf"
+
+[[ik_clients_configs]]
+name = "arm_ik"
+client_name = "lower_arm"
+solver_name = "arm_ik_solver"
+
+[ik_solvers_configs.arm_ik_solver]
+root_node_name = "l_shoulder_pitch"
+ik_target = "l_tool_fixed"
+
+[[transform_buffer_config.static_transforms]]
+parent = "l_shoulder1"
+child = "camera"
+translation = [0.0, 0.0, 0.1]
+rotation = [0.0, 0.0, 0.0]
+"#,
+    )
+    .unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    let client = robot_client(config);
+    // The parent link of l_shoulder_pitch.
+    assert_eq!(client.ik_root_frame("arm_ik").unwrap(), "l_shoulder1");
+
+    let pose = k::Isometry3::translation(0.1, 0.0, 0.0);
+    let t = client
+        .pose_in_ik_root_frame("arm_ik", &pose, "camera")
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.1);
+    assert_approx_eq!(t.translation.y, 0.0);
+    assert_approx_eq!(t.translation.z, 0.1);
+    let t = client
+        .pose_in_ik_root_frame("arm_ik", &pose, "root")
+        .unwrap();
+    assert_approx_eq!(t.translation.x, 0.1);
+    assert_approx_eq!(t.translation.y, -0.2);
+    assert_approx_eq!(t.translation.z, -0.5);
+}
+
+#[test]
+fn test_ik_tool() {
+    let mut config: OpenrrClientsConfig = toml::from_str(
+        r#"
+urdf_path = "../openrr-planner/sample.urdf"
+
+[[ik_clients_configs]]
+name = "arm_ik"
+client_name = "arm"
+solver_name = "arm_ik_solver"
+
+[ik_solvers_configs.arm_ik_solver]
+ik_target = "l_tool_fixed"
+
+[[tool_frames_configs]]
+name = "pen"
+link = "l_tool"
+translation = [0.1, 0.0, 0.0]
+
+[[tool_frames_configs]]
+name = "shoulder"
+link = "l_shoulder1"
+"#,
+    )
+    .unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    let client = robot_client(config);
+    assert_eq!(client.ik_tool_name("arm_ik").unwrap(), None);
+    let end_pose = tokio_test::block_on(client.current_end_transform("arm_ik")).unwrap();
+
+    client.set_ik_tool("arm_ik", Some("pen")).unwrap();
+    assert_eq!(
+        client.ik_tool_name("arm_ik").unwrap(),
+        Some("pen".to_owned())
+    );
+    let tool_pose = tokio_test::block_on(client.current_end_transform("arm_ik")).unwrap();
+    let expected = end_pose * k::Isometry3::translation(0.1, 0.0, 0.0);
+    assert_approx_eq!(
+        (tool_pose.translation.vector - expected.translation.vector).norm(),
+        0.0
+    );
+
+    // Not attached to the IK target.
+    assert!(client.set_ik_tool("arm_ik", Some("shoulder")).is_err());
+    assert!(client.set_ik_tool("arm_ik", Some("unknown")).is_err());
+    assert_eq!(
+        client.ik_tool_name("arm_ik").unwrap(),
+        Some("pen".to_owned())
+    );
+
+    client.set_ik_tool("arm_ik", None).unwrap();
+    assert_eq!(client.ik_tool_name("arm_ik").unwrap(), None);
+}
+
+#[test]
+fn test_ik_tool_on_non_fixed_joint() {
+    let mut config: OpenrrClientsConfig = toml::from_str(
+        r#"
+urdf_path = "../openrr-planner/sample.urdf"
+
+[[ik_clients_configs]]
+name = "arm_ik"
+client_name = "arm"
+solver_name = "arm_ik_solver"
+
+[ik_solvers_configs.arm_ik_solver]
+ik_target = "l_wrist_pitch"
+
+[[tool_frames_configs]]
+name = "pen"
+link = "l_tool"
+
+[[tool_frames_configs]]
+name = "finger"
+link = "l_gripper1"
+"#,
+    )
+    .unwrap();
+    config.resolve_path("Cargo.toml").unwrap();
+    let client = robot_client(config);
+    let end_pose = tokio_test::block_on(client.current_end_transform("arm_ik")).unwrap();
+    client.set_ik_tool("arm_ik", Some("pen")).unwrap();
+    // The origin of l_tool_fixed in the URDF.
+    let tool_pose = tokio_test::block_on(client.current_end_transform("arm_ik")).unwrap();
+    let expected = end_pose * k::Isometry3::translation(0.2, 0.0, 0.0);
+    assert_approx_eq!(
+        (tool_pose.translation.vector - expected.translation.vector).norm(),
+        0.0
+    );
+    assert_approx_eq!(tool_pose.rotation.angle_to(&expected.rotation), 0.0);
+    // l_gripper1 moves by the prismatic joint which is not in the IK chain.
+    assert!(matches!(
+        client.set_ik_tool("arm_ik", Some("finger")),
+        Err(Error::NoFixedLink(link)) if link == "l_gripper1"
+    ));
+}
+
+#[test]
+fn test_save_joints_pose() {
+    let path = std::env::temp_dir().join("openrr_client_test_robot_client_save_joints_pose.toml");
+    let _ = std::fs::remove_file(&path);
+    // `send_joints_pose` requires the URDF.
+    let mut config = sample_urdf_config();
+    config.joints_poses_path = Some(path.clone());
+    let client = robot_client(config);
+    let positions = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
+    client
+        .save_joints_pose("arm", "taught", positions.clone())
+        .unwrap();
+    assert!(client.save_joints_pose("arm", "short", vec![0.0]).is_err());
+    assert!(client
+        .save_joints_pose("no_client", "taught", positions.clone())
+        .is_err());
+    assert_eq!(client.joints_poses_names("arm"), vec!["taught".to_owned()]);
+    assert_eq!(client.joints_poses().len(), 1);
+    assert_eq!(client.joints_poses()[0].positions, positions);
+    tokio_test::block_on(client.send_joints_pose("arm", "taught", 0.1)).unwrap();
+    assert_eq!(
+        tokio_test::block_on(client.current_joint_positions("arm")).unwrap(),
+        positions
+    );
+
+    // The saved pose is merged into the config.
+    let mut config = OpenrrClientsConfig::default();
+    config.joints_poses_path = Some(path.clone());
+    config.merge_joints_poses_file().unwrap();
+    assert_eq!(config.joints_poses.len(), 1);
+    assert_eq!(config.joints_poses[0].pose_name, "taught");
+    assert_eq!(config.joints_poses[0].positions, positions);
+    std::fs::remove_file(&path).unwrap();
+}
@@ -12,8 +12,12 @@ pub enum Error {
     NoCommand(Vec<String>),
     #[error("openrr-command: Failed to execute Command {:?} ({}).", .0, .1)]
     CommandExecutionFailure(Vec<String>, #[source] std::io::Error),
+    #[error("openrr-command: Failed to parse Command {:?} ({}).", .0, .1)]
+    CommandParseFailure(String, String),
     #[error("openrr-command: Command {:?} Error ({:?}).", .0, .1)]
     CommandFailure(Vec<String>, String),
+    #[error("openrr-command: Script Error ({}).", .0)]
+    ScriptFailure(String),
     #[error("openrr-command: arci: {:?}", .0)]
     Arci(#[from] arci::Error),
     #[error("openrr-command: openrr-client: {:?}", .0)]
@@ -1,5 +1,11 @@
 mod error;
 mod robot_command;
+mod robot_script;
+mod robot_shell;
+#[cfg(test)]
+mod test_util;
 
 pub use error::*;
 pub use robot_command::*;
+pub use robot_script::*;
+pub use robot_shell::*;
@@ -1,5 +1,7 @@
-use crate::Error as OpenrrCommandError;
-use arci::{BaseVelocity, Localization, MoveBase, Navigation};
+use crate::{Error as OpenrrCommandError, RobotScriptEngine};
+use arci::{
+    BaseVelocity, Localization, MoveBase, Navigation, RobotStatus, TransformResolver, Waypoint,
+};
 use async_recursion::async_recursion;
 use k::nalgebra::{Isometry2, Vector2};
 use openrr_client::{isometry, BoxRobotClient};
@@ -9,8 +11,9 @@ use std::{
     io::{BufRead, BufReader},
     path::PathBuf,
     process::Command,
+    sync::Arc,
     thread::sleep,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 use structopt::StructOpt;
 use tracing::{error, info};
@@ -28,6 +31,28 @@ where
     Ok((s[..pos].parse()?, s[pos + 1..].parse()?))
 }
 
+/// Parse waypoint formatted as `x,y,yaw` or `x,y,yaw,translation_tolerance,rotation_tolerance`.
+fn parse_waypoint(s: &str) -> Result<Waypoint, Box<dyn Error>> {
+    let values = s
+        .split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let pose = |values: &[f64]| Isometry2::new(Vector2::new(values[0], values[1]), values[2]);
+    match values.len() {
+        3 => Ok(Waypoint::new(pose(&values))),
+        5 => Ok(Waypoint::with_tolerance(
+            pose(&values),
+            values[3],
+            values[4],
+        )),
+        _ => Err(format!(
+            "invalid waypoint `{}`: expected `x,y,yaw` or `x,y,yaw,translation_tolerance,rotation_tolerance`",
+            s
+        )
+        .into()),
+    }
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(rename_all = "snake_case")]
 pub enum RobotCommand {
@@ -50,6 +75,8 @@ pub enum RobotCommand {
         #[structopt(short, long, default_value = "3.0")]
         duration: f64,
     },
+    /// Save current joint positions as a predefined pose.
+    SavePose { name: String, pose_name: String },
     /// Move with ik
     MoveIk {
         name: String,
@@ -72,7 +99,16 @@ pub enum RobotCommand {
         use_interpolation: bool,
         #[structopt(name = "local", short, long)]
         is_local: bool,
+        /// Frame in which the target pose is expressed (e.g. a camera link or map).
+        /// Unspecified components are taken from the current pose in this frame.
+        #[structopt(short, long, conflicts_with = "local")]
+        frame_id: Option<String>,
+        /// Switch the active tool frame before moving.
+        #[structopt(long)]
+        tool: Option<String>,
     },
+    /// Switch the active tool frame of the IK client. The IK target is used if no tool is given.
+    SetIkTool { name: String, tool: Option<String> },
     /// Get joint positions and end pose if applicable.
     GetState { name: String },
     /// Load commands from file and execute them.
@@ -80,6 +116,11 @@ pub enum RobotCommand {
         #[structopt(parse(from_os_str))]
         command_file_path: PathBuf,
     },
+    /// Run a Rhai script which can use variables, loops, conditionals and error handling.
+    RunScript {
+        #[structopt(parse(from_os_str))]
+        script_path: PathBuf,
+    },
     /// List available clients.
     List,
     /// Speak text message.
@@ -100,6 +141,40 @@ pub enum RobotCommand {
     },
     /// Cancel navigation gaol.
     CancelNavigationGoal,
+    /// Get navigation goal status and feedback.
+    GetNavigationStatus,
+    /// Visit navigation waypoints in order.
+    FollowNavigationWaypoints {
+        /// Waypoint formatted as `x,y,yaw` or `x,y,yaw,translation_tolerance,rotation_tolerance`.
+        #[structopt(short, long, parse(try_from_str=parse_waypoint))]
+        waypoint: Vec<Waypoint>,
+        #[structopt(short, long, default_value = "map")]
+        frame_id: String,
+        /// Timeout for each waypoint.
+        #[structopt(short, long, default_value = "100.0")]
+        timeout_secs: f64,
+    },
+    /// Plan navigation path to the goal pose without moving.
+    PlanNavigationPath {
+        x: f64,
+        y: f64,
+        yaw: f64,
+        #[structopt(short, long, default_value = "map")]
+        frame_id: String,
+    },
+    /// Move base to the pose precisely by feedback control on the current pose.
+    LocalMove {
+        x: f64,
+        y: f64,
+        yaw: f64,
+        #[structopt(short, long, default_value = "map")]
+        frame_id: String,
+        #[structopt(short, long, default_value = "10.0")]
+        timeout_secs: f64,
+        /// The pose is relative to the current pose.
+        #[structopt(name = "relative", short, long)]
+        is_relative: bool,
+    },
     /// Send base velocity.
     SendBaseVelocity {
         x: f64,
@@ -108,6 +183,8 @@ pub enum RobotCommand {
         #[structopt(short, long, default_value = "1.0")]
         duration_secs: f64,
     },
+    /// Get robot status (battery, emergency stop, faults and controller mode).
+    GetStatus,
 }
 
 pub struct RobotCommandExecutor {}
@@ -116,7 +193,7 @@ impl RobotCommandExecutor {
     #[async_recursion]
     pub async fn execute(
         &self,
-        client: &BoxRobotClient,
+        client: &Arc<BoxRobotClient>,
         command: &RobotCommand,
     ) -> Result<(), OpenrrCommandError> {
         match &command {
@@ -155,6 +232,11 @@ impl RobotCommandExecutor {
             } => {
                 client.send_joints_pose(name, pose_name, *duration).await?;
             }
+            RobotCommand::SavePose { name, pose_name } => {
+                let positions = client.current_joint_positions(name).await?;
+                client.save_joints_pose(name, pose_name, positions.clone())?;
+                println!("Saved {} {} : {:?}", name, pose_name, positions);
+            }
             RobotCommand::MoveIk {
                 name,
                 x,
@@ -166,12 +248,26 @@ impl RobotCommandExecutor {
                 duration,
                 use_interpolation,
                 is_local,
+                frame_id,
+                tool,
             } => {
                 if !client.is_ik_client(name) {
                     return Err(OpenrrCommandError::NoIkClient(name.clone()));
                 }
+                if let Some(tool) = tool {
+                    client.set_ik_tool(name, Some(tool))?;
+                }
                 let mut should_send = false;
                 let current_pose = client.current_end_transform(name).await?;
+                let current_pose = if let Some(frame_id) = frame_id {
+                    client.resolve_transformation(
+                        frame_id,
+                        &client.ik_root_frame(name)?,
+                        SystemTime::now(),
+                    )? * current_pose
+                } else {
+                    current_pose
+                };
                 let target_pose = [
                     if let Some(x) = x {
                         should_send = true;
@@ -236,6 +332,8 @@ impl RobotCommandExecutor {
 
                 let target_pose = if *is_local {
                     client.transform(name, &target_pose).await?
+                } else if let Some(frame_id) = frame_id {
+                    client.pose_in_ik_root_frame(name, &target_pose, frame_id)?
                 } else {
                     target_pose
                 };
@@ -247,12 +345,21 @@ impl RobotCommandExecutor {
                     client.move_ik(name, &target_pose, *duration).await?
                 }
             }
+            RobotCommand::SetIkTool { name, tool } => {
+                if !client.is_ik_client(name) {
+                    return Err(OpenrrCommandError::NoIkClient(name.clone()));
+                }
+                client.set_ik_tool(name, tool.as_deref())?;
+            }
             RobotCommand::GetState { name } => {
                 println!(
                     "Joint positions : {:?}",
                     client.current_joint_positions(name).await?
                 );
                 if client.is_ik_client(name) {
+                    if let Some(tool) = client.ik_tool_name(name)? {
+                        println!("Tool : {}", tool);
+                    }
                     let pose = client.current_end_transform(name).await?;
                     println!("End pose");
                     println!(" translation = {:?}", pose.translation.vector.data);
@@ -261,14 +368,22 @@ impl RobotCommandExecutor {
             }
             RobotCommand::LoadCommands { command_file_path } => {
                 for command in load_command_file_and_filter(command_file_path.clone())? {
-                    let command_parsed_iter = command.split_whitespace();
-                    // Parse the command
-                    let read_opt = RobotCommand::from_iter(command_parsed_iter);
+                    // Parse the command with quotations and comments like a shell.
+                    // The first word is the binary name.
+                    let read_opt = shell_words::split(&command)
+                        .map_err(|e| e.to_string())
+                        .and_then(|words| {
+                            RobotCommand::from_iter_safe(words).map_err(|e| e.message)
+                        })
+                        .map_err(|e| OpenrrCommandError::CommandParseFailure(command.clone(), e))?;
                     // Execute the parsed command
                     info!("Executing {}", command);
                     self.execute(client, &read_opt).await?;
                 }
             }
+            RobotCommand::RunScript { script_path } => {
+                RobotScriptEngine::new(client.clone()).run_file(script_path)?;
+            }
             RobotCommand::List => {
                 println!("Raw joint trajectory clients");
                 for name in client.raw_joint_trajectory_clients_names() {
@@ -286,11 +401,12 @@ impl RobotCommandExecutor {
                 for name in client.ik_clients_names() {
                     println!(" {}", name);
                 }
+                println!("Tool frames");
+                for name in client.tool_frames_names() {
+                    println!(" {}", name);
+                }
             }
             RobotCommand::Speak { name, message } => {
-                // TODO: Parse quotations and comments
-                // Currently '"Foo bar" # hoge' is parsed as message in below command.
-                // 'openrr_apps_robot_command speak "Foo bar" # hoge'
                 client.speak(&name, &message.join(" "));
             }
             RobotCommand::ExecuteCommand { command } => {
@@ -333,6 +449,53 @@ impl RobotCommandExecutor {
             RobotCommand::CancelNavigationGoal => {
                 client.cancel()?;
             }
+            RobotCommand::GetNavigationStatus => {
+                println!("Status : {:?}", client.status()?);
+                if let Some(feedback) = client.feedback()? {
+                    println!("Current pose {}", feedback.current_pose);
+                    println!("Distance remaining : {:.3}", feedback.distance_remaining);
+                }
+            }
+            RobotCommand::FollowNavigationWaypoints {
+                waypoint,
+                frame_id,
+                timeout_secs,
+            } => {
+                client
+                    .follow_waypoints(waypoint, frame_id, Duration::from_secs_f64(*timeout_secs))
+                    .await?;
+            }
+            RobotCommand::PlanNavigationPath {
+                x,
+                y,
+                yaw,
+                frame_id,
+            } => {
+                let path =
+                    client.plan_path(Isometry2::new(Vector2::new(*x, *y), *yaw), frame_id)?;
+                println!("Path");
+                for pose in path {
+                    println!(" {}", pose);
+                }
+            }
+            RobotCommand::LocalMove {
+                x,
+                y,
+                yaw,
+                frame_id,
+                timeout_secs,
+                is_relative,
+            } => {
+                let goal = Isometry2::new(Vector2::new(*x, *y), *yaw);
+                let timeout = Duration::from_secs_f64(*timeout_secs);
+                if *is_relative {
+                    client
+                        .local_move_to_relative(goal, frame_id, timeout)
+                        .await?;
+                } else {
+                    client.local_move_to_pose(goal, frame_id, timeout).await?;
+                }
+            }
             RobotCommand::SendBaseVelocity {
                 x,
                 y,
@@ -347,11 +510,40 @@ impl RobotCommandExecutor {
                     sleep(sleep_duration);
                 }
             }
+            RobotCommand::GetStatus => {
+                let battery_state = client.battery_state()?;
+                println!("Battery");
+                match battery_state.percentage {
+                    Some(percentage) => println!(" percentage = {:.1}%", percentage * 100.0),
+                    None => println!(" percentage = unknown"),
+                }
+                if let Some(voltage) = battery_state.voltage {
+                    println!(" voltage = {:.2}V", voltage);
+                }
+                println!(" charging = {}", battery_state.is_charging);
+                println!("Emergency stopped : {}", client.is_emergency_stopped()?);
+                println!("Controller mode : {:?}", client.controller_mode()?);
+                let faults = client.faults()?;
+                println!("Faults");
+                for fault in faults {
+                    println!(" [{:?}] {}: {}", fault.level, fault.name, fault.message);
+                }
+            }
         }
         Ok(())
     }
 }
 
+/// Parses `line` with quotations and comments like a shell.
+///
+/// The first argument is treated as the binary name by structopt, so `bin_name` is prepended.
+pub(crate) fn parse_command_line(bin_name: &str, line: &str) -> Result<RobotCommand, String> {
+    let words = shell_words::split(line).map_err(|e| e.to_string())?;
+    // Includes the usage requested by `help` or `--help`.
+    RobotCommand::from_iter_safe(std::iter::once(bin_name.to_owned()).chain(words))
+        .map_err(|e| e.message)
+}
+
 pub fn load_command_file_and_filter(file_path: PathBuf) -> Result<Vec<String>, OpenrrCommandError> {
     let file = File::open(&file_path)
         .map_err(|e| OpenrrCommandError::CommandFileOpenFailure(file_path, e.to_string()))?;
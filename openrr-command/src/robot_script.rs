@@ -0,0 +1,303 @@
+use crate::{robot_command::parse_command_line, Error, RobotCommandExecutor};
+use arci::{BaseVelocity, Localization, MoveBase, Navigation};
+use k::nalgebra::{Isometry2, Vector2};
+use openrr_client::{isometry, BoxRobotClient};
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+use std::{
+    future::Future,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn script_error(e: impl std::fmt::Display) -> Box<EvalAltResult> {
+    e.to_string().into()
+}
+
+/// Runs the async method of the client from the script.
+///
+/// The script is evaluated in `tokio::task::block_in_place`, so the handle of the runtime is available.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Handle::current().block_on(future)
+}
+
+fn to_positions(array: Array) -> ScriptResult<Vec<f64>> {
+    array
+        .into_iter()
+        .map(|v| {
+            if let Some(v) = v.clone().try_cast::<f64>() {
+                Ok(v)
+            } else if let Some(v) = v.try_cast::<i64>() {
+                Ok(v as f64)
+            } else {
+                Err(script_error("positions must be an array of numbers"))
+            }
+        })
+        .collect()
+}
+
+fn to_array(values: impl IntoIterator<Item = f64>) -> Array {
+    values.into_iter().map(Dynamic::from).collect()
+}
+
+/// Script engine for robot tasks using [Rhai](https://rhai.rs).
+///
+/// The following functions are available in addition to the Rhai standard library.
+/// Numbers must be written as floats (e.g. `1.0`) except for positions arrays.
+///
+/// - `send_joints(name, positions, duration_secs)`
+/// - `send_joints_pose(name, pose_name, duration_secs)`
+/// - `current_joint_positions(name)`: returns an array
+/// - `move_ik(name, x, y, z, roll, pitch, yaw, duration_secs)`
+/// - `current_end_pose(name)`: returns `[x, y, z, roll, pitch, yaw]`
+/// - `speak(name, message)`
+/// - `send_navigation_goal(x, y, yaw, frame_id, timeout_secs)`
+/// - `cancel_navigation_goal()`
+/// - `navigation_status()`: returns the status as a string, e.g. `"Succeeded"`
+/// - `current_pose(frame_id)`: returns `[x, y, yaw]`
+/// - `send_base_velocity(x, y, theta, duration_secs)`
+/// - `sleep(secs)`
+/// - `command(line)`: executes a `RobotCommand`, e.g. `command("send_joints arm -j 0=1.0")`.
+///   The line is split like a shell, e.g. `command("speak voice 'hello world'")`.
+///
+/// Errors of these functions can be caught by `try { } catch (err) { }`.
+///
+/// The script must be run in the multi-threaded runtime of tokio, because the functions block
+/// the thread by `tokio::task::block_in_place`.
+pub struct RobotScriptEngine {
+    engine: Engine,
+}
+
+impl RobotScriptEngine {
+    pub fn new(client: Arc<BoxRobotClient>) -> Self {
+        let mut engine = Engine::new();
+
+        let c = client.clone();
+        engine.register_fn(
+            "send_joints",
+            move |name: &str, positions: Array, duration_secs: f64| -> ScriptResult<()> {
+                let positions = to_positions(positions)?;
+                block_on(c.send_joint_positions(name, &positions, duration_secs))
+                    .map_err(script_error)
+            },
+        );
+        let c = client.clone();
+        engine.register_fn(
+            "send_joints_pose",
+            move |name: &str, pose_name: &str, duration_secs: f64| -> ScriptResult<()> {
+                block_on(c.send_joints_pose(name, pose_name, duration_secs)).map_err(script_error)
+            },
+        );
+        let c = client.clone();
+        engine.register_fn(
+            "current_joint_positions",
+            move |name: &str| -> ScriptResult<Array> {
+                Ok(to_array(
+                    block_on(c.current_joint_positions(name)).map_err(script_error)?,
+                ))
+            },
+        );
+        let c = client.clone();
+        engine.register_fn(
+            "move_ik",
+            move |name: &str,
+                  x: f64,
+                  y: f64,
+                  z: f64,
+                  roll: f64,
+                  pitch: f64,
+                  yaw: f64,
+                  duration_secs: f64|
+                  -> ScriptResult<()> {
+                if !c.is_ik_client(name) {
+                    return Err(script_error(Error::NoIkClient(name.to_owned())));
+                }
+                let target_pose = isometry(x, y, z, roll, pitch, yaw);
+                block_on(c.move_ik(name, &target_pose, duration_secs)).map_err(script_error)
+            },
+        );
+        let c = client.clone();
+        engine.register_fn(
+            "current_end_pose",
+            move |name: &str| -> ScriptResult<Array> {
+                let pose = block_on(c.current_end_transform(name)).map_err(script_error)?;
+                let (roll, pitch, yaw) = pose.rotation.euler_angles();
+                let t = pose.translation;
+                Ok(to_array(vec![t.x, t.y, t.z, roll, pitch, yaw]))
+            },
+        );
+        let c = client.clone();
+        engine.register_fn("speak", move |name: &str, message: &str| {
+            c.speak(name, message);
+        });
+        let c = client.clone();
+        engine.register_fn(
+            "send_navigation_goal",
+            move |x: f64,
+                  y: f64,
+                  yaw: f64,
+                  frame_id: &str,
+                  timeout_secs: f64|
+                  -> ScriptResult<()> {
+                block_on(c.move_to(
+                    Isometry2::new(Vector2::new(x, y), yaw),
+                    frame_id,
+                    Duration::from_secs_f64(timeout_secs),
+                ))
+                .map_err(script_error)
+            },
+        );
+        let c = client.clone();
+        engine.register_fn("cancel_navigation_goal", move || -> ScriptResult<()> {
+            c.cancel().map_err(script_error)
+        });
+        let c = client.clone();
+        engine.register_fn("navigation_status", move || -> ScriptResult<String> {
+            Ok(format!("{:?}", c.status().map_err(script_error)?))
+        });
+        let c = client.clone();
+        engine.register_fn(
+            "current_pose",
+            move |frame_id: &str| -> ScriptResult<Array> {
+                let pose = c.current_pose(frame_id).map_err(script_error)?;
+                Ok(to_array(vec![
+                    pose.translation.x,
+                    pose.translation.y,
+                    pose.rotation.angle(),
+                ]))
+            },
+        );
+        let c = client.clone();
+        engine.register_fn(
+            "send_base_velocity",
+            move |x: f64, y: f64, theta: f64, duration_secs: f64| -> ScriptResult<()> {
+                let start = Instant::now();
+                let duration = Duration::from_secs_f64(duration_secs);
+                while start.elapsed() < duration {
+                    c.send_velocity(&BaseVelocity::new(x, y, theta))
+                        .map_err(script_error)?;
+                    std::thread::sleep(Duration::from_secs_f64(0.01));
+                }
+                Ok(())
+            },
+        );
+        engine.register_fn("sleep", |secs: f64| {
+            std::thread::sleep(Duration::from_secs_f64(secs));
+        });
+        let c = client;
+        engine.register_fn("command", move |line: &str| -> ScriptResult<()> {
+            let command = parse_command_line(env!("CARGO_PKG_NAME"), line).map_err(script_error)?;
+            info!("Executing {}", line);
+            block_on(RobotCommandExecutor {}.execute(&c, &command)).map_err(script_error)
+        });
+
+        Self { engine }
+    }
+
+    /// Evaluates the script.
+    ///
+    /// # Panics
+    ///
+    /// Panics if it is called outside of the multi-threaded runtime of tokio.
+    pub fn run(&self, script: &str) -> Result<(), Error> {
+        tokio::task::block_in_place(|| self.engine.run(script))
+            .map_err(|e| Error::ScriptFailure(e.to_string()))
+    }
+
+    /// Evaluates the script file.
+    pub fn run_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let script = std::fs::read_to_string(&path)
+            .map_err(|e| Error::CommandFileOpenFailure(path.as_ref().to_owned(), e.to_string()))?;
+        self.run(&script)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::robot_client;
+    use arci::DummySpeaker;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_joints() {
+        let client = robot_client(Arc::new(DummySpeaker::new()));
+        RobotScriptEngine::new(client.clone())
+            .run(
+                r#"
+                send_joints("arm", [0, 0.5, 0, 0, 0, 0], 0.0);
+                let positions = current_joint_positions("arm");
+                if positions[1] != 0.5 {
+                    throw "unexpected positions";
+                }
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            client.current_joint_positions("arm").await.unwrap(),
+            vec![0.0, 0.5, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_command() {
+        let speaker = Arc::new(DummySpeaker::new());
+        let client = robot_client(speaker.clone());
+        RobotScriptEngine::new(client.clone())
+            .run(
+                r#"
+                command("send_joints arm -d 0.0 -j 0=0.5");
+                command("speak voice 'hello  world'");
+                "#,
+            )
+            .unwrap();
+        assert_eq!(
+            client.current_joint_positions("arm").await.unwrap(),
+            vec![0.5, 0.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        // The quoted message is one argument.
+        assert_eq!(speaker.messages(), vec!["hello  world"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_load_commands() {
+        let path = std::env::temp_dir().join("openrr_command_test_load_commands.txt");
+        std::fs::write(
+            &path,
+            "# comment\nopenrr_apps_robot_command speak voice \"hello  world\" # comment\n",
+        )
+        .unwrap();
+        let speaker = Arc::new(DummySpeaker::new());
+        RobotScriptEngine::new(robot_client(speaker.clone()))
+            .run(&format!(r#"command("load_commands {}");"#, path.display()))
+            .unwrap();
+        assert_eq!(speaker.messages(), vec!["hello  world"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_error() {
+        let engine = RobotScriptEngine::new(robot_client(Arc::new(DummySpeaker::new())));
+        let result = engine.run(r#"send_joints("leg", [1.0], 0.0);"#);
+        assert!(matches!(result, Err(Error::ScriptFailure(_))));
+        let result = engine.run(r#"command("speak voice 'hello");"#);
+        assert!(matches!(result, Err(Error::ScriptFailure(_))));
+        // Errors can be caught in the script.
+        engine
+            .run(
+                r#"
+                try {
+                    send_joints("leg", [1.0], 0.0);
+                    throw "not failed";
+                } catch (err) {
+                    if err == "not failed" {
+                        throw err;
+                    }
+                }
+                "#,
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use crate::{robot_command::parse_command_line, RobotCommandExecutor};
+use openrr_client::BoxRobotClient;
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    validate::Validator, Context, Editor, Helper,
+};
+use std::{path::PathBuf, sync::Arc};
+use tracing::{info, warn};
+
+const PROMPT: &str = "openrr> ";
+const EXIT_COMMANDS: &[&str] = &["exit", "quit"];
+
+/// Names of the subcommands of `RobotCommand`.
+///
+/// clap 2 has no public accessor of the subcommands, so they are listed here and checked
+/// against the usage by the test.
+const COMMAND_NAMES: &[&str] = &[
+    "send_joints",
+    "send_joints_pose",
+    "save_pose",
+    "move_ik",
+    "set_ik_tool",
+    "get_state",
+    "load_commands",
+    "run_script",
+    "list",
+    "speak",
+    "execute_command",
+    "get_navigation_current_pose",
+    "send_navigation_goal",
+    "cancel_navigation_goal",
+    "get_navigation_status",
+    "follow_navigation_waypoints",
+    "plan_navigation_path",
+    "local_move",
+    "send_base_velocity",
+    "get_status",
+];
+
+fn command_names() -> Vec<String> {
+    COMMAND_NAMES.iter().map(|s| s.to_string()).collect()
+}
+
+/// Completes command names, client names, pose names, tool names and speaker names.
+struct RobotShellHelper {
+    client: Arc<BoxRobotClient>,
+    command_names: Vec<String>,
+}
+
+impl RobotShellHelper {
+    fn client_names(&self) -> Vec<String> {
+        let mut names = self.client.joint_trajectory_clients_names();
+        names.extend(self.client.ik_clients_names());
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Candidates of the `index`-th word of the line, where the first word is the command.
+    fn candidates(&self, words: &[&str], index: usize) -> Vec<String> {
+        if index == 0 {
+            let mut names = self.command_names.clone();
+            // `help` is added by clap when parsing.
+            names.push("help".to_owned());
+            names.extend(EXIT_COMMANDS.iter().map(|s| s.to_string()));
+            return names;
+        }
+        match (words[0], index) {
+            (
+                "send_joints" | "send_joints_pose" | "save_pose" | "move_ik" | "set_ik_tool"
+                | "get_state",
+                1,
+            ) => self.client_names(),
+            ("send_joints_pose" | "save_pose", 2) => self.client.joints_poses_names(words[1]),
+            ("set_ik_tool", 2) => self.client.tool_frames_names(),
+            ("speak", 1) => self.client.speakers().keys().cloned().collect(),
+            ("help", 1) => self.command_names.clone(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Completer for RobotShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let words: Vec<_> = line.split_whitespace().collect();
+        let (index, word) = match words.last() {
+            Some(last) if !line.ends_with(char::is_whitespace) => (words.len() - 1, *last),
+            _ => (words.len(), ""),
+        };
+        let mut candidates: Vec<_> = self
+            .candidates(&words, index)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .collect();
+        candidates.sort();
+        Ok((pos - word.len(), candidates))
+    }
+}
+
+impl Hinter for RobotShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for RobotShellHelper {}
+
+impl Validator for RobotShellHelper {}
+
+impl Helper for RobotShellHelper {}
+
+/// Interactive shell to execute `RobotCommand` with one `BoxRobotClient`.
+///
+/// It supports history and tab completion. `help` or `help <command>` shows the usage,
+/// and `exit`, `quit` or Ctrl-D exits the shell.
+pub struct RobotShell {
+    client: Arc<BoxRobotClient>,
+    history_path: Option<PathBuf>,
+}
+
+impl RobotShell {
+    pub fn new(client: Arc<BoxRobotClient>) -> Self {
+        Self {
+            client,
+            history_path: None,
+        }
+    }
+
+    /// Loads and saves the history to `history_path`.
+    pub fn with_history_path(mut self, history_path: PathBuf) -> Self {
+        self.history_path = Some(history_path);
+        self
+    }
+
+    pub async fn run(&self) {
+        let mut editor = Editor::<RobotShellHelper>::new();
+        editor.set_helper(Some(RobotShellHelper {
+            client: self.client.clone(),
+            command_names: command_names(),
+        }));
+        if let Some(path) = &self.history_path {
+            if path.exists() {
+                if let Err(e) = editor.load_history(path) {
+                    warn!("Failed to load history {:?}: {}", path, e);
+                }
+            }
+        }
+        let executor = RobotCommandExecutor {};
+        loop {
+            let line = match tokio::task::block_in_place(|| editor.readline(PROMPT)) {
+                Ok(line) => line,
+                // Ctrl-C clears the current line.
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => {
+                    warn!("Failed to read line: {}", e);
+                    break;
+                }
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            editor.add_history_entry(line);
+            if EXIT_COMMANDS.contains(&line) {
+                break;
+            }
+            let command = match parse_command_line("", line) {
+                Ok(command) => command,
+                Err(message) => {
+                    println!("{}", message);
+                    continue;
+                }
+            };
+            info!("Executing {}", line);
+            if let Err(e) = executor.execute(&self.client, &command).await {
+                println!("{}", e);
+            }
+        }
+        if let Some(path) = &self.history_path {
+            if let Err(e) = editor.save_history(path) {
+                warn!("Failed to save history {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_util::robot_client, RobotCommand};
+    use arci::DummySpeaker;
+    use rustyline::history::History;
+    use structopt::StructOpt;
+
+    fn helper() -> RobotShellHelper {
+        RobotShellHelper {
+            client: robot_client(Arc::new(DummySpeaker::new())),
+            command_names: command_names(),
+        }
+    }
+
+    fn complete(helper: &RobotShellHelper, line: &str) -> (usize, Vec<String>) {
+        let history = History::new();
+        helper
+            .complete(line, line.len(), &Context::new(&history))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_command_names() {
+        let mut help = vec![];
+        RobotCommand::clap().write_long_help(&mut help).unwrap();
+        let help = String::from_utf8(help).unwrap();
+        let mut names: Vec<_> = help
+            .lines()
+            .skip_while(|line| !line.starts_with("SUBCOMMANDS:"))
+            .skip(1)
+            .filter_map(|line| {
+                // The names are indented and the descriptions are indented more.
+                let name = line.strip_prefix("    ")?;
+                if name.starts_with(char::is_whitespace) {
+                    None
+                } else {
+                    name.split_whitespace().next()
+                }
+            })
+            .filter(|name| *name != "help")
+            .collect();
+        names.sort_unstable();
+        let mut expected = COMMAND_NAMES.to_vec();
+        expected.sort_unstable();
+        assert_eq!(names, expected);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let helper = helper();
+        let commands = helper.candidates(&[], 0);
+        assert!(commands.contains(&"send_joints".to_owned()));
+        assert!(commands.contains(&"help".to_owned()));
+        assert!(commands.contains(&"exit".to_owned()));
+        assert_eq!(helper.candidates(&["send_joints"], 1), vec!["arm"]);
+        assert_eq!(
+            helper.candidates(&["send_joints_pose", "arm"], 2),
+            vec!["home"]
+        );
+        assert_eq!(helper.candidates(&["speak"], 1), vec!["voice"]);
+        assert!(helper.candidates(&["send_joints", "arm"], 2).is_empty());
+        assert!(helper.candidates(&["unknown"], 1).is_empty());
+    }
+
+    #[test]
+    fn test_complete() {
+        let helper = helper();
+        assert_eq!(
+            complete(&helper, "send_joints_p"),
+            (0, vec!["send_joints_pose".to_owned()])
+        );
+        assert_eq!(
+            complete(&helper, "get_"),
+            (
+                0,
+                vec![
+                    "get_navigation_current_pose".to_owned(),
+                    "get_navigation_status".to_owned(),
+                    "get_state".to_owned(),
+                    "get_status".to_owned(),
+                ]
+            )
+        );
+        assert_eq!(
+            complete(&helper, "send_joints_pose "),
+            (17, vec!["arm".to_owned()])
+        );
+        assert_eq!(
+            complete(&helper, "send_joints_pose arm h"),
+            (21, vec!["home".to_owned()])
+        );
+        assert_eq!(complete(&helper, "speak v"), (6, vec!["voice".to_owned()]));
+        assert_eq!(complete(&helper, "speak voice "), (12, vec![]));
+    }
+}
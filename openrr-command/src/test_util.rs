@@ -0,0 +1,50 @@
+use arci::{
+    DummyJointTrajectoryClient, DummyLocalization, DummyMoveBase, DummyNavigation, DummySpeaker,
+    JointTrajectoryClient, Localization, MoveBase, Navigation, Speaker,
+};
+use openrr_client::{BoxRobotClient, JointsPose, OpenrrClientsConfig, RobotClient};
+use std::{collections::HashMap, sync::Arc};
+
+/// Robot client with the six-joint "arm" client, the "home" pose and the "voice" speaker.
+pub(crate) fn robot_client(speaker: Arc<DummySpeaker>) -> Arc<BoxRobotClient> {
+    let mut clients = HashMap::new();
+    clients.insert(
+        "arm".to_owned(),
+        Arc::new(DummyJointTrajectoryClient::new(
+            vec![
+                "l_shoulder_yaw",
+                "l_shoulder_pitch",
+                "l_shoulder_roll",
+                "l_elbow_pitch",
+                "l_wrist_yaw",
+                "l_wrist_pitch",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        )) as Arc<dyn JointTrajectoryClient>,
+    );
+    let mut speakers = HashMap::new();
+    speakers.insert("voice".to_owned(), speaker as Arc<dyn Speaker>);
+    // The sample URDF of openrr-planner.
+    let mut config = OpenrrClientsConfig::default();
+    config.urdf_path = Some("../openrr-planner/sample.urdf".to_owned());
+    config.resolve_path("Cargo.toml").unwrap();
+    config.joints_poses.push(JointsPose {
+        pose_name: "home".to_owned(),
+        client_name: "arm".to_owned(),
+        positions: vec![0.0; 6],
+    });
+    Arc::new(
+        RobotClient::try_new(
+            config,
+            clients,
+            speakers,
+            Some(Box::new(DummyLocalization::new()) as Box<dyn Localization>),
+            Some(Box::new(DummyMoveBase::new()) as Box<dyn MoveBase>),
+            Some(Box::new(DummyNavigation::new()) as Box<dyn Navigation>),
+            None,
+        )
+        .unwrap(),
+    )
+}
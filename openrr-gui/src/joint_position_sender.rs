@@ -8,7 +8,7 @@ use iced::{
 use iced::{pick_list, text_input};
 use openrr_client::RobotClient;
 use rand::Rng;
-use tracing::{debug, debug_span, error, warn, Instrument};
+use tracing::{debug, debug_span, error, info, warn, Instrument};
 use urdf_rs::JointType;
 
 use crate::{style, Error};
@@ -96,7 +96,7 @@ where
     let settings = Settings {
         flags: Some(gui),
         window: window::Settings {
-            size: (400, 550),
+            size: (400, 620),
             ..window::Settings::default()
         },
         ..Settings::default()
@@ -133,6 +133,10 @@ where
     duration_input: String,
     duration_input_state: text_input::State,
 
+    pose_name_input: String,
+    pose_name_input_state: text_input::State,
+    save_pose_button: button::State,
+
     errors: Errors,
 }
 
@@ -156,6 +160,7 @@ impl Errors {
         }
 
         match message {
+            Message::PoseNameTextInputChanged(..) => false,
             Message::DurationTextInputChanged(..) if self.duration_input.is_some() => false,
             Message::SliderChanged { index, .. }
             | Message::SliderTextInputChanged { index, .. }
@@ -221,6 +226,9 @@ where
             duration: Duration::from_secs_f64(0.1),
             duration_input: "0.1".into(),
             duration_input_state: Default::default(),
+            pose_name_input: String::new(),
+            pose_name_input_state: Default::default(),
+            save_pose_button: Default::default(),
             errors: Default::default(),
         }
     }
@@ -248,6 +256,8 @@ enum Message {
     SliderTextInputChanged { index: usize, position: String },
     DurationTextInputChanged(String),
     PickListChanged(String),
+    PoseNameTextInputChanged(String),
+    SavePoseButtonPressed,
 }
 
 impl<L, M, N> Application for JointPositionSender<L, M, N>
@@ -284,6 +294,24 @@ where
 
         match message {
             Message::Ignore => return Command::none(),
+            Message::PoseNameTextInputChanged(pose_name) => {
+                self.pose_name_input = pose_name;
+                return Command::none();
+            }
+            Message::SavePoseButtonPressed => {
+                let pose_name = self.pose_name_input.trim();
+                let joint_positions = self.current_joint_positions();
+                match self.robot_client.save_joints_pose(
+                    &self.current_joint_trajectory_client,
+                    pose_name,
+                    joint_positions.clone(),
+                ) {
+                    Ok(()) => info!(?pose_name, ?joint_positions, "saved pose"),
+                    // TODO: Decide how to handle errors.
+                    Err(e) => error!("{}", e),
+                }
+                return Command::none();
+            }
             Message::PickListChanged(client_name) => {
                 drop(_guard);
                 let span = debug_span(&client_name);
@@ -559,6 +587,30 @@ where
                 }),
             );
 
+        let mut save_pose_button = Button::new(
+            &mut self.save_pose_button,
+            Text::new("Save pose").horizontal_alignment(HorizontalAlignment::Center),
+        )
+        .padding(5)
+        .style(THEME);
+        // Disabled until the pose name is entered.
+        if !self.pose_name_input.trim().is_empty() {
+            save_pose_button = save_pose_button.on_press(Message::SavePoseButtonPressed);
+        }
+        let save_pose = Row::new()
+            .spacing(10)
+            .padding(5)
+            .push(
+                TextInput::new(
+                    &mut self.pose_name_input_state,
+                    "Pose name",
+                    &self.pose_name_input,
+                    Message::PoseNameTextInputChanged,
+                )
+                .style(style::TextInput::Default),
+            )
+            .push(save_pose_button);
+
         let mut content = Column::new().spacing(20).padding(20).max_width(400);
         if let Some(pick_list) = pick_list {
             content = content.push(pick_list);
@@ -568,6 +620,7 @@ where
             .push(zero_button)
             .push(sliders)
             .push(duration)
+            .push(save_pose)
             .height(Length::Fill);
 
         if self.errors.is_none() {
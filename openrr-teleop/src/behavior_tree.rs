@@ -0,0 +1,188 @@
+use crate::{BehaviorTreeBindings, Bindings, ControlNode, ControlNodeStatus, Error};
+use arci::{gamepad::GamepadEvent, Speaker};
+use async_trait::async_trait;
+use openrr_behavior_tree::{BehaviorTree, BehaviorTreeConfig, NodeStatus};
+use openrr_client::ArcRobotClient;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tracing::{error, info};
+
+/// Runs behavior trees by the gamepad.
+///
+/// `next_tree` selects the tree, `start` with `enable` starts it, and `cancel` or releasing
+/// `enable` cancels it (see `BehaviorTreeBindings`).
+pub struct BehaviorTreeNode<S>
+where
+    S: Speaker + Clone + 'static,
+{
+    mode: String,
+    submode: String,
+    trees: Vec<(String, Arc<BehaviorTree>)>,
+    tree_index: usize,
+    speaker: S,
+    is_trigger_holding: bool,
+    bindings: BehaviorTreeBindings,
+}
+
+impl<S> BehaviorTreeNode<S>
+where
+    S: Speaker + Clone + 'static,
+{
+    /// Returns `Error::NoBehaviorTree` if `trees` is empty.
+    pub fn new(
+        mode: String,
+        trees: Vec<(String, Arc<BehaviorTree>)>,
+        speaker: S,
+    ) -> Result<Self, Error> {
+        if trees.is_empty() {
+            return Err(Error::NoBehaviorTree(mode));
+        }
+        Ok(Self {
+            mode,
+            submode: format!(" {}", trees[0].0),
+            trees,
+            tree_index: 0,
+            speaker,
+            is_trigger_holding: false,
+            bindings: BehaviorTreeBindings::default(),
+        })
+    }
+
+    pub fn new_from_config(
+        config: BehaviorTreeNodeConfig,
+        client: Arc<ArcRobotClient>,
+        speaker: S,
+    ) -> Result<Self, Error> {
+        let mut trees = vec![];
+        for tree_config in config.trees {
+            let tree = BehaviorTree::new_from_config(
+                BehaviorTreeConfig::try_new(&tree_config.path)?,
+                client.clone(),
+            )?;
+            trees.push((tree_config.name, Arc::new(tree)));
+        }
+        Ok(Self::new(config.mode, trees, speaker)?.with_bindings(config.bindings))
+    }
+
+    pub fn with_bindings(mut self, bindings: BehaviorTreeBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
+    fn current_tree(&self) -> &Arc<BehaviorTree> {
+        &self.trees[self.tree_index].1
+    }
+
+    fn is_running(&self) -> bool {
+        self.trees.iter().any(|(_, tree)| tree.is_running())
+    }
+
+    fn start(&self) {
+        if self.is_running() {
+            return;
+        }
+        let name = self.trees[self.tree_index].0.clone();
+        // The tree is running when `spawn` returns, so the following `cancel` is not missed.
+        let handle = match self.current_tree().spawn() {
+            Ok(handle) => handle,
+            Err(e) => {
+                error!("Failed to start {}: {}", name, e);
+                return;
+            }
+        };
+        let speaker = self.speaker.clone();
+        speaker.speak(&format!("start {}", name));
+        tokio::spawn(async move {
+            match handle.await {
+                Ok(Ok(NodeStatus::Success)) => speaker.speak(&format!("{} succeeded", name)),
+                Ok(Ok(NodeStatus::Failure)) => speaker.speak(&format!("{} failed", name)),
+                Ok(Err(openrr_behavior_tree::Error::Canceled)) => {
+                    speaker.speak(&format!("{} canceled", name))
+                }
+                // Errors of the tree or the task.
+                result => {
+                    error!("Failed to run {}: {:?}", name, result);
+                    speaker.speak(&format!("{} failed", name));
+                }
+            }
+        });
+    }
+
+    fn cancel(&self) {
+        for (name, tree) in &self.trees {
+            if tree.is_running() {
+                info!("Cancel {}", name);
+                tree.cancel();
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S> ControlNode for BehaviorTreeNode<S>
+where
+    S: Speaker + Clone + 'static,
+{
+    fn set_event(&mut self, event: GamepadEvent) {
+        let bindings = &self.bindings;
+        match event {
+            GamepadEvent::ButtonPressed(b) if b == bindings.next_tree && !self.is_running() => {
+                self.tree_index = (self.tree_index + 1) % self.trees.len();
+                self.submode = format!(" {}", self.trees[self.tree_index].0);
+                self.speaker
+                    .speak(&format!("{}{}", self.mode, self.submode()));
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
+                self.is_trigger_holding = true;
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
+                self.is_trigger_holding = false;
+                self.cancel();
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.start && self.is_trigger_holding => {
+                self.start();
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.cancel => {
+                self.cancel();
+            }
+            _ => {}
+        }
+    }
+    async fn proc(&self) {}
+    fn mode(&self) -> &str {
+        &self.mode
+    }
+    fn submode(&self) -> &str {
+        &self.submode
+    }
+    async fn stop(&mut self) {
+        self.is_trigger_holding = false;
+        self.cancel();
+    }
+    fn status(&self) -> ControlNodeStatus {
+        ControlNodeStatus {
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorTreeNodeConfig {
+    #[serde(default = "default_mode")]
+    pub mode: String,
+    pub trees: Vec<BehaviorTreeFileConfig>,
+    #[serde(default)]
+    pub bindings: BehaviorTreeBindings,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BehaviorTreeFileConfig {
+    pub name: String,
+    /// Path to the tree definition. Relative to the teleop config if loaded by openrr-apps.
+    pub path: PathBuf,
+}
+
+fn default_mode() -> String {
+    "task".to_string()
+}
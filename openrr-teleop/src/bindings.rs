@@ -0,0 +1,357 @@
+use crate::Error;
+use arci::gamepad::{Axis, Button};
+use serde::{Deserialize, Serialize};
+
+/// Gamepad buttons and axes used by a control node.
+pub trait Bindings {
+    /// Pairs of the action name and the button.
+    fn buttons(&self) -> Vec<(&'static str, Button)>;
+    /// Pairs of the action name and the axis.
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![]
+    }
+    /// Pairs of the button or axis name and the action name, for showing to the operator.
+    fn legend(&self) -> Vec<(String, String)> {
+        let buttons = self
+            .buttons()
+            .into_iter()
+            .map(|(action, button)| (format!("{:?}", button), action.to_owned()));
+        let axes = self
+            .axes()
+            .into_iter()
+            .map(|(action, axis)| (format!("{:?}", axis), action.to_owned()));
+        buttons.chain(axes).collect()
+    }
+
+    /// Checks that each button and axis is bound to one action and that no `reserved`
+    /// button (e.g. the mode switch button) is used.
+    fn validate(&self, node_name: &str, reserved: &[(&str, Button)]) -> Result<(), Error> {
+        let buttons = self.buttons();
+        for (i, (action, button)) in buttons.iter().enumerate() {
+            if let Some((other, _)) = buttons[..i]
+                .iter()
+                .chain(reserved)
+                .find(|(_, b)| b == button)
+            {
+                return Err(Error::ConflictingBindings(
+                    node_name.to_owned(),
+                    format!("{:?} is bound to both {} and {}", button, other, action),
+                ));
+            }
+        }
+        let axes = self.axes();
+        for (i, (action, axis)) in axes.iter().enumerate() {
+            if let Some((other, _)) = axes[..i].iter().find(|(_, a)| a == axis) {
+                return Err(Error::ConflictingBindings(
+                    node_name.to_owned(),
+                    format!("{:?} is bound to both {} and {}", axis, other, action),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bindings of `MoveBaseNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MoveBaseBindings {
+    /// Deadman button. The base moves only while this is pressed.
+    pub enable: Button,
+    pub turbo: Button,
+    pub linear_x: Axis,
+    pub linear_y: Axis,
+    pub angular_z: Axis,
+}
+
+impl Default for MoveBaseBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            turbo: Button::LeftTrigger2,
+            linear_x: Axis::LeftStickY,
+            linear_y: Axis::LeftStickX,
+            angular_z: Axis::RightStickX,
+        }
+    }
+}
+
+impl Bindings for MoveBaseBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![("enable", self.enable), ("turbo", self.turbo)]
+    }
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![
+            ("linear_x", self.linear_x),
+            ("linear_y", self.linear_y),
+            ("angular_z", self.angular_z),
+        ]
+    }
+}
+
+/// Bindings of `IkNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IkNodeBindings {
+    /// Deadman button. The arm moves only while this is pressed.
+    pub enable: Button,
+    pub turbo: Button,
+    pub switch_tool: Button,
+    pub up: Button,
+    pub down: Button,
+    pub yaw_left: Button,
+    pub yaw_right: Button,
+    pub x: Axis,
+    pub y: Axis,
+    pub roll: Axis,
+    pub pitch: Axis,
+    /// Axes of 6DoF devices, which move the end effector in the tool frame.
+    pub linear_x: Axis,
+    pub linear_y: Axis,
+    pub linear_z: Axis,
+    pub angular_x: Axis,
+    pub angular_y: Axis,
+    pub angular_z: Axis,
+}
+
+impl Default for IkNodeBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            turbo: Button::LeftTrigger2,
+            switch_tool: Button::East,
+            up: Button::West,
+            down: Button::South,
+            yaw_left: Button::DPadLeft,
+            yaw_right: Button::DPadRight,
+            x: Axis::RightStickY,
+            y: Axis::RightStickX,
+            roll: Axis::LeftStickX,
+            pitch: Axis::LeftStickY,
+            linear_x: Axis::LinearX,
+            linear_y: Axis::LinearY,
+            linear_z: Axis::LinearZ,
+            angular_x: Axis::AngularX,
+            angular_y: Axis::AngularY,
+            angular_z: Axis::AngularZ,
+        }
+    }
+}
+
+impl Bindings for IkNodeBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("turbo", self.turbo),
+            ("switch_tool", self.switch_tool),
+            ("up", self.up),
+            ("down", self.down),
+            ("yaw_left", self.yaw_left),
+            ("yaw_right", self.yaw_right),
+        ]
+    }
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![
+            ("x", self.x),
+            ("y", self.y),
+            ("roll", self.roll),
+            ("pitch", self.pitch),
+            ("linear_x", self.linear_x),
+            ("linear_y", self.linear_y),
+            ("linear_z", self.linear_z),
+            ("angular_x", self.angular_x),
+            ("angular_y", self.angular_y),
+            ("angular_z", self.angular_z),
+        ]
+    }
+}
+
+/// Bindings of `JoyJointTeleopNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct JoyJointTeleopBindings {
+    /// Deadman button. The joint moves only while this is pressed.
+    pub enable: Button,
+    pub turbo: Button,
+    pub next_joint: Button,
+    pub increase: Button,
+    pub decrease: Button,
+    pub save_pose: Button,
+    pub velocity: Axis,
+}
+
+impl Default for JoyJointTeleopBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            turbo: Button::LeftTrigger2,
+            next_joint: Button::East,
+            increase: Button::West,
+            decrease: Button::South,
+            save_pose: Button::DPadUp,
+            velocity: Axis::RightStickY,
+        }
+    }
+}
+
+impl Bindings for JoyJointTeleopBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("turbo", self.turbo),
+            ("next_joint", self.next_joint),
+            ("increase", self.increase),
+            ("decrease", self.decrease),
+            ("save_pose", self.save_pose),
+        ]
+    }
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![("velocity", self.velocity)]
+    }
+}
+
+/// Bindings of `JointsPoseSender`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct JointsPoseSenderBindings {
+    /// Deadman button. The pose is sent only while this and `send` are pressed.
+    pub enable: Button,
+    pub send: Button,
+    pub next_pose: Button,
+    pub save_pose: Button,
+}
+
+impl Default for JointsPoseSenderBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            send: Button::West,
+            next_pose: Button::East,
+            save_pose: Button::DPadUp,
+        }
+    }
+}
+
+impl Bindings for JointsPoseSenderBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("send", self.send),
+            ("next_pose", self.next_pose),
+            ("save_pose", self.save_pose),
+        ]
+    }
+}
+
+/// Bindings of `BehaviorTreeNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct BehaviorTreeBindings {
+    /// Deadman button. Releasing this cancels the tree.
+    pub enable: Button,
+    pub start: Button,
+    pub cancel: Button,
+    pub next_tree: Button,
+}
+
+impl Default for BehaviorTreeBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            start: Button::West,
+            cancel: Button::South,
+            next_tree: Button::East,
+        }
+    }
+}
+
+impl Bindings for BehaviorTreeBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("start", self.start),
+            ("cancel", self.cancel),
+            ("next_tree", self.next_tree),
+        ]
+    }
+}
+
+/// Bindings of `WholeBodyNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WholeBodyBindings {
+    /// Deadman button. The arm and the base move only while this is pressed.
+    pub enable: Button,
+    pub turbo: Button,
+    pub up: Button,
+    pub down: Button,
+    pub x: Axis,
+    pub y: Axis,
+    pub angular_z: Axis,
+}
+
+impl Default for WholeBodyBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            turbo: Button::LeftTrigger2,
+            up: Button::West,
+            down: Button::South,
+            x: Axis::LeftStickY,
+            y: Axis::LeftStickX,
+            angular_z: Axis::RightStickX,
+        }
+    }
+}
+
+impl Bindings for WholeBodyBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("turbo", self.turbo),
+            ("up", self.up),
+            ("down", self.down),
+        ]
+    }
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![("x", self.x), ("y", self.y), ("angular_z", self.angular_z)]
+    }
+}
+
+/// Bindings of `TrajectoryPlaybackNode`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TrajectoryPlaybackBindings {
+    /// Deadman button. The joints move only while this is pressed.
+    pub enable: Button,
+    pub play: Button,
+    pub step_forward: Button,
+    pub step_backward: Button,
+    pub scrub: Axis,
+}
+
+impl Default for TrajectoryPlaybackBindings {
+    fn default() -> Self {
+        Self {
+            enable: Button::RightTrigger2,
+            play: Button::South,
+            step_forward: Button::DPadRight,
+            step_backward: Button::DPadLeft,
+            scrub: Axis::RightStickX,
+        }
+    }
+}
+
+impl Bindings for TrajectoryPlaybackBindings {
+    fn buttons(&self) -> Vec<(&'static str, Button)> {
+        vec![
+            ("enable", self.enable),
+            ("play", self.play),
+            ("step_forward", self.step_forward),
+            ("step_backward", self.step_backward),
+        ]
+    }
+    fn axes(&self) -> Vec<(&'static str, Axis)> {
+        vec![("scrub", self.scrub)]
+    }
+}
@@ -1,6 +1,48 @@
 use arci::gamepad::GamepadEvent;
+use arci::{BaseVelocity, JointTrajectoryClient};
 use async_trait::async_trait;
 use auto_impl::auto_impl;
+use k::Isometry3;
+use std::{sync::Mutex, time::Duration};
+use tracing::{debug, error};
+
+/// Status of a control node for showing to the operator.
+#[derive(Debug, Clone, Default)]
+pub struct ControlNodeStatus {
+    /// Names and current positions of the joints of the active client.
+    pub joints: Vec<(String, f64)>,
+    /// Commanded velocity of the base.
+    pub base_velocity: Option<BaseVelocity>,
+    /// Current pose of the IK target in the root frame of the IK chain.
+    pub ik_target: Option<Isometry3<f64>>,
+    /// Pairs of the button or axis name and the action bound to it.
+    pub legend: Vec<(String, String)>,
+}
+
+/// Vibration of the gamepad requested by a control node (see `ControlNode::take_rumble`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rumble {
+    /// 0.0 to 1.0
+    pub intensity: f64,
+    pub duration: Duration,
+}
+
+impl Rumble {
+    pub fn new(intensity: f64, duration: Duration) -> Self {
+        Self {
+            intensity,
+            duration,
+        }
+    }
+    /// Strong rumble to notify a failure, e.g. the IK failed or the command was rejected.
+    pub fn failure() -> Self {
+        Self::new(1.0, Duration::from_millis(200))
+    }
+    /// Weak rumble to notify the mode switch.
+    pub fn mode_switch() -> Self {
+        Self::new(0.3, Duration::from_millis(100))
+    }
+}
 
 #[async_trait]
 #[auto_impl(Box)]
@@ -9,4 +51,63 @@ pub trait ControlNode: Send + Sync {
     async fn proc(&self);
     fn mode(&self) -> &str;
     fn submode(&self) -> &str;
+    /// Stops the ongoing motion and releases the pressed buttons and axes.
+    ///
+    /// This is called when the mode is switched to another node, the gamepad is disconnected
+    /// or silent for the deadman timeout, and the teleop is shut down.
+    async fn stop(&mut self) {}
+    fn status(&self) -> ControlNodeStatus {
+        ControlNodeStatus::default()
+    }
+    /// Takes the rumble requested since the last call. `ControlNodeSwitcher` calls this after
+    /// `proc` and vibrates the gamepad.
+    fn take_rumble(&self) -> Option<Rumble> {
+        None
+    }
+}
+
+/// Holds the latest rumble requested in `ControlNode::proc`.
+#[derive(Debug, Default)]
+pub(crate) struct RumbleRequest(Mutex<Option<Rumble>>);
+
+impl RumbleRequest {
+    pub(crate) fn request(&self, rumble: Rumble) {
+        *self.0.lock().unwrap() = Some(rumble);
+    }
+    pub(crate) fn take(&self) -> Option<Rumble> {
+        self.0.lock().unwrap().take()
+    }
+}
+
+/// Names and current positions of the joints, or empty if the positions are not available.
+pub(crate) fn joint_states<J>(client: &J) -> Vec<(String, f64)>
+where
+    J: JointTrajectoryClient,
+{
+    match client.current_joint_positions() {
+        Ok(positions) => client
+            .joint_names()
+            .iter()
+            .cloned()
+            .zip(positions)
+            .collect(),
+        Err(e) => {
+            debug!("Failed to get the joint positions: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Halts the joints at the current positions.
+pub(crate) async fn stop_joint_trajectory_client<J>(client: &J, duration: Duration)
+where
+    J: JointTrajectoryClient,
+{
+    let result = match client.current_joint_positions() {
+        Ok(positions) => client.send_joint_positions(positions, duration).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        error!("Failed to stop the joints: {}", e);
+    }
 }
@@ -1,9 +1,10 @@
 use crate::{
-    ControlNode, IkNode, IkNodeConfig, JointsPoseSender, JointsPoseSenderConfig,
-    JoyJointTeleopNode, JoyJointTeleopNodeConfig, MoveBaseNode,
+    BehaviorTreeNodeConfig, Bindings, ControlNode, Error, IkNode, IkNodeConfig, JointsPoseSender,
+    JointsPoseSenderConfig, JoyJointTeleopNode, JoyJointTeleopNodeConfig, MoveBaseNode,
+    MoveBaseNodeConfig, TrajectoryPlaybackNodeConfig, WholeBodyNode, WholeBodyNodeConfig,
 };
-use arci::{JointTrajectoryClient, MoveBase, Speaker};
-use openrr_client::{IkSolverWithChain, JointsPose};
+use arci::{gamepad::Button, JointTrajectoryClient, MoveBase, Speaker};
+use openrr_client::{ArcRobotClient, IkSolverWithChain, OpenrrClientsConfig};
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 
@@ -20,65 +21,178 @@ pub struct IkNodeTeleopConfig {
     pub joint_trajectory_client_name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WholeBodyNodeTeleopConfig {
+    pub config: WholeBodyNodeConfig,
+    pub solver_name: String,
+    pub joint_trajectory_client_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrajectoryPlaybackTeleopConfig {
+    pub config: TrajectoryPlaybackNodeConfig,
+    pub joint_trajectory_client_name: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ControlNodesConfig {
     pub joy_joint_teleop_configs: Vec<JoyJointTeleopConfig>,
+    /// Same as `move_base_node_config` with the default gains and bindings.
     pub move_base_mode: Option<String>,
+    pub move_base_node_config: Option<MoveBaseNodeConfig>,
     pub ik_node_teleop_configs: Vec<IkNodeTeleopConfig>,
+    /// Requires the move base client.
+    #[serde(default)]
+    pub whole_body_node_teleop_configs: Vec<WholeBodyNodeTeleopConfig>,
     pub joints_pose_sender_config: Option<JointsPoseSenderConfig>,
+    /// Used by `BehaviorTreeNode`, which is created from the robot client by the application.
+    pub behavior_tree_node_config: Option<BehaviorTreeNodeConfig>,
+    /// Used by `TrajectoryPlaybackNode`, which is created by the application to load the records.
+    #[serde(default)]
+    pub trajectory_playback_teleop_configs: Vec<TrajectoryPlaybackTeleopConfig>,
 }
 
 impl ControlNodesConfig {
+    fn move_base_node_config(&self) -> Option<MoveBaseNodeConfig> {
+        self.move_base_node_config.clone().or_else(|| {
+            self.move_base_mode.as_ref().map(|mode| MoveBaseNodeConfig {
+                mode: mode.clone(),
+                ..Default::default()
+            })
+        })
+    }
+
+    /// Checks that each node binds a button or an axis to one action and does not use
+    /// `mode_switch_button` of `ControlNodeSwitcher`.
+    pub fn validate(&self, mode_switch_button: Button) -> Result<(), Error> {
+        let reserved = [("mode switch", mode_switch_button)];
+        for config in &self.joy_joint_teleop_configs {
+            config
+                .config
+                .bindings
+                .validate(&config.config.mode, &reserved)?;
+        }
+        if let Some(config) = self.move_base_node_config() {
+            config.bindings.validate(&config.mode, &reserved)?;
+        }
+        for config in &self.ik_node_teleop_configs {
+            config
+                .config
+                .bindings
+                .validate(&config.config.mode, &reserved)?;
+        }
+        for config in &self.whole_body_node_teleop_configs {
+            config
+                .config
+                .bindings
+                .validate(&config.config.mode, &reserved)?;
+        }
+        if let Some(config) = &self.joints_pose_sender_config {
+            config.bindings.validate(&config.mode, &reserved)?;
+        }
+        if let Some(config) = &self.behavior_tree_node_config {
+            config.bindings.validate(&config.mode, &reserved)?;
+        }
+        for config in &self.trajectory_playback_teleop_configs {
+            config
+                .config
+                .bindings
+                .validate(&config.config.mode, &reserved)?;
+        }
+        Ok(())
+    }
+
+    /// `robot_client` is used to save the poses by `JoyJointTeleopNode` and `JointsPoseSender`.
     pub fn create_control_nodes(
         &self,
         speaker: Arc<dyn Speaker>,
         joint_trajectory_client_map: &HashMap<String, Arc<dyn JointTrajectoryClient>>,
         ik_solver_with_chain_map: &HashMap<String, Arc<IkSolverWithChain>>,
         move_base: Option<Arc<dyn MoveBase>>,
-        joints_poses: Vec<JointsPose>,
+        openrr_clients_config: &OpenrrClientsConfig,
+        robot_client: Option<Arc<ArcRobotClient>>,
     ) -> Vec<Box<dyn ControlNode>> {
         let mut nodes: Vec<Box<dyn ControlNode>> = vec![];
+        let joints_poses = openrr_clients_config.joints_poses.clone();
 
         for joy_joint_teleop_config in &self.joy_joint_teleop_configs {
-            nodes.push(Box::new(JoyJointTeleopNode::new_from_config(
+            let mut node = JoyJointTeleopNode::new_from_config(
                 joy_joint_teleop_config.config.clone(),
                 joint_trajectory_client_map[&joy_joint_teleop_config.client_name].clone(),
                 speaker.clone(),
-            )));
+            );
+            if let Some(robot_client) = &robot_client {
+                node = node.with_robot_client(
+                    joy_joint_teleop_config.client_name.clone(),
+                    robot_client.clone(),
+                );
+            }
+            nodes.push(Box::new(node));
         }
 
-        if let Some(mode) = &self.move_base_mode {
-            if let Some(m) = move_base {
-                nodes.push(Box::new(MoveBaseNode::new(mode.clone(), m.clone())));
+        if let Some(config) = self.move_base_node_config() {
+            if let Some(m) = &move_base {
+                nodes.push(Box::new(MoveBaseNode::new_from_config(config, m.clone())));
             }
         }
 
         for ik_node_teleop_config in &self.ik_node_teleop_configs {
+            let ik_solver_with_chain =
+                ik_solver_with_chain_map[&ik_node_teleop_config.solver_name].clone();
+            // Tools attached to other arms are ignored.
+            let tools = openrr_clients_config
+                .tool_frames_configs
+                .iter()
+                .filter_map(|config| ik_solver_with_chain.tool_frame(config))
+                .collect();
             let ik_node = IkNode::new_from_config(
                 ik_node_teleop_config.config.clone(),
                 joint_trajectory_client_map[&ik_node_teleop_config.joint_trajectory_client_name]
                     .clone(),
                 speaker.clone(),
-                ik_solver_with_chain_map[&ik_node_teleop_config.solver_name].clone(),
-            );
+                ik_solver_with_chain,
+            )
+            .with_tool_frames(tools);
             nodes.push(Box::new(ik_node));
         }
 
+        if let Some(m) = &move_base {
+            for whole_body_node_teleop_config in &self.whole_body_node_teleop_configs {
+                nodes.push(Box::new(WholeBodyNode::new_from_config(
+                    whole_body_node_teleop_config.config.clone(),
+                    joint_trajectory_client_map
+                        [&whole_body_node_teleop_config.joint_trajectory_client_name]
+                        .clone(),
+                    m.clone(),
+                    ik_solver_with_chain_map[&whole_body_node_teleop_config.solver_name].clone(),
+                )));
+            }
+        }
+
         if !joints_poses.is_empty() {
             if let Some(sender_config) = &self.joints_pose_sender_config {
-                let mut joint_trajectory_clients = HashMap::new();
+                // The poses of any client can be saved through the robot client.
+                let mut joint_trajectory_clients = if robot_client.is_some() {
+                    joint_trajectory_client_map.clone()
+                } else {
+                    HashMap::new()
+                };
                 for joints_pose in &joints_poses {
                     joint_trajectory_clients.insert(
                         joints_pose.client_name.to_owned(),
                         joint_trajectory_client_map[&joints_pose.client_name].clone(),
                     );
                 }
-                nodes.push(Box::new(JointsPoseSender::new_from_config(
+                let mut node = JointsPoseSender::new_from_config(
                     sender_config.clone(),
                     joints_poses,
                     joint_trajectory_clients,
                     speaker.clone(),
-                )));
+                );
+                if let Some(robot_client) = &robot_client {
+                    node = node.with_robot_client(robot_client.clone());
+                }
+                nodes.push(Box::new(node));
             }
         }
 
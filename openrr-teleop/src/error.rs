@@ -0,0 +1,19 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("openrr-teleop: Conflicting bindings in {} ({}).", .0, .1)]
+    ConflictingBindings(String, String),
+    #[error("openrr-teleop: No behavior tree is given to mode {}.", .0)]
+    NoBehaviorTree(String),
+    #[error("openrr-teleop: MismatchedLength {} != {}.", .0, .1)]
+    MismatchedLength(usize, usize),
+    #[error("openrr-teleop: No joint trajectory of {} is found in {:?}.", .0, .1)]
+    NoJointTrajectory(String, PathBuf),
+    #[error("openrr-teleop: openrr-behavior-tree: {:?}", .0)]
+    OpenrrBehaviorTree(#[from] openrr_behavior_tree::Error),
+    #[error("openrr-teleop: openrr-client: {:?}", .0)]
+    OpenrrClient(#[from] openrr_client::Error),
+}
@@ -1,11 +1,17 @@
-use super::control_node::ControlNode;
-use arci::gamepad::{Axis, Button, GamepadEvent};
+use super::control_node::{
+    joint_states, stop_joint_trajectory_client, ControlNode, ControlNodeStatus, Rumble,
+    RumbleRequest,
+};
+use crate::Bindings;
+use crate::IkNodeBindings;
+use arci::gamepad::GamepadEvent;
 use arci::{JointTrajectoryClient, Speaker};
 use async_trait::async_trait;
 use k::{Translation3, Vector3};
-use openrr_client::IkSolverWithChain;
+use openrr_client::{IkSolverWithChain, ServoConfig, ToolFrame, Twist};
 use serde::{Deserialize, Serialize};
 use std::{sync::Arc, time::Duration};
+use tracing::error;
 
 const IK_POSITION_TURBO_GAIN: f64 = 2.0;
 
@@ -24,8 +30,15 @@ where
     move_step_angular: [f64; 3],
     step_duration: Duration,
     ik_solver_with_chain: Arc<IkSolverWithChain>,
+    tools: Vec<ToolFrame>,
+    tool_index: Option<usize>,
     is_turbo: bool,
     is_sending: bool,
+    turbo_gain: f64,
+    /// Uses the Jacobian instead of solving the IK for each step if set.
+    servo_config: Option<ServoConfig>,
+    rumble: RumbleRequest,
+    bindings: IkNodeBindings,
 }
 
 impl<J, S> IkNode<J, S>
@@ -53,17 +66,28 @@ where
             move_step_angular,
             step_duration,
             ik_solver_with_chain,
+            tools: vec![],
+            tool_index: None,
             is_turbo: false,
             is_sending: false,
+            turbo_gain: IK_POSITION_TURBO_GAIN,
+            servo_config: None,
+            rumble: RumbleRequest::default(),
+            bindings: IkNodeBindings::default(),
         }
     }
+    /// Tools which can be switched by `IkNodeBindings::switch_tool`.
+    pub fn with_tool_frames(mut self, tools: Vec<ToolFrame>) -> Self {
+        self.tools = tools;
+        self
+    }
     pub fn new_from_config(
         config: IkNodeConfig,
         joint_trajectory_client: J,
         speaker: S,
         ik_solver_with_chain: Arc<IkSolverWithChain>,
     ) -> Self {
-        Self::new(
+        let mut node = Self::new(
             config.mode,
             joint_trajectory_client,
             config.move_step_linear,
@@ -71,7 +95,21 @@ where
             Duration::from_secs_f64(config.step_duration_secs),
             speaker,
             ik_solver_with_chain,
-        )
+        );
+        node.turbo_gain = config.turbo_gain;
+        node.servo_config = config.servo;
+        node.with_bindings(config.bindings)
+    }
+    /// Moves the arm with the velocity of `move_step_* / step_duration` using the Jacobian
+    /// (see `IkSolverWithChain::servo_trajectory_point`), which is smoother than solving the IK
+    /// for each step.
+    pub fn with_servo_config(mut self, servo_config: ServoConfig) -> Self {
+        self.servo_config = Some(servo_config);
+        self
+    }
+    pub fn with_bindings(mut self, bindings: IkNodeBindings) -> Self {
+        self.bindings = bindings;
+        self
     }
 }
 
@@ -80,6 +118,59 @@ where
     N: JointTrajectoryClient,
     S: Speaker,
 {
+    fn tool_offset(&self) -> k::Isometry3<f64> {
+        self.tool_index
+            .map_or_else(k::Isometry3::identity, |index| self.tools[index].offset)
+    }
+    fn switch_tool(&mut self) {
+        // IK target -> tools[0] -> ... -> IK target
+        self.tool_index = match self.tool_index {
+            None if !self.tools.is_empty() => Some(0),
+            Some(index) if index + 1 < self.tools.len() => Some(index + 1),
+            _ => None,
+        };
+        self.submode = self
+            .tool_index
+            .map_or_else(String::new, |index| self.tools[index].name.to_owned());
+        self.speaker
+            .speak(&format!("{}{}", self.mode, self.submode));
+    }
+    async fn servo(&self) {
+        let servo_config = self.servo_config.as_ref().unwrap();
+        let current_positions = match self.joint_trajectory_client.current_joint_positions() {
+            Ok(positions) => positions,
+            Err(e) => {
+                error!("Failed to get the joint positions: {}", e);
+                return;
+            }
+        };
+        self.ik_solver_with_chain
+            .set_joint_positions_clamped(&current_positions);
+        let tool_offset = self.tool_offset();
+        // The velocities are in the tool frame.
+        let rotation = (self.ik_solver_with_chain.end_transform() * tool_offset).rotation;
+        let gain = if self.is_turbo { self.turbo_gain } else { 1.0 };
+        let dt = self.step_duration.as_secs_f64();
+        let twist = Twist::new(
+            rotation * self.linear_velocity * gain / dt,
+            rotation * self.angular_velocity / dt,
+        );
+        let point = self.ik_solver_with_chain.servo_trajectory_point(
+            &current_positions,
+            &tool_offset,
+            &twist,
+            servo_config,
+            self.step_duration,
+        );
+        if let Err(e) = self
+            .joint_trajectory_client
+            .send_joint_trajectory(vec![point])
+            .await
+        {
+            error!("Failed to send the trajectory: {}", e);
+            self.rumble.request(Rumble::failure());
+        }
+    }
     fn clear_velocity(&mut self) {
         self.linear_velocity.x = 0.0;
         self.linear_velocity.y = 0.0;
@@ -97,68 +188,95 @@ where
     S: Speaker,
 {
     fn set_event(&mut self, event: GamepadEvent) {
+        let bindings = &self.bindings;
         match event {
-            GamepadEvent::ButtonPressed(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonPressed(b)
+                if b == bindings.switch_tool && !self.tools.is_empty() =>
+            {
+                self.switch_tool();
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.turbo => {
                 self.is_turbo = true;
             }
-            GamepadEvent::ButtonReleased(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.turbo => {
                 self.is_turbo = false;
             }
-            GamepadEvent::ButtonPressed(Button::RightTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
                 self.is_sending = true;
             }
-            GamepadEvent::ButtonReleased(Button::RightTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
                 self.is_sending = false;
                 self.clear_velocity();
             }
-            GamepadEvent::ButtonPressed(Button::South) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.down => {
                 self.linear_velocity.z = -self.move_step_linear[2];
             }
-            GamepadEvent::ButtonReleased(Button::South) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.down => {
                 self.linear_velocity.z = 0.0;
             }
-            GamepadEvent::ButtonPressed(Button::West) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.up => {
                 self.linear_velocity.z = self.move_step_linear[2];
             }
-            GamepadEvent::ButtonReleased(Button::West) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.up => {
                 self.linear_velocity.z = 0.0;
             }
-            GamepadEvent::AxisChanged(Axis::RightStickY, v) => {
+            GamepadEvent::AxisChanged(a, v) if a == bindings.x => {
                 self.linear_velocity.x = self.move_step_linear[0] * v;
             }
-            GamepadEvent::AxisChanged(Axis::RightStickX, v) => {
+            GamepadEvent::AxisChanged(a, v) if a == bindings.y => {
                 self.linear_velocity.y = self.move_step_linear[1] * v;
             }
-            GamepadEvent::AxisChanged(Axis::LeftStickX, v) => {
+            GamepadEvent::AxisChanged(a, v) if a == bindings.roll => {
                 self.angular_velocity.x = -self.move_step_angular[0] * v;
             }
-            GamepadEvent::AxisChanged(Axis::LeftStickY, v) => {
+            GamepadEvent::AxisChanged(a, v) if a == bindings.pitch => {
                 self.angular_velocity.y = self.move_step_angular[1] * v;
             }
-            GamepadEvent::ButtonPressed(Button::DPadRight) => {
+            GamepadEvent::AxisChanged(a, v) if a == bindings.linear_x => {
+                self.linear_velocity.x = self.move_step_linear[0] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.linear_y => {
+                self.linear_velocity.y = self.move_step_linear[1] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.linear_z => {
+                self.linear_velocity.z = self.move_step_linear[2] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.angular_x => {
+                self.angular_velocity.x = self.move_step_angular[0] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.angular_y => {
+                self.angular_velocity.y = self.move_step_angular[1] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.angular_z => {
+                self.angular_velocity.z = self.move_step_angular[2] * v;
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.yaw_right => {
                 self.angular_velocity.z = -self.move_step_angular[2];
             }
-            GamepadEvent::ButtonReleased(Button::DPadRight) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.yaw_right => {
                 self.angular_velocity.z = 0.0;
             }
-            GamepadEvent::ButtonPressed(Button::DPadLeft) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.yaw_left => {
                 self.angular_velocity.z = self.move_step_angular[2];
             }
-            GamepadEvent::ButtonReleased(Button::DPadLeft) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.yaw_left => {
                 self.angular_velocity.z = 0.0;
             }
             _ => {}
         }
     }
     async fn proc(&self) {
-        if self.is_sending {
+        if self.is_sending && self.servo_config.is_some() {
+            self.servo().await;
+        } else if self.is_sending {
             let current_positions = self
                 .joint_trajectory_client
                 .current_joint_positions()
                 .unwrap();
             self.ik_solver_with_chain
                 .set_joint_positions_clamped(&current_positions);
-            let current_pose = self.ik_solver_with_chain.end_transform();
+            let tool_offset = self.tool_offset();
+            let current_pose = self.ik_solver_with_chain.end_transform() * tool_offset;
             let rotated = current_pose
                 * k::UnitQuaternion::from_euler_angles(
                     self.angular_velocity.x,
@@ -167,21 +285,25 @@ where
                 );
             let target_pose = rotated
                 * Translation3::from(
-                    self.linear_velocity
-                        * if self.is_turbo {
-                            IK_POSITION_TURBO_GAIN
-                        } else {
-                            1.0
-                        },
+                    self.linear_velocity * if self.is_turbo { self.turbo_gain } else { 1.0 },
                 );
-            if self.ik_solver_with_chain.solve(&target_pose).is_ok() {
+            if self
+                .ik_solver_with_chain
+                .solve(&(target_pose * tool_offset.inverse()))
+                .is_ok()
+            {
                 let pos = self.ik_solver_with_chain.joint_positions();
-                self.joint_trajectory_client
+                if let Err(e) = self
+                    .joint_trajectory_client
                     .send_joint_positions(pos, self.step_duration)
                     .await
-                    .unwrap();
+                {
+                    error!("Failed to send the joint positions: {}", e);
+                    self.rumble.request(Rumble::failure());
+                }
             } else {
                 self.speaker.speak("ik fail");
+                self.rumble.request(Rumble::failure());
             }
         }
     }
@@ -191,6 +313,32 @@ where
     fn submode(&self) -> &str {
         &self.submode
     }
+    async fn stop(&mut self) {
+        self.is_sending = false;
+        self.is_turbo = false;
+        self.clear_velocity();
+        stop_joint_trajectory_client(&self.joint_trajectory_client, self.step_duration).await;
+    }
+    fn take_rumble(&self) -> Option<Rumble> {
+        self.rumble.take()
+    }
+    fn status(&self) -> ControlNodeStatus {
+        let joints = joint_states(&self.joint_trajectory_client);
+        let positions: Vec<_> = joints.iter().map(|(_, position)| *position).collect();
+        let ik_target = if positions.is_empty() {
+            None
+        } else {
+            self.ik_solver_with_chain
+                .set_joint_positions_clamped(&positions);
+            Some(self.ik_solver_with_chain.end_transform() * self.tool_offset())
+        };
+        ControlNodeStatus {
+            joints,
+            ik_target,
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -202,6 +350,12 @@ pub struct IkNodeConfig {
     pub move_step_linear: [f64; 3],
     #[serde(default = "default_step_duration_secs")]
     pub step_duration_secs: f64,
+    #[serde(default = "default_turbo_gain")]
+    pub turbo_gain: f64,
+    /// Uses the Jacobian based velocity control if set, e.g. `servo = {}`.
+    pub servo: Option<ServoConfig>,
+    #[serde(default)]
+    pub bindings: IkNodeBindings,
 }
 
 fn default_move_step_angular() -> [f64; 3] {
@@ -215,3 +369,7 @@ fn default_move_step_linear() -> [f64; 3] {
 fn default_step_duration_secs() -> f64 {
     0.1
 }
+
+fn default_turbo_gain() -> f64 {
+    IK_POSITION_TURBO_GAIN
+}
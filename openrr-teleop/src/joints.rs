@@ -1,9 +1,15 @@
-use super::control_node::ControlNode;
-use arci::gamepad::{Axis, Button, GamepadEvent};
+use super::control_node::{
+    joint_states, stop_joint_trajectory_client, ControlNode, ControlNodeStatus, Rumble,
+    RumbleRequest,
+};
+use crate::{Bindings, JoyJointTeleopBindings};
+use arci::gamepad::GamepadEvent;
 use arci::{JointTrajectoryClient, Speaker};
 use async_trait::async_trait;
+use openrr_client::ArcRobotClient;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
+use tracing::error;
 
 const AXIS_GAIN: f64 = 2.0;
 const JOINT_POSITION_TURBO_GAIN: f64 = 2.0;
@@ -24,6 +30,12 @@ where
     joint_index: usize,
     is_turbo: bool,
     is_sending: bool,
+    /// Client name and robot client to save the poses.
+    pose_saving: Option<(String, Arc<ArcRobotClient>)>,
+    axis_gain: f64,
+    turbo_gain: f64,
+    rumble: RumbleRequest,
+    bindings: JoyJointTeleopBindings,
 }
 
 impl<J, S> JoyJointTeleopNode<J, S>
@@ -51,6 +63,11 @@ where
             joint_index: 0,
             is_turbo: false,
             is_sending: false,
+            pose_saving: None,
+            axis_gain: AXIS_GAIN,
+            turbo_gain: JOINT_POSITION_TURBO_GAIN,
+            rumble: RumbleRequest::default(),
+            bindings: JoyJointTeleopBindings::default(),
         }
     }
     pub fn new_from_config(
@@ -58,13 +75,59 @@ where
         joint_trajectory_client: J,
         speaker: S,
     ) -> Self {
-        Self::new(
+        let mut node = Self::new(
             config.mode,
             joint_trajectory_client,
             config.joint_step,
             Duration::from_secs_f64(config.step_duration_secs),
             speaker,
-        )
+        );
+        node.axis_gain = config.axis_gain;
+        node.turbo_gain = config.turbo_gain;
+        node.with_bindings(config.bindings)
+    }
+    pub fn with_bindings(mut self, bindings: JoyJointTeleopBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+    /// Enables saving the current joint positions as a new pose by
+    /// `JoyJointTeleopBindings::save_pose` while holding `JoyJointTeleopBindings::enable`.
+    ///
+    /// The pose is named `saved_<number>` and saved as a pose of `client_name` by
+    /// `RobotClient::save_joints_pose`.
+    pub fn with_robot_client(
+        mut self,
+        client_name: String,
+        robot_client: Arc<ArcRobotClient>,
+    ) -> Self {
+        self.pose_saving = Some((client_name, robot_client));
+        self
+    }
+    fn save_current_pose(&self) {
+        let (client_name, robot_client) = match &self.pose_saving {
+            Some(pose_saving) => pose_saving,
+            None => return,
+        };
+        let result = self
+            .joint_trajectory_client
+            .current_joint_positions()
+            .map_err(openrr_client::Error::from)
+            .and_then(|positions| {
+                let pose_names = robot_client.joints_poses_names(client_name);
+                let pose_name = (0..)
+                    .map(|i| format!("saved_{}", i))
+                    .find(|pose_name| !pose_names.contains(pose_name))
+                    .unwrap();
+                robot_client.save_joints_pose(client_name, &pose_name, positions)?;
+                Ok(pose_name)
+            });
+        match result {
+            Ok(pose_name) => self.speaker.speak(&format!("saved {}", pose_name)),
+            Err(e) => {
+                error!("Failed to save the pose of {}: {}", client_name, e);
+                self.speaker.speak("failed to save");
+            }
+        }
     }
 }
 
@@ -75,40 +138,44 @@ where
     S: Speaker,
 {
     fn set_event(&mut self, event: GamepadEvent) {
+        let bindings = &self.bindings;
         match event {
-            GamepadEvent::ButtonPressed(Button::East) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.next_joint => {
                 self.joint_index = (self.joint_index + 1) % self.dof;
                 self.submode = format!("{}", self.joint_index);
                 self.speaker
                     .speak(&format!("{}{}", self.mode, self.submode()));
             }
-            GamepadEvent::ButtonPressed(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.turbo => {
                 self.is_turbo = true;
             }
-            GamepadEvent::ButtonReleased(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.turbo => {
                 self.is_turbo = false;
             }
-            GamepadEvent::ButtonPressed(Button::RightTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
                 self.is_sending = true;
             }
-            GamepadEvent::ButtonReleased(Button::RightTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
                 self.is_sending = false;
                 self.velocity = 0.0;
             }
-            GamepadEvent::ButtonPressed(Button::West) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.increase => {
                 self.velocity = self.joint_step;
             }
-            GamepadEvent::ButtonReleased(Button::West) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.increase => {
                 self.velocity = 0.0;
             }
-            GamepadEvent::ButtonPressed(Button::South) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.decrease => {
                 self.velocity = -self.joint_step;
             }
-            GamepadEvent::ButtonReleased(Button::South) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.decrease => {
                 self.velocity = 0.0;
             }
-            GamepadEvent::AxisChanged(Axis::RightStickY, v) => {
-                self.velocity = self.joint_step * v * AXIS_GAIN;
+            GamepadEvent::ButtonPressed(b) if b == bindings.save_pose && self.is_sending => {
+                self.save_current_pose();
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.velocity => {
+                self.velocity = self.joint_step * v * self.axis_gain;
             }
             _ => {}
         }
@@ -119,16 +186,16 @@ where
                 .joint_trajectory_client
                 .current_joint_positions()
                 .unwrap();
-            pos[self.joint_index] += self.velocity
-                * if self.is_turbo {
-                    JOINT_POSITION_TURBO_GAIN
-                } else {
-                    1.0
-                };
-            self.joint_trajectory_client
+            pos[self.joint_index] +=
+                self.velocity * if self.is_turbo { self.turbo_gain } else { 1.0 };
+            if let Err(e) = self
+                .joint_trajectory_client
                 .send_joint_positions(pos, self.step_duration)
                 .await
-                .unwrap();
+            {
+                error!("Failed to send the joint positions: {}", e);
+                self.rumble.request(Rumble::failure());
+            }
         }
     }
     fn mode(&self) -> &str {
@@ -137,6 +204,22 @@ where
     fn submode(&self) -> &str {
         &self.submode
     }
+    async fn stop(&mut self) {
+        self.is_sending = false;
+        self.is_turbo = false;
+        self.velocity = 0.0;
+        stop_joint_trajectory_client(&self.joint_trajectory_client, self.step_duration).await;
+    }
+    fn take_rumble(&self) -> Option<Rumble> {
+        self.rumble.take()
+    }
+    fn status(&self) -> ControlNodeStatus {
+        ControlNodeStatus {
+            joints: joint_states(&self.joint_trajectory_client),
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -146,6 +229,12 @@ pub struct JoyJointTeleopNodeConfig {
     pub joint_step: f64,
     #[serde(default = "default_step_duration_secs")]
     pub step_duration_secs: f64,
+    #[serde(default = "default_axis_gain")]
+    pub axis_gain: f64,
+    #[serde(default = "default_turbo_gain")]
+    pub turbo_gain: f64,
+    #[serde(default)]
+    pub bindings: JoyJointTeleopBindings,
 }
 
 fn default_joint_step() -> f64 {
@@ -155,3 +244,11 @@ fn default_joint_step() -> f64 {
 fn default_step_duration_secs() -> f64 {
     0.1
 }
+
+fn default_axis_gain() -> f64 {
+    AXIS_GAIN
+}
+
+fn default_turbo_gain() -> f64 {
+    JOINT_POSITION_TURBO_GAIN
+}
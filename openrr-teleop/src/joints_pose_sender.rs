@@ -1,12 +1,13 @@
-use crate::ControlNode;
-use arci::{
-    gamepad::{Button, GamepadEvent},
-    JointTrajectoryClient, Speaker,
+use crate::{
+    control_node::{joint_states, stop_joint_trajectory_client, RumbleRequest},
+    Bindings, ControlNode, ControlNodeStatus, JointsPoseSenderBindings, Rumble,
 };
+use arci::{gamepad::GamepadEvent, JointTrajectoryClient, Speaker};
 use async_trait::async_trait;
-use openrr_client::JointsPose;
+use openrr_client::{ArcRobotClient, JointsPose};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tracing::error;
 
 pub struct JointsPoseSender<S, J>
 where
@@ -22,6 +23,9 @@ where
     is_trigger_holding: bool,
     is_sending: bool,
     duration: Duration,
+    robot_client: Option<Arc<ArcRobotClient>>,
+    rumble: RumbleRequest,
+    bindings: JointsPoseSenderBindings,
 }
 
 impl<S, J> JointsPoseSender<S, J>
@@ -49,6 +53,9 @@ where
             is_trigger_holding: false,
             is_sending: false,
             duration,
+            robot_client: None,
+            rumble: RumbleRequest::default(),
+            bindings: JointsPoseSenderBindings::default(),
         }
     }
     pub fn new_from_config(
@@ -64,6 +71,68 @@ where
             speaker,
             Duration::from_secs_f64(config.duration_secs),
         )
+        .with_bindings(config.bindings)
+    }
+    pub fn with_bindings(mut self, bindings: JointsPoseSenderBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+    /// Enables overwriting the selected pose with the current joint positions by
+    /// `JointsPoseSenderBindings::save_pose` while holding `JointsPoseSenderBindings::enable`.
+    ///
+    /// The pose is saved by `RobotClient::save_joints_pose`, and the poses saved by the other
+    /// users of `robot_client` are added to the poses of this node.
+    pub fn with_robot_client(mut self, robot_client: Arc<ArcRobotClient>) -> Self {
+        self.robot_client = Some(robot_client);
+        self
+    }
+    /// Updates the poses with the ones of the robot client, keeping the order of the poses.
+    fn update_joints_poses(&mut self) {
+        let robot_client = match &self.robot_client {
+            Some(robot_client) => robot_client,
+            None => return,
+        };
+        for new_pose in robot_client.joints_poses() {
+            if !self
+                .joint_trajectory_clients
+                .contains_key(&new_pose.client_name)
+            {
+                continue;
+            }
+            match self.joints_poses.iter_mut().find(|pose| {
+                pose.client_name == new_pose.client_name && pose.pose_name == new_pose.pose_name
+            }) {
+                Some(pose) => pose.positions = new_pose.positions,
+                None => self.joints_poses.push(new_pose),
+            }
+        }
+    }
+    fn save_current_pose(&mut self) {
+        let robot_client = match &self.robot_client {
+            Some(robot_client) => robot_client,
+            None => return,
+        };
+        let joints_pose = &mut self.joints_poses[self.pose_index];
+        let client = &self.joint_trajectory_clients[&joints_pose.client_name];
+        let result = client
+            .current_joint_positions()
+            .map_err(openrr_client::Error::from)
+            .and_then(|positions| {
+                robot_client.save_joints_pose(
+                    &joints_pose.client_name,
+                    &joints_pose.pose_name,
+                    positions.clone(),
+                )?;
+                joints_pose.positions = positions;
+                Ok(())
+            });
+        match result {
+            Ok(()) => self.speaker.speak(&format!("saved{}", self.submode)),
+            Err(e) => {
+                error!("Failed to save{}: {}", self.submode, e);
+                self.speaker.speak("failed to save");
+            }
+        }
     }
 }
 
@@ -74,27 +143,35 @@ where
     J: JointTrajectoryClient,
 {
     fn set_event(&mut self, event: arci::gamepad::GamepadEvent) {
+        let bindings = &self.bindings;
         match event {
-            GamepadEvent::ButtonPressed(Button::East) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.next_pose => {
+                self.update_joints_poses();
                 self.pose_index = (self.pose_index + 1) % self.joints_poses.len();
                 let joints_pose = &self.joints_poses[self.pose_index];
                 self.submode = format!(" {} {}", joints_pose.client_name, joints_pose.pose_name);
                 self.speaker
                     .speak(&format!("{}{}", self.mode, self.submode()));
             }
-            GamepadEvent::ButtonPressed(Button::RightTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
                 self.is_trigger_holding = true;
             }
-            GamepadEvent::ButtonReleased(Button::RightTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
                 self.is_trigger_holding = false;
                 self.is_sending = false;
             }
-            GamepadEvent::ButtonPressed(Button::West) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.send => {
+                self.update_joints_poses();
                 self.is_sending = true;
             }
-            GamepadEvent::ButtonReleased(Button::West) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.send => {
                 self.is_sending = false;
             }
+            GamepadEvent::ButtonPressed(b)
+                if b == bindings.save_pose && self.is_trigger_holding =>
+            {
+                self.save_current_pose();
+            }
             _ => {}
         }
     }
@@ -105,10 +182,13 @@ where
             .get(&joints_pose.client_name)
             .unwrap();
         if self.is_sending && self.is_trigger_holding {
-            client
+            if let Err(e) = client
                 .send_joint_positions(joints_pose.positions.to_owned(), self.duration)
                 .await
-                .unwrap();
+            {
+                error!("Failed to send{}: {}", self.submode, e);
+                self.rumble.request(Rumble::failure());
+            }
         } else {
             client
                 .send_joint_positions(client.current_joint_positions().unwrap(), self.duration)
@@ -122,6 +202,24 @@ where
     fn submode(&self) -> &str {
         &self.submode
     }
+    async fn stop(&mut self) {
+        self.is_trigger_holding = false;
+        self.is_sending = false;
+        let client_name = &self.joints_poses[self.pose_index].client_name;
+        stop_joint_trajectory_client(&self.joint_trajectory_clients[client_name], self.duration)
+            .await;
+    }
+    fn take_rumble(&self) -> Option<Rumble> {
+        self.rumble.take()
+    }
+    fn status(&self) -> ControlNodeStatus {
+        let client_name = &self.joints_poses[self.pose_index].client_name;
+        ControlNodeStatus {
+            joints: joint_states(&self.joint_trajectory_clients[client_name]),
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -130,6 +228,8 @@ pub struct JointsPoseSenderConfig {
     pub mode: String,
     #[serde(default = "default_duration_secs")]
     pub duration_secs: f64,
+    #[serde(default)]
+    pub bindings: JointsPoseSenderBindings,
 }
 
 fn default_mode() -> String {
@@ -1,15 +1,27 @@
+mod behavior_tree;
+mod bindings;
 mod control_node;
 mod control_nodes_config;
+mod error;
 mod ik;
 mod joints;
 mod joints_pose_sender;
 mod move_base;
+mod robot_status_alert;
 mod switcher;
+mod trajectory_playback;
+mod whole_body;
 
+pub use behavior_tree::*;
+pub use bindings::*;
 pub use control_node::*;
 pub use control_nodes_config::*;
+pub use error::*;
 pub use ik::*;
 pub use joints::*;
 pub use joints_pose_sender::*;
 pub use move_base::*;
+pub use robot_status_alert::*;
 pub use switcher::*;
+pub use trajectory_playback::*;
+pub use whole_body::*;
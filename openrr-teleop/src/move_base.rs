@@ -1,7 +1,11 @@
-use super::control_node::ControlNode;
-use arci::gamepad::{Axis, Button, GamepadEvent};
+use super::control_node::{ControlNode, ControlNodeStatus};
+use crate::{Bindings, MoveBaseBindings};
+use arci::gamepad::GamepadEvent;
 use arci::{BaseVelocity, MoveBase};
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{debug, error};
 
 const BASE_LINEAR_VEL_AXIS_GAIN: f64 = 0.5;
 const BASE_ANGULAR_VEL_AXIS_GAIN: f64 = 1.5;
@@ -14,6 +18,13 @@ pub struct MoveBaseNode<T: MoveBase> {
     vel: BaseVelocity,
     is_enabled: bool,
     is_turbo: bool,
+    linear_gain: f64,
+    angular_gain: f64,
+    turbo_gain: f64,
+    bindings: MoveBaseBindings,
+    /// True while the velocity commands keep failing, e.g. rejected by the geofence.
+    /// Only the first error of them is logged.
+    is_failing: AtomicBool,
 }
 
 impl<T> MoveBaseNode<T>
@@ -28,6 +39,39 @@ where
             vel: BaseVelocity::default(),
             is_enabled: false,
             is_turbo: false,
+            linear_gain: BASE_LINEAR_VEL_AXIS_GAIN,
+            angular_gain: BASE_ANGULAR_VEL_AXIS_GAIN,
+            turbo_gain: BASE_TURBO_GAIN,
+            bindings: MoveBaseBindings::default(),
+            is_failing: AtomicBool::new(false),
+        }
+    }
+    pub fn new_from_config(config: MoveBaseNodeConfig, move_base: T) -> Self {
+        let mut node = Self::new(config.mode, move_base);
+        node.linear_gain = config.linear_gain;
+        node.angular_gain = config.angular_gain;
+        node.turbo_gain = config.turbo_gain;
+        node.with_bindings(config.bindings)
+    }
+    pub fn with_bindings(mut self, bindings: MoveBaseBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
+    /// Velocity sent to the base.
+    fn commanded_velocity(&self) -> BaseVelocity {
+        if !self.is_enabled {
+            BaseVelocity::default()
+        } else if self.is_turbo {
+            self.vel * self.turbo_gain
+        } else {
+            self.vel
+        }
+    }
+
+    fn stop_base(&self) {
+        if let Err(e) = self.move_base.send_velocity(&BaseVelocity::default()) {
+            error!("Failed to stop the base: {}", e);
         }
     }
 }
@@ -38,30 +82,30 @@ where
     T: MoveBase,
 {
     fn set_event(&mut self, ev: GamepadEvent) {
+        let bindings = &self.bindings;
         match ev {
-            GamepadEvent::AxisChanged(Axis::LeftStickX, v) => {
-                self.vel.y = v * BASE_LINEAR_VEL_AXIS_GAIN
+            GamepadEvent::AxisChanged(a, v) if a == bindings.linear_y => {
+                self.vel.y = v * self.linear_gain
             }
-            GamepadEvent::AxisChanged(Axis::LeftStickY, v) => {
-                self.vel.x = v * BASE_LINEAR_VEL_AXIS_GAIN
+            GamepadEvent::AxisChanged(a, v) if a == bindings.linear_x => {
+                self.vel.x = v * self.linear_gain
             }
-            GamepadEvent::AxisChanged(Axis::RightStickX, v) => {
-                self.vel.theta = v * BASE_ANGULAR_VEL_AXIS_GAIN
+            GamepadEvent::AxisChanged(a, v) if a == bindings.angular_z => {
+                self.vel.theta = v * self.angular_gain
             }
-            GamepadEvent::ButtonPressed(Button::RightTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
                 self.is_enabled = true;
+                self.is_failing.store(false, Ordering::Relaxed);
             }
-            GamepadEvent::ButtonReleased(Button::RightTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
                 self.is_enabled = false;
                 // stop immediately
-                self.move_base
-                    .send_velocity(&BaseVelocity::default())
-                    .unwrap();
+                self.stop_base();
             }
-            GamepadEvent::ButtonPressed(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonPressed(b) if b == bindings.turbo => {
                 self.is_turbo = true;
             }
-            GamepadEvent::ButtonReleased(Button::LeftTrigger2) => {
+            GamepadEvent::ButtonReleased(b) if b == bindings.turbo => {
                 self.is_turbo = false;
             }
             _ => {}
@@ -69,11 +113,12 @@ where
     }
     async fn proc(&self) {
         if self.is_enabled {
-            if self.is_turbo {
-                let turbo_vel = self.vel * BASE_TURBO_GAIN;
-                self.move_base.send_velocity(&turbo_vel).unwrap();
-            } else {
-                self.move_base.send_velocity(&self.vel).unwrap();
+            match self.move_base.send_velocity(&self.commanded_velocity()) {
+                Ok(_) => self.is_failing.store(false, Ordering::Relaxed),
+                Err(e) if self.is_failing.swap(true, Ordering::Relaxed) => {
+                    debug!("Failed to send velocity: {}", e);
+                }
+                Err(e) => error!("Failed to send velocity: {}", e),
             }
         }
     }
@@ -83,4 +128,59 @@ where
     fn submode(&self) -> &str {
         &self.submode
     }
+    async fn stop(&mut self) {
+        self.is_enabled = false;
+        self.is_turbo = false;
+        self.vel = BaseVelocity::default();
+        self.stop_base();
+    }
+    fn status(&self) -> ControlNodeStatus {
+        ControlNodeStatus {
+            base_velocity: Some(self.commanded_velocity()),
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoveBaseNodeConfig {
+    #[serde(default = "default_mode")]
+    pub mode: String,
+    #[serde(default = "default_linear_gain")]
+    pub linear_gain: f64,
+    #[serde(default = "default_angular_gain")]
+    pub angular_gain: f64,
+    #[serde(default = "default_turbo_gain")]
+    pub turbo_gain: f64,
+    #[serde(default)]
+    pub bindings: MoveBaseBindings,
+}
+
+impl Default for MoveBaseNodeConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_mode(),
+            linear_gain: default_linear_gain(),
+            angular_gain: default_angular_gain(),
+            turbo_gain: default_turbo_gain(),
+            bindings: MoveBaseBindings::default(),
+        }
+    }
+}
+
+fn default_mode() -> String {
+    "base".to_string()
+}
+
+fn default_linear_gain() -> f64 {
+    BASE_LINEAR_VEL_AXIS_GAIN
+}
+
+fn default_angular_gain() -> f64 {
+    BASE_ANGULAR_VEL_AXIS_GAIN
+}
+
+fn default_turbo_gain() -> f64 {
+    BASE_TURBO_GAIN
 }
@@ -0,0 +1,140 @@
+use arci::{RobotStatus, Speaker};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tracing::{info, warn};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RobotStatusAlertConfig {
+    /// Speak alert when the battery percentage (0 to 1) becomes lower than this value.
+    #[serde(default = "default_low_battery_threshold")]
+    pub low_battery_threshold: f64,
+    #[serde(default = "default_check_interval_secs")]
+    pub check_interval_secs: f64,
+}
+
+fn default_low_battery_threshold() -> f64 {
+    0.2
+}
+
+fn default_check_interval_secs() -> f64 {
+    1.0
+}
+
+impl Default for RobotStatusAlertConfig {
+    fn default() -> Self {
+        Self {
+            low_battery_threshold: default_low_battery_threshold(),
+            check_interval_secs: default_check_interval_secs(),
+        }
+    }
+}
+
+/// Periodically checks the robot status and speaks when the battery becomes low
+/// or the emergency stop is pressed / released.
+pub struct RobotStatusAlert<R, S>
+where
+    R: RobotStatus,
+    S: Speaker,
+{
+    robot_status: R,
+    speaker: S,
+    low_battery_threshold: f64,
+    check_interval: Duration,
+    is_running: Arc<AtomicBool>,
+}
+
+impl<R, S> RobotStatusAlert<R, S>
+where
+    R: RobotStatus,
+    S: Speaker,
+{
+    pub fn new(
+        robot_status: R,
+        speaker: S,
+        low_battery_threshold: f64,
+        check_interval: Duration,
+    ) -> Self {
+        Self {
+            robot_status,
+            speaker,
+            low_battery_threshold,
+            check_interval,
+            is_running: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    pub fn new_from_config(config: RobotStatusAlertConfig, robot_status: R, speaker: S) -> Self {
+        Self::new(
+            robot_status,
+            speaker,
+            config.low_battery_threshold,
+            Duration::from_secs_f64(config.check_interval_secs),
+        )
+    }
+
+    /// Stops `main`. `main` returns immediately if this is called before it starts.
+    pub fn stop(&self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+
+    pub async fn main(&self) {
+        let mut interval = tokio::time::interval(self.check_interval);
+        let mut was_low_battery = false;
+        let mut was_emergency_stopped = false;
+        // Warns only when the state becomes unavailable, not at every check.
+        let mut is_battery_state_available = true;
+        let mut is_emergency_stop_state_available = true;
+        while self.is_running.load(Ordering::Relaxed) {
+            interval.tick().await;
+            match self.robot_status.battery_state() {
+                Ok(battery_state) => {
+                    let is_low_battery = battery_state
+                        .percentage
+                        .map(|p| p < self.low_battery_threshold)
+                        .unwrap_or(false)
+                        && !battery_state.is_charging;
+                    if is_low_battery && !was_low_battery {
+                        self.speaker.speak("Battery low");
+                    }
+                    was_low_battery = is_low_battery;
+                    if !is_battery_state_available {
+                        info!("Battery state is available again");
+                        is_battery_state_available = true;
+                    }
+                }
+                Err(e) => {
+                    if is_battery_state_available {
+                        warn!("Failed to get battery state: {}", e);
+                        is_battery_state_available = false;
+                    }
+                }
+            }
+            match self.robot_status.is_emergency_stopped() {
+                Ok(is_emergency_stopped) => {
+                    if is_emergency_stopped != was_emergency_stopped {
+                        self.speaker.speak(if is_emergency_stopped {
+                            "Emergency stop"
+                        } else {
+                            "Emergency stop released"
+                        });
+                    }
+                    was_emergency_stopped = is_emergency_stopped;
+                    if !is_emergency_stop_state_available {
+                        info!("Emergency stop state is available again");
+                        is_emergency_stop_state_available = true;
+                    }
+                }
+                Err(e) => {
+                    if is_emergency_stop_state_available {
+                        warn!("Failed to get emergency stop state: {}", e);
+                        is_emergency_stop_state_available = false;
+                    }
+                }
+            }
+        }
+    }
+}
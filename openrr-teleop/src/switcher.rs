@@ -1,13 +1,14 @@
-use super::control_node::ControlNode;
+use super::control_node::{ControlNode, ControlNodeStatus, Rumble};
 use arci::gamepad::{Button, Gamepad, GamepadEvent};
 use arci::Speaker;
+use std::fmt;
 use std::sync::{
     atomic::{AtomicBool, AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex,
 };
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex as TokioMutex;
-use tracing::{debug, warn};
+use tracing::{debug, error, info, warn};
 
 pub struct ControlNodeSwitcher<N, S>
 where
@@ -18,6 +19,8 @@ where
     control_nodes: Arc<TokioMutex<Vec<N>>>,
     speaker: S,
     is_running: Arc<AtomicBool>,
+    mode_switch_button: Button,
+    deadman_timeout: Option<Duration>,
 }
 
 impl<N, S> ControlNodeSwitcher<N, S>
@@ -32,13 +35,31 @@ where
             control_nodes: Arc::new(TokioMutex::new(control_nodes)),
             speaker,
             is_running: Arc::new(AtomicBool::new(false)),
+            mode_switch_button: Button::North,
+            deadman_timeout: None,
         }
     }
+    /// Switches the control node by `mode_switch_button` instead of `Button::North`.
+    pub fn with_mode_switch_button(mut self, mode_switch_button: Button) -> Self {
+        self.mode_switch_button = mode_switch_button;
+        self
+    }
+    /// Stops the current control node if no gamepad event arrives for `deadman_timeout`.
+    ///
+    /// Many gamepads send events only when the state changes, so the timeout should be longer
+    /// than the operator keeps the buttons and axes still, unless the gamepad repeats the events
+    /// (e.g. `joy_node` of ROS with `autorepeat_rate`).
+    pub fn with_deadman_timeout(mut self, deadman_timeout: Duration) -> Self {
+        self.deadman_timeout = Some(deadman_timeout);
+        self
+    }
+    /// Stops the current control node and switches to the next one.
     pub async fn increment_mode(&self) {
-        let len = self.control_nodes.lock().await.len();
-        self.current_index.fetch_add(1, Ordering::Relaxed);
-        let next = self.current_index.load(Ordering::Relaxed) % len;
+        let mut nodes = self.control_nodes.lock().await;
+        nodes[self.current_index()].stop().await;
+        let next = (self.current_index() + 1) % nodes.len();
         self.current_index.store(next, Ordering::Relaxed);
+        drop(nodes);
         self.speak_current_mode().await;
     }
     pub async fn speak_current_mode(&self) {
@@ -48,15 +69,34 @@ where
         let submode = nodes[i].submode();
         self.speaker.speak(&format!("{}{}", mode, submode,));
     }
+    /// Status of the control nodes for showing to the operator.
+    pub async fn status(&self) -> ControlNodeSwitcherStatus {
+        let nodes = self.control_nodes.lock().await;
+        let current_node = &nodes[self.current_index()];
+        ControlNodeSwitcherStatus {
+            modes: nodes.iter().map(|node| node.mode().to_owned()).collect(),
+            current_index: self.current_index(),
+            submode: current_node.submode().to_owned(),
+            mode_switch_button: self.mode_switch_button,
+            node: current_node.status(),
+        }
+    }
     fn current_index(&self) -> usize {
         self.current_index.load(Ordering::Relaxed)
     }
     fn is_running(&self) -> bool {
         self.is_running.load(Ordering::Relaxed)
     }
+    /// Stops `main`. The current control node is stopped before `main` returns.
     pub fn stop(&self) {
         self.is_running.store(false, Ordering::Relaxed);
     }
+    /// Stops the current control node, e.g. before exiting the process without waiting for `main`.
+    pub async fn stop_current_node(&self) {
+        self.control_nodes.lock().await[self.current_index()]
+            .stop()
+            .await;
+    }
     pub async fn main<G>(&self, gamepad: G)
     where
         G: 'static + Gamepad,
@@ -64,27 +104,64 @@ where
         let nodes = self.control_nodes.clone();
         let index = self.current_index.clone();
         let is_running = self.is_running.clone();
+        let deadman_timeout = self.deadman_timeout;
+        let last_event_time = Arc::new(Mutex::new(Instant::now()));
+        let last_event_time_cloned = last_event_time.clone();
         self.is_running.store(true, Ordering::Relaxed);
         self.speak_current_mode().await;
         let gamepad = Arc::new(gamepad);
         let gamepad_cloned = gamepad.clone();
-        tokio::spawn(async move {
+        let proc_handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(50));
+            let mut is_timed_out = false;
             while is_running.load(Ordering::Relaxed) {
                 debug!("tick");
-                nodes.lock().await[index.load(Ordering::Relaxed)]
-                    .proc()
-                    .await;
+                {
+                    let mut nodes = nodes.lock().await;
+                    let node = &mut nodes[index.load(Ordering::Relaxed)];
+                    let elapsed = last_event_time_cloned.lock().unwrap().elapsed();
+                    match deadman_timeout {
+                        Some(timeout) if elapsed > timeout => {
+                            if !is_timed_out {
+                                warn!("No gamepad event for {:?}, stop {}", elapsed, node.mode());
+                                node.stop().await;
+                                is_timed_out = true;
+                            }
+                        }
+                        _ => {
+                            is_timed_out = false;
+                            node.proc().await;
+                            if let Some(rumble) = node.take_rumble() {
+                                gamepad_cloned.rumble(rumble.intensity, rumble.duration);
+                            }
+                        }
+                    }
+                }
                 interval.tick().await;
             }
+            nodes.lock().await[index.load(Ordering::Relaxed)]
+                .stop()
+                .await;
             gamepad_cloned.stop();
         });
         while self.is_running() {
             let ev = gamepad.next_event().await;
             debug!("event: {:?}", ev);
+            *last_event_time.lock().unwrap() = Instant::now();
             match ev {
-                GamepadEvent::ButtonPressed(Button::North) => {
+                GamepadEvent::ButtonPressed(b) if b == self.mode_switch_button => {
                     self.increment_mode().await;
+                    let rumble = Rumble::mode_switch();
+                    gamepad.rumble(rumble.intensity, rumble.duration);
+                }
+                GamepadEvent::Connected => {
+                    info!("gamepad connected");
+                    self.speaker.speak("gamepad connected");
+                }
+                GamepadEvent::Disconnected => {
+                    warn!("gamepad disconnected");
+                    self.stop_current_node().await;
+                    self.speaker.speak("gamepad disconnected");
                 }
                 GamepadEvent::Unknown => {
                     warn!("gamepad Unkwon");
@@ -95,5 +172,71 @@ where
                 }
             }
         }
+        if let Err(e) = proc_handle.await {
+            error!("Failed to stop the control node: {}", e);
+        }
+    }
+}
+
+/// Status of `ControlNodeSwitcher`.
+///
+/// The `Display` implementation formats it as lines of text for terminals.
+#[derive(Debug, Clone)]
+pub struct ControlNodeSwitcherStatus {
+    pub modes: Vec<String>,
+    pub current_index: usize,
+    /// Submode of the current control node.
+    pub submode: String,
+    pub mode_switch_button: Button,
+    /// Status of the current control node.
+    pub node: ControlNodeStatus,
+}
+
+impl fmt::Display for ControlNodeSwitcherStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Mode: {}{} ({}/{})",
+            self.modes[self.current_index],
+            self.submode,
+            self.current_index + 1,
+            self.modes.len()
+        )?;
+        for (i, mode) in self.modes.iter().enumerate() {
+            let marker = if i == self.current_index { ">" } else { " " };
+            writeln!(f, " {} {}", marker, mode)?;
+        }
+        if !self.node.joints.is_empty() {
+            writeln!(f, "Joints:")?;
+            for (name, position) in &self.node.joints {
+                writeln!(f, "  {:<24} {:>8.3}", name, position)?;
+            }
+        }
+        if let Some(velocity) = &self.node.base_velocity {
+            writeln!(
+                f,
+                "Base velocity: x {:.3} y {:.3} theta {:.3}",
+                velocity.x, velocity.y, velocity.theta
+            )?;
+        }
+        if let Some(pose) = &self.node.ik_target {
+            let position = pose.translation.vector;
+            let (roll, pitch, yaw) = pose.rotation.euler_angles();
+            writeln!(
+                f,
+                "IK target: x {:.3} y {:.3} z {:.3} roll {:.3} pitch {:.3} yaw {:.3}",
+                position.x, position.y, position.z, roll, pitch, yaw
+            )?;
+        }
+        writeln!(f, "Bindings:")?;
+        writeln!(
+            f,
+            "  {:<24} switch mode",
+            format!("{:?}", self.mode_switch_button)
+        )?;
+        for (input, action) in &self.node.legend {
+            writeln!(f, "  {:<24} {}", input, action)?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,329 @@
+use crate::{
+    control_node::{joint_states, stop_joint_trajectory_client, RumbleRequest},
+    Bindings, ControlNode, ControlNodeStatus, Error, Rumble, TrajectoryPlaybackBindings,
+};
+use arci::{gamepad::GamepadEvent, JointTrajectoryClient, Speaker, TrajectoryPoint};
+use async_trait::async_trait;
+use openrr_client::{joint_trajectory_from_records, load_records};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::error;
+
+/// Margin [s] to find the next or the previous point of the trajectory.
+const STEP_TIME_MARGIN: f64 = 1e-6;
+
+/// Plays back a joint trajectory on the robot, e.g. to check the output of the planner slowly
+/// before running it at full speed.
+///
+/// While `enable` is pressed, `play` plays or pauses the trajectory, `step_forward` and
+/// `step_backward` move to the next or the previous point of it, and `scrub` moves through it
+/// by the speed of the stick. The joints never move faster than `max_joint_velocity`, and the
+/// playback waits while the robot is behind, e.g. when it moves to the trajectory first.
+///
+/// Pass a collision checked client to check the trajectory on the way.
+pub struct TrajectoryPlaybackNode<J, S>
+where
+    J: JointTrajectoryClient,
+    S: Speaker,
+{
+    mode: String,
+    submode: String,
+    joint_trajectory_client: J,
+    speaker: S,
+    trajectory: Vec<TrajectoryPoint>,
+    /// Current time [s] in the trajectory.
+    time: Mutex<f64>,
+    is_playing: Mutex<bool>,
+    last_proc_time: Mutex<Option<Instant>>,
+    is_enabled: bool,
+    /// The joints are stopped in the next `proc` after `enable` is released.
+    is_stop_requested: AtomicBool,
+    scrub_value: f64,
+    speed: f64,
+    scrub_speed: f64,
+    max_joint_velocity: f64,
+    step_duration: Duration,
+    rumble: RumbleRequest,
+    bindings: TrajectoryPlaybackBindings,
+}
+
+impl<J, S> TrajectoryPlaybackNode<J, S>
+where
+    J: JointTrajectoryClient,
+    S: Speaker,
+{
+    /// `trajectory` must not be empty.
+    pub fn new(
+        mode: String,
+        joint_trajectory_client: J,
+        trajectory: Vec<TrajectoryPoint>,
+        speaker: S,
+    ) -> Self {
+        assert!(!trajectory.is_empty(), "trajectory must not be empty");
+        Self {
+            mode,
+            submode: "".to_string(),
+            joint_trajectory_client,
+            speaker,
+            trajectory,
+            time: Mutex::new(0.0),
+            is_playing: Mutex::new(false),
+            last_proc_time: Mutex::new(None),
+            is_enabled: false,
+            is_stop_requested: AtomicBool::new(false),
+            scrub_value: 0.0,
+            speed: default_speed(),
+            scrub_speed: default_scrub_speed(),
+            max_joint_velocity: default_max_joint_velocity(),
+            step_duration: Duration::from_secs_f64(default_step_duration_secs()),
+            rumble: RumbleRequest::default(),
+            bindings: TrajectoryPlaybackBindings::default(),
+        }
+    }
+    /// Loads the trajectory of `record_client_name` from `record_path`.
+    pub fn new_from_config(
+        config: TrajectoryPlaybackNodeConfig,
+        joint_trajectory_client: J,
+        speaker: S,
+    ) -> Result<Self, Error> {
+        let trajectory = joint_trajectory_from_records(
+            &load_records(&config.record_path)?,
+            &config.record_client_name,
+        );
+        if trajectory.is_empty() {
+            return Err(Error::NoJointTrajectory(
+                config.record_client_name,
+                config.record_path,
+            ));
+        }
+        let dof = joint_trajectory_client.joint_names().len();
+        if let Some(point) = trajectory.iter().find(|p| p.positions.len() != dof) {
+            return Err(Error::MismatchedLength(point.positions.len(), dof));
+        }
+        let mut node = Self::new(config.mode, joint_trajectory_client, trajectory, speaker);
+        node.speed = config.speed;
+        node.scrub_speed = config.scrub_speed;
+        node.max_joint_velocity = config.max_joint_velocity;
+        node.step_duration = Duration::from_secs_f64(config.step_duration_secs);
+        Ok(node.with_bindings(config.bindings))
+    }
+    pub fn with_bindings(mut self, bindings: TrajectoryPlaybackBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
+    fn point_times(&self) -> impl DoubleEndedIterator<Item = f64> + '_ {
+        self.trajectory
+            .iter()
+            .map(|point| point.time_from_start.as_secs_f64())
+    }
+
+    fn end_time(&self) -> f64 {
+        self.point_times().last().unwrap()
+    }
+
+    /// Positions at `time`, interpolated linearly between the points.
+    fn positions_at(&self, time: f64) -> Vec<f64> {
+        match self.point_times().position(|t| t > time) {
+            None => self.trajectory.last().unwrap().positions.clone(),
+            Some(0) => self.trajectory[0].positions.clone(),
+            Some(i) => {
+                let (prev, next) = (&self.trajectory[i - 1], &self.trajectory[i]);
+                let prev_time = prev.time_from_start.as_secs_f64();
+                let ratio = (time - prev_time) / (next.time_from_start.as_secs_f64() - prev_time);
+                prev.positions
+                    .iter()
+                    .zip(&next.positions)
+                    .map(|(p, n)| p + (n - p) * ratio)
+                    .collect()
+            }
+        }
+    }
+
+    fn step(&mut self, is_forward: bool) {
+        *self.is_playing.get_mut().unwrap() = false;
+        let current = *self.time.get_mut().unwrap();
+        let time = if is_forward {
+            self.point_times()
+                .find(|t| *t > current + STEP_TIME_MARGIN)
+                .unwrap_or_else(|| self.end_time())
+        } else {
+            self.point_times()
+                .rev()
+                .find(|t| *t < current - STEP_TIME_MARGIN)
+                .unwrap_or(0.0)
+        };
+        *self.time.get_mut().unwrap() = time;
+    }
+}
+
+#[async_trait]
+impl<J, S> ControlNode for TrajectoryPlaybackNode<J, S>
+where
+    J: JointTrajectoryClient,
+    S: Speaker,
+{
+    fn set_event(&mut self, event: GamepadEvent) {
+        let bindings = &self.bindings;
+        match event {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
+                self.is_enabled = true;
+                self.is_stop_requested.store(false, Ordering::Relaxed);
+                *self.last_proc_time.get_mut().unwrap() = None;
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
+                self.is_enabled = false;
+                *self.is_playing.get_mut().unwrap() = false;
+                self.is_stop_requested.store(true, Ordering::Relaxed);
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.play && self.is_enabled => {
+                let is_playing = !*self.is_playing.get_mut().unwrap();
+                if is_playing && *self.time.get_mut().unwrap() >= self.end_time() {
+                    *self.time.get_mut().unwrap() = 0.0;
+                }
+                *self.is_playing.get_mut().unwrap() = is_playing;
+                self.speaker
+                    .speak(if is_playing { "play" } else { "pause" });
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.step_forward && self.is_enabled => {
+                self.step(true);
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.step_backward && self.is_enabled => {
+                self.step(false);
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.scrub => {
+                self.scrub_value = v;
+            }
+            _ => {}
+        }
+    }
+    async fn proc(&self) {
+        if !self.is_enabled {
+            if self.is_stop_requested.swap(false, Ordering::Relaxed) {
+                stop_joint_trajectory_client(&self.joint_trajectory_client, self.step_duration)
+                    .await;
+            }
+            return;
+        }
+        let dt = self.step_duration.as_secs_f64();
+        let now = Instant::now();
+        let elapsed = self
+            .last_proc_time
+            .lock()
+            .unwrap()
+            .replace(now)
+            .map_or(0.0, |last| (now - last).as_secs_f64().min(dt));
+        let time = *self.time.lock().unwrap();
+        let target = self.positions_at(time);
+        let current = match self.joint_trajectory_client.current_joint_positions() {
+            Ok(positions) => positions,
+            Err(e) => {
+                error!("Failed to get the joint positions: {}", e);
+                return;
+            }
+        };
+        let max_diff = current
+            .iter()
+            .zip(&target)
+            .map(|(c, t)| (t - c).abs())
+            .fold(0.0, f64::max);
+        let duration = (max_diff / self.max_joint_velocity).max(dt);
+        if let Err(e) = self
+            .joint_trajectory_client
+            .send_joint_positions(target, Duration::from_secs_f64(duration))
+            .await
+        {
+            error!("Failed to send the joint positions: {}", e);
+            *self.is_playing.lock().unwrap() = false;
+            self.rumble.request(Rumble::failure());
+            return;
+        }
+        if duration > dt {
+            // Waits for the robot.
+            return;
+        }
+        let mut is_playing = self.is_playing.lock().unwrap();
+        let play_speed = if *is_playing { self.speed } else { 0.0 };
+        let end_time = self.end_time();
+        let new_time = (time + (play_speed + self.scrub_value * self.scrub_speed) * elapsed)
+            .clamp(0.0, end_time);
+        *self.time.lock().unwrap() = new_time;
+        if *is_playing && new_time >= end_time {
+            *is_playing = false;
+            self.speaker.speak("end");
+        }
+    }
+    fn mode(&self) -> &str {
+        &self.mode
+    }
+    fn submode(&self) -> &str {
+        &self.submode
+    }
+    async fn stop(&mut self) {
+        self.is_enabled = false;
+        *self.is_playing.get_mut().unwrap() = false;
+        self.is_stop_requested.store(false, Ordering::Relaxed);
+        stop_joint_trajectory_client(&self.joint_trajectory_client, self.step_duration).await;
+    }
+    fn take_rumble(&self) -> Option<Rumble> {
+        self.rumble.take()
+    }
+    fn status(&self) -> ControlNodeStatus {
+        ControlNodeStatus {
+            joints: joint_states(&self.joint_trajectory_client),
+            legend: self.bindings.legend(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrajectoryPlaybackNodeConfig {
+    #[serde(default = "default_mode")]
+    pub mode: String,
+    /// File written by `openrr_client::Recorder`. Relative to the teleop config if loaded
+    /// by openrr-apps.
+    pub record_path: PathBuf,
+    /// Name of the client in the record file.
+    pub record_client_name: String,
+    /// Speed of `play`, relative to the recorded speed.
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// Speed of `scrub` at the full stick, relative to the recorded speed.
+    #[serde(default = "default_scrub_speed")]
+    pub scrub_speed: f64,
+    /// Maximum joint velocity [rad/s] while following the trajectory.
+    #[serde(default = "default_max_joint_velocity")]
+    pub max_joint_velocity: f64,
+    #[serde(default = "default_step_duration_secs")]
+    pub step_duration_secs: f64,
+    #[serde(default)]
+    pub bindings: TrajectoryPlaybackBindings,
+}
+
+fn default_mode() -> String {
+    "playback".to_string()
+}
+
+fn default_speed() -> f64 {
+    0.5
+}
+
+fn default_scrub_speed() -> f64 {
+    1.0
+}
+
+fn default_max_joint_velocity() -> f64 {
+    0.5
+}
+
+fn default_step_duration_secs() -> f64 {
+    0.1
+}
@@ -0,0 +1,502 @@
+use super::control_node::{
+    joint_states, stop_joint_trajectory_client, ControlNode, ControlNodeStatus, Rumble,
+    RumbleRequest,
+};
+use crate::{Bindings, WholeBodyBindings};
+use arci::gamepad::GamepadEvent;
+use arci::{BaseVelocity, JointTrajectoryClient, MoveBase};
+use async_trait::async_trait;
+use k::{
+    nalgebra::{DMatrix, DVector, Matrix6xX, Vector2, Vector6},
+    UnitQuaternion, Vector3,
+};
+use openrr_client::IkSolverWithChain;
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::error;
+
+const WHOLE_BODY_TURBO_GAIN: f64 = 2.0;
+
+/// Moves the end effector of the arm by the velocity in the root frame, using both the arm
+/// and the base.
+///
+/// The joint velocities of the arm are computed from the Jacobian. The vertical motion and the
+/// orientation of the end effector are kept by the arm first, because the base cannot do them,
+/// and the horizontal motion is done within the null space of them. Near the reach limit (or
+/// other singularities) of the arm, the pseudo-inverse is damped, so the arm follows less of the
+/// horizontal velocity and the base takes the rest of it. The remaining null space of the arm is
+/// used to move the joints back to the positions where `enable` is pressed. The yaw axis rotates
+/// the base.
+///
+/// The root frame of the IK chain is assumed to be the base frame of `MoveBase`.
+pub struct WholeBodyNode<J, M>
+where
+    J: JointTrajectoryClient,
+    M: MoveBase,
+{
+    joint_trajectory_client: J,
+    move_base: M,
+    ik_solver_with_chain: Arc<IkSolverWithChain>,
+    mode: String,
+    submode: String,
+    /// Commanded velocity of the end effector.
+    linear_velocity: Vector3<f64>,
+    angular_velocity: f64,
+    max_linear_velocity: [f64; 3],
+    max_angular_velocity: f64,
+    step_duration: Duration,
+    damping: f64,
+    posture_gain: f64,
+    /// Set when `enable` is pressed.
+    arm_target: Mutex<Option<ArmTarget>>,
+    /// Velocity sent to the base most recently.
+    base_velocity: Mutex<BaseVelocity>,
+    is_enabled: bool,
+    is_turbo: bool,
+    turbo_gain: f64,
+    rumble: RumbleRequest,
+    bindings: WholeBodyBindings,
+}
+
+impl<J, M> WholeBodyNode<J, M>
+where
+    J: JointTrajectoryClient,
+    M: MoveBase,
+{
+    pub fn new(
+        mode: String,
+        joint_trajectory_client: J,
+        move_base: M,
+        ik_solver_with_chain: Arc<IkSolverWithChain>,
+    ) -> Self {
+        Self {
+            joint_trajectory_client,
+            move_base,
+            ik_solver_with_chain,
+            mode,
+            submode: "".to_string(),
+            linear_velocity: Vector3::zeros(),
+            angular_velocity: 0.0,
+            max_linear_velocity: default_linear_velocity(),
+            max_angular_velocity: default_angular_velocity(),
+            step_duration: Duration::from_secs_f64(default_step_duration_secs()),
+            damping: default_damping(),
+            posture_gain: default_posture_gain(),
+            arm_target: Mutex::new(None),
+            base_velocity: Mutex::new(BaseVelocity::default()),
+            is_enabled: false,
+            is_turbo: false,
+            turbo_gain: WHOLE_BODY_TURBO_GAIN,
+            rumble: RumbleRequest::default(),
+            bindings: WholeBodyBindings::default(),
+        }
+    }
+    pub fn new_from_config(
+        config: WholeBodyNodeConfig,
+        joint_trajectory_client: J,
+        move_base: M,
+        ik_solver_with_chain: Arc<IkSolverWithChain>,
+    ) -> Self {
+        let mut node = Self::new(
+            config.mode,
+            joint_trajectory_client,
+            move_base,
+            ik_solver_with_chain,
+        );
+        node.max_linear_velocity = config.linear_velocity;
+        node.max_angular_velocity = config.angular_velocity;
+        node.step_duration = Duration::from_secs_f64(config.step_duration_secs);
+        node.damping = config.damping;
+        node.posture_gain = config.posture_gain;
+        node.turbo_gain = config.turbo_gain;
+        node.with_bindings(config.bindings)
+    }
+    pub fn with_bindings(mut self, bindings: WholeBodyBindings) -> Self {
+        self.bindings = bindings;
+        self
+    }
+
+    fn stop_base(&self) {
+        *self.base_velocity.lock().unwrap() = BaseVelocity::default();
+        if let Err(e) = self.move_base.send_velocity(&BaseVelocity::default()) {
+            error!("Failed to stop the base: {}", e);
+        }
+    }
+
+    /// Moves the arm by `velocity` for a step, and returns the horizontal velocity the arm does
+    /// not follow.
+    async fn move_arm(&self, velocity: &Vector3<f64>) -> Result<Vector2<f64>, arci::Error> {
+        let current_positions = self.joint_trajectory_client.current_joint_positions()?;
+        self.ik_solver_with_chain
+            .set_joint_positions_clamped(&current_positions);
+        let positions = DVector::from_vec(self.ik_solver_with_chain.joint_positions());
+        let current_pose = self.ik_solver_with_chain.end_transform();
+        let current_height = current_pose.translation.z;
+        let dt = self.step_duration.as_secs_f64();
+        let (twist, posture_velocities) = {
+            let mut arm_target = self.arm_target.lock().unwrap();
+            let arm_target = arm_target.get_or_insert_with(|| ArmTarget {
+                joint_positions: positions.clone(),
+                rotation: current_pose.rotation,
+                height: current_height,
+            });
+            // The base does not follow the vertical velocity, so the height is kept by the arm
+            // even if the arm does not follow the velocity on the way.
+            let max_offset = velocity.z.abs().max(self.max_linear_velocity[2]) * dt;
+            arm_target.height = (arm_target.height + velocity.z * dt)
+                .clamp(current_height - max_offset, current_height + max_offset);
+            let angular_velocity =
+                (arm_target.rotation * current_pose.rotation.inverse()).scaled_axis() / dt;
+            let twist = Vector6::new(
+                velocity.x,
+                velocity.y,
+                (arm_target.height - current_height) / dt,
+                angular_velocity.x,
+                angular_velocity.y,
+                angular_velocity.z,
+            );
+            (
+                twist,
+                (&arm_target.joint_positions - &positions) * self.posture_gain,
+            )
+        };
+        let jacobian = self.ik_solver_with_chain.jacobian();
+        let joint_velocities =
+            arm_joint_velocities(&jacobian, &twist, &posture_velocities, self.damping);
+        self.ik_solver_with_chain
+            .set_joint_positions_clamped((&positions + joint_velocities * dt).as_slice());
+        let target_positions = self.ik_solver_with_chain.joint_positions();
+        // Velocity of the end effector by the joint velocities after clamped by the limits.
+        let arm_velocity =
+            jacobian * (DVector::from_column_slice(&target_positions) - positions) / dt;
+        self.joint_trajectory_client
+            .send_joint_positions(target_positions, self.step_duration)
+            .await?;
+        Ok(velocity.xy() - Vector2::new(arm_velocity[0], arm_velocity[1]))
+    }
+}
+
+/// Targets of the arm of `WholeBodyNode`.
+struct ArmTarget {
+    /// Joint positions to which the joints move back in the null space.
+    joint_positions: DVector<f64>,
+    /// Orientation of the end effector to keep.
+    rotation: UnitQuaternion<f64>,
+    /// Height of the end effector, which is moved by the vertical velocity.
+    height: f64,
+}
+
+/// Joint velocities to move the end link by `twist` (the linear and the angular velocity), plus
+/// `posture_velocities` projected onto the null space of the Jacobian.
+///
+/// The vertical and the angular velocity have priority over the horizontal velocity, which the
+/// base can take instead. The horizontal velocity is followed within the null space of them.
+fn arm_joint_velocities(
+    jacobian: &Matrix6xX<f64>,
+    twist: &Vector6<f64>,
+    posture_velocities: &DVector<f64>,
+    damping: f64,
+) -> DVector<f64> {
+    let dof = jacobian.ncols();
+    let jacobian = DMatrix::from_column_slice(6, dof, jacobian.as_slice());
+    let twist = DVector::from_column_slice(twist.as_slice());
+
+    let primary = jacobian.rows(2, 4).into_owned();
+    let primary_inverse = damped_pseudo_inverse(&primary, damping);
+    let primary_null_space = DMatrix::identity(dof, dof) - &primary_inverse * &primary;
+    let velocities = &primary_inverse * twist.rows(2, 4);
+
+    let horizontal = jacobian.rows(0, 2) * &primary_null_space;
+    let horizontal_inverse = damped_pseudo_inverse(&horizontal, damping);
+    let velocities =
+        &velocities + &horizontal_inverse * (twist.rows(0, 2) - jacobian.rows(0, 2) * &velocities);
+
+    let null_space = primary_null_space - &horizontal_inverse * &horizontal;
+    velocities + null_space * posture_velocities
+}
+
+/// Pseudo-inverse of `matrix`, which is damped only while the smallest singular value is smaller
+/// than `damping`, so the solution is exact away from the singularities.
+fn damped_pseudo_inverse(matrix: &DMatrix<f64>, damping: f64) -> DMatrix<f64> {
+    let svd = matrix.clone().svd(true, true);
+    let (u, v_t) = match (svd.u, svd.v_t) {
+        (Some(u), Some(v_t)) => (u, v_t),
+        _ => return DMatrix::zeros(matrix.ncols(), matrix.nrows()),
+    };
+    let damping_squared = (damping.powi(2) - svd.singular_values.min().powi(2)).max(0.0);
+    let inverse_values = svd.singular_values.map(|value| {
+        let denominator = value.powi(2) + damping_squared;
+        if denominator > f64::EPSILON {
+            value / denominator
+        } else {
+            0.0
+        }
+    });
+    v_t.transpose() * DMatrix::from_diagonal(&inverse_values) * u.transpose()
+}
+
+#[async_trait]
+impl<J, M> ControlNode for WholeBodyNode<J, M>
+where
+    J: JointTrajectoryClient,
+    M: MoveBase,
+{
+    fn set_event(&mut self, event: GamepadEvent) {
+        let bindings = &self.bindings;
+        match event {
+            GamepadEvent::ButtonPressed(b) if b == bindings.enable => {
+                self.is_enabled = true;
+                *self.arm_target.lock().unwrap() = None;
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.enable => {
+                self.is_enabled = false;
+                self.linear_velocity = Vector3::zeros();
+                self.angular_velocity = 0.0;
+                // stop immediately
+                self.stop_base();
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.turbo => {
+                self.is_turbo = true;
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.turbo => {
+                self.is_turbo = false;
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.up => {
+                self.linear_velocity.z = self.max_linear_velocity[2];
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.up => {
+                self.linear_velocity.z = 0.0;
+            }
+            GamepadEvent::ButtonPressed(b) if b == bindings.down => {
+                self.linear_velocity.z = -self.max_linear_velocity[2];
+            }
+            GamepadEvent::ButtonReleased(b) if b == bindings.down => {
+                self.linear_velocity.z = 0.0;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.x => {
+                self.linear_velocity.x = self.max_linear_velocity[0] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.y => {
+                self.linear_velocity.y = self.max_linear_velocity[1] * v;
+            }
+            GamepadEvent::AxisChanged(a, v) if a == bindings.angular_z => {
+                self.angular_velocity = self.max_angular_velocity * v;
+            }
+            _ => {}
+        }
+    }
+    async fn proc(&self) {
+        if !self.is_enabled {
+            return;
+        }
+        let gain = if self.is_turbo { self.turbo_gain } else { 1.0 };
+        let velocity = self.linear_velocity * gain;
+        let mut base_velocity = Vector2::zeros();
+        if velocity != Vector3::zeros() {
+            match self.move_arm(&velocity).await {
+                Ok(velocity) => {
+                    // The base moves as fast as the end effector at most.
+                    base_velocity.x = velocity.x.clamp(
+                        -self.max_linear_velocity[0] * gain,
+                        self.max_linear_velocity[0] * gain,
+                    );
+                    base_velocity.y = velocity.y.clamp(
+                        -self.max_linear_velocity[1] * gain,
+                        self.max_linear_velocity[1] * gain,
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to move the arm: {}", e);
+                    self.rumble.request(Rumble::failure());
+                    self.stop_base();
+                    return;
+                }
+            }
+        }
+        let base_velocity = BaseVelocity::new(
+            base_velocity.x,
+            base_velocity.y,
+            self.angular_velocity * gain,
+        );
+        *self.base_velocity.lock().unwrap() = base_velocity;
+        if let Err(e) = self.move_base.send_velocity(&base_velocity) {
+            error!("Failed to send velocity: {}", e);
+        }
+    }
+    fn mode(&self) -> &str {
+        &self.mode
+    }
+    fn submode(&self) -> &str {
+        &self.submode
+    }
+    async fn stop(&mut self) {
+        self.is_enabled = false;
+        self.is_turbo = false;
+        self.linear_velocity = Vector3::zeros();
+        self.angular_velocity = 0.0;
+        self.stop_base();
+        stop_joint_trajectory_client(&self.joint_trajectory_client, self.step_duration).await;
+    }
+    fn take_rumble(&self) -> Option<Rumble> {
+        self.rumble.take()
+    }
+    fn status(&self) -> ControlNodeStatus {
+        let joints = joint_states(&self.joint_trajectory_client);
+        let positions: Vec<_> = joints.iter().map(|(_, position)| *position).collect();
+        let ik_target = if positions.is_empty() {
+            None
+        } else {
+            self.ik_solver_with_chain
+                .set_joint_positions_clamped(&positions);
+            Some(self.ik_solver_with_chain.end_transform())
+        };
+        ControlNodeStatus {
+            joints,
+            base_velocity: Some(*self.base_velocity.lock().unwrap()),
+            ik_target,
+            legend: self.bindings.legend(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WholeBodyNodeConfig {
+    #[serde(default = "default_mode")]
+    pub mode: String,
+    /// Maximum velocity [m/s] of the end effector in x, y and z.
+    #[serde(default = "default_linear_velocity")]
+    pub linear_velocity: [f64; 3],
+    /// Maximum angular velocity [rad/s] of the base.
+    #[serde(default = "default_angular_velocity")]
+    pub angular_velocity: f64,
+    #[serde(default = "default_step_duration_secs")]
+    pub step_duration_secs: f64,
+    #[serde(default = "default_turbo_gain")]
+    pub turbo_gain: f64,
+    /// The arm follows the velocity exactly while the smallest singular value of the Jacobian is
+    /// larger than this. The larger it is, the earlier the base takes over the velocity before the
+    /// arm reaches its limit.
+    #[serde(default = "default_damping")]
+    pub damping: f64,
+    /// Gain [1/s] to move the joints back to the positions where `enable` is pressed, within the
+    /// null space of the arm.
+    #[serde(default = "default_posture_gain")]
+    pub posture_gain: f64,
+    #[serde(default)]
+    pub bindings: WholeBodyBindings,
+}
+
+fn default_mode() -> String {
+    "whole_body".to_string()
+}
+
+fn default_linear_velocity() -> [f64; 3] {
+    [0.1, 0.1, 0.05]
+}
+
+fn default_angular_velocity() -> f64 {
+    0.5
+}
+
+fn default_step_duration_secs() -> f64 {
+    0.1
+}
+
+fn default_turbo_gain() -> f64 {
+    WHOLE_BODY_TURBO_GAIN
+}
+
+fn default_damping() -> f64 {
+    0.05
+}
+
+fn default_posture_gain() -> f64 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    fn assert_vector_eq(a: &DVector<f64>, b: &DVector<f64>) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert_approx_eq!(a, b, 1e-3);
+        }
+    }
+
+    fn twist() -> Vector6<f64> {
+        Vector6::new(0.1, 0.2, 0.3, 0.0, 0.0, 0.1)
+    }
+
+    #[test]
+    fn test_arm_joint_velocities() {
+        let jacobian = Matrix6xX::identity(6);
+        let velocities =
+            arm_joint_velocities(&jacobian, &twist(), &DVector::from_element(6, 1.0), 0.01);
+        // There is no null space.
+        assert_vector_eq(
+            &DVector::from_column_slice((jacobian * velocities).as_slice()),
+            &DVector::from_column_slice(twist().as_slice()),
+        );
+    }
+
+    #[test]
+    fn test_arm_joint_velocities_at_singularity() {
+        // The first joint cannot move the end link.
+        let mut jacobian = Matrix6xX::identity(6);
+        jacobian[(0, 0)] = 0.0;
+        let velocities =
+            arm_joint_velocities(&jacobian, &twist(), &DVector::from_element(6, 1.0), 0.01);
+        // The arm does not follow the velocity in the singular direction, and the first joint
+        // moves in the null space.
+        let arm_twist = &jacobian * &velocities;
+        assert_approx_eq!(arm_twist[0], 0.0);
+        assert_vector_eq(
+            &DVector::from_column_slice(&arm_twist.as_slice()[1..]),
+            &DVector::from_column_slice(&twist().as_slice()[1..]),
+        );
+        assert_approx_eq!(velocities[0], 1.0, 1e-3);
+    }
+
+    #[test]
+    fn test_arm_joint_velocities_keep_vertical() {
+        // Only the first joint moves the end link in z, which also moves it in x.
+        let mut jacobian = Matrix6xX::identity(6);
+        jacobian[(2, 0)] = 1.0;
+        jacobian[(2, 2)] = 0.0;
+        let velocities = arm_joint_velocities(&jacobian, &twist(), &DVector::zeros(6), 0.01);
+        // The vertical velocity is followed instead of the horizontal one.
+        let arm_twist = &jacobian * &velocities;
+        assert_approx_eq!(arm_twist[0], 0.3, 1e-3);
+        assert_vector_eq(
+            &DVector::from_column_slice(&arm_twist.as_slice()[1..]),
+            &DVector::from_column_slice(&twist().as_slice()[1..]),
+        );
+    }
+
+    #[test]
+    fn test_arm_joint_velocities_with_redundancy() {
+        // The seventh joint moves the end link in x as the first one.
+        let mut jacobian = Matrix6xX::zeros(7);
+        for i in 0..6 {
+            jacobian[(i, i)] = 1.0;
+        }
+        jacobian[(0, 6)] = 1.0;
+        let posture_velocities = DVector::from_column_slice(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, -1.0]);
+        let velocities = arm_joint_velocities(&jacobian, &twist(), &posture_velocities, 0.01);
+        assert_vector_eq(
+            &DVector::from_column_slice((&jacobian * &velocities).as_slice()),
+            &DVector::from_column_slice(twist().as_slice()),
+        );
+        // The posture velocities are in the null space.
+        assert_vector_eq(
+            &velocities,
+            &DVector::from_column_slice(&[1.05, 0.2, 0.3, 0.0, 0.0, 0.1, -0.95]),
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use arci::{
+    gamepad::{Button, GamepadEvent},
+    DummyJointTrajectoryClient, DummySpeaker, JointTrajectoryClient, Localization, MoveBase,
+    Navigation,
+};
+use openrr_client::{ArcRobotClient, JointsPose, OpenrrClientsConfig, RobotClient};
+use openrr_teleop::{ControlNode, JointsPoseSender, JoyJointTeleopNode};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+struct Robot {
+    arm: Arc<DummyJointTrajectoryClient>,
+    speaker: Arc<DummySpeaker>,
+    client: Arc<ArcRobotClient>,
+}
+
+impl Robot {
+    fn new() -> Self {
+        let arm = Arc::new(DummyJointTrajectoryClient::new(vec![
+            "j0".to_owned(),
+            "j1".to_owned(),
+        ]));
+        let mut config = OpenrrClientsConfig::default();
+        config.joints_poses = vec![zero_pose()];
+        let mut raw_joint_trajectory_clients = HashMap::new();
+        raw_joint_trajectory_clients.insert(
+            "arm".to_owned(),
+            arm.clone() as Arc<dyn JointTrajectoryClient>,
+        );
+        let client = RobotClient::try_new(
+            config,
+            raw_joint_trajectory_clients,
+            HashMap::new(),
+            None::<Arc<dyn Localization>>,
+            None::<Arc<dyn MoveBase>>,
+            None::<Arc<dyn Navigation>>,
+            None,
+        )
+        .unwrap();
+        Self {
+            arm,
+            speaker: Arc::new(DummySpeaker::new()),
+            client: Arc::new(client),
+        }
+    }
+
+    fn joy_joint_node(
+        &self,
+    ) -> JoyJointTeleopNode<Arc<DummyJointTrajectoryClient>, Arc<DummySpeaker>> {
+        JoyJointTeleopNode::new(
+            "arm".to_owned(),
+            self.arm.clone(),
+            0.1,
+            Duration::from_millis(100),
+            self.speaker.clone(),
+        )
+        .with_robot_client("arm".to_owned(), self.client.clone())
+    }
+
+    fn joints_pose_sender(
+        &self,
+    ) -> JointsPoseSender<Arc<DummySpeaker>, Arc<DummyJointTrajectoryClient>> {
+        let mut clients = HashMap::new();
+        clients.insert("arm".to_owned(), self.arm.clone());
+        JointsPoseSender::new(
+            "pose".to_owned(),
+            vec![zero_pose()],
+            clients,
+            self.speaker.clone(),
+            Duration::from_millis(100),
+        )
+        .with_robot_client(self.client.clone())
+    }
+
+    async fn move_arm(&self, positions: Vec<f64>) {
+        self.arm
+            .send_joint_positions(positions, Duration::default())
+            .await
+            .unwrap();
+    }
+}
+
+fn zero_pose() -> JointsPose {
+    JointsPose {
+        pose_name: "zero".to_owned(),
+        client_name: "arm".to_owned(),
+        positions: vec![0.0, 0.0],
+    }
+}
+
+fn press(node: &mut impl ControlNode, button: Button) {
+    node.set_event(GamepadEvent::ButtonPressed(button));
+    node.set_event(GamepadEvent::ButtonReleased(button));
+}
+
+#[tokio::test]
+async fn test_save_pose() {
+    let robot = Robot::new();
+    let mut joy_joint_node = robot.joy_joint_node();
+    let mut sender = robot.joints_pose_sender();
+
+    // The joint mode saves a new pose only while holding the deadman button.
+    robot.move_arm(vec![0.1, 0.2]).await;
+    press(&mut joy_joint_node, Button::DPadUp);
+    assert_eq!(robot.client.joints_poses_names("arm"), vec!["zero"]);
+    joy_joint_node.set_event(GamepadEvent::ButtonPressed(Button::RightTrigger2));
+    press(&mut joy_joint_node, Button::DPadUp);
+    joy_joint_node.set_event(GamepadEvent::ButtonReleased(Button::RightTrigger2));
+    assert_eq!(
+        robot.client.joints_poses_names("arm"),
+        vec!["saved_0", "zero"]
+    );
+    assert_eq!(robot.speaker.messages().last().unwrap(), "saved saved_0");
+
+    // The pose mode selects the pose saved by the joint mode.
+    press(&mut sender, Button::East);
+    assert_eq!(sender.submode(), " arm saved_0");
+
+    // The pose mode overwrites the selected pose only while holding the deadman button.
+    robot.move_arm(vec![0.3, 0.4]).await;
+    press(&mut sender, Button::DPadUp);
+    let saved_positions = |client: &ArcRobotClient| {
+        client
+            .joints_poses()
+            .into_iter()
+            .find(|pose| pose.pose_name == "saved_0")
+            .unwrap()
+            .positions
+    };
+    assert_eq!(saved_positions(&robot.client), vec![0.1, 0.2]);
+    sender.set_event(GamepadEvent::ButtonPressed(Button::RightTrigger2));
+    press(&mut sender, Button::DPadUp);
+    assert_eq!(saved_positions(&robot.client), vec![0.3, 0.4]);
+
+    // The saved pose is sent by the pose mode.
+    robot.move_arm(vec![0.0, 0.0]).await;
+    sender.set_event(GamepadEvent::ButtonPressed(Button::West));
+    sender.proc().await;
+    assert_eq!(robot.arm.current_joint_positions().unwrap(), vec![0.3, 0.4]);
+}
@@ -0,0 +1,99 @@
+use arci::{BatteryState, ControllerMode, DummyRobotStatus, DummySpeaker, Fault, RobotStatus};
+use openrr_teleop::RobotStatusAlert;
+use std::{sync::Arc, time::Duration};
+
+const CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+fn alert<R: RobotStatus>(
+    robot_status: R,
+    speaker: Arc<DummySpeaker>,
+) -> Arc<RobotStatusAlert<R, Arc<DummySpeaker>>> {
+    Arc::new(RobotStatusAlert::new(
+        robot_status,
+        speaker,
+        0.2,
+        CHECK_INTERVAL,
+    ))
+}
+
+#[tokio::test]
+async fn test_battery_and_emergency_stop() {
+    let robot_status = Arc::new(DummyRobotStatus::new());
+    let speaker = Arc::new(DummySpeaker::new());
+    let alert = alert(robot_status.clone(), speaker.clone());
+    let alert_cloned = alert.clone();
+    let handle = tokio::spawn(async move { alert_cloned.main().await });
+
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    assert!(speaker.messages().is_empty());
+
+    // Low battery is alerted once, and not while charging.
+    robot_status.battery_state.lock().unwrap().is_charging = true;
+    robot_status.battery_state.lock().unwrap().percentage = Some(0.1);
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    assert!(speaker.messages().is_empty());
+    robot_status.battery_state.lock().unwrap().is_charging = false;
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    assert_eq!(speaker.messages(), vec!["Battery low"]);
+
+    *robot_status.is_emergency_stopped.lock().unwrap() = true;
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    *robot_status.is_emergency_stopped.lock().unwrap() = false;
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    assert_eq!(
+        speaker.messages(),
+        vec!["Battery low", "Emergency stop", "Emergency stop released"]
+    );
+
+    alert.stop();
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_stop_before_main() {
+    let alert = alert(DummyRobotStatus::new(), Arc::new(DummySpeaker::new()));
+    alert.stop();
+    tokio::time::timeout(Duration::from_secs(1), alert.main())
+        .await
+        .unwrap();
+}
+
+/// RobotStatus whose topics are not available.
+struct UnavailableRobotStatus;
+
+impl RobotStatus for UnavailableRobotStatus {
+    fn battery_state(&self) -> Result<BatteryState, arci::Error> {
+        Err(arci::Error::Uninitialized {
+            message: "no battery state".to_owned(),
+        })
+    }
+    fn is_emergency_stopped(&self) -> Result<bool, arci::Error> {
+        Err(arci::Error::Uninitialized {
+            message: "no emergency stop state".to_owned(),
+        })
+    }
+    fn faults(&self) -> Result<Vec<Fault>, arci::Error> {
+        Ok(vec![])
+    }
+    fn controller_mode(&self) -> Result<ControllerMode, arci::Error> {
+        Ok(ControllerMode::Unknown)
+    }
+}
+
+#[tokio::test]
+async fn test_unavailable_status() {
+    let speaker = Arc::new(DummySpeaker::new());
+    let alert = alert(UnavailableRobotStatus, speaker.clone());
+    let alert_cloned = alert.clone();
+    let handle = tokio::spawn(async move { alert_cloned.main().await });
+    tokio::time::sleep(CHECK_INTERVAL * 5).await;
+    alert.stop();
+    tokio::time::timeout(Duration::from_secs(1), handle)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(speaker.messages().is_empty());
+}
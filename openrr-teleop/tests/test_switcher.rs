@@ -0,0 +1,309 @@
+use arci::{
+    gamepad::{Axis, Button, GamepadEvent},
+    BaseVelocity, DummyJointTrajectoryClient, DummyMoveBase, DummySpeaker, GamepadScript,
+    JointTrajectoryClient, MoveBase, ScriptedGamepad, TrajectoryPoint,
+};
+use assert_approx_eq::assert_approx_eq;
+use async_trait::async_trait;
+use openrr_teleop::{ControlNode, ControlNodeSwitcher, JoyJointTeleopNode, MoveBaseNode, Rumble};
+use std::{sync::Arc, time::Duration};
+
+struct Robot {
+    base: Arc<DummyMoveBase>,
+    arm: Arc<DummyJointTrajectoryClient>,
+    speaker: Arc<DummySpeaker>,
+}
+
+impl Robot {
+    fn new() -> Self {
+        Self {
+            base: Arc::new(DummyMoveBase::new()),
+            arm: Arc::new(DummyJointTrajectoryClient::new(vec![
+                "j0".to_owned(),
+                "j1".to_owned(),
+            ])),
+            speaker: Arc::new(DummySpeaker::new()),
+        }
+    }
+
+    /// Switcher of "base" and "arm" nodes.
+    fn switcher(
+        &self,
+        deadman_timeout: Option<Duration>,
+    ) -> ControlNodeSwitcher<Box<dyn ControlNode>, Arc<DummySpeaker>> {
+        let nodes: Vec<Box<dyn ControlNode>> = vec![
+            Box::new(MoveBaseNode::new("base".to_owned(), self.base.clone())),
+            Box::new(JoyJointTeleopNode::new(
+                "arm".to_owned(),
+                self.arm.clone(),
+                0.1,
+                Duration::from_millis(100),
+                self.speaker.clone(),
+            )),
+        ];
+        let switcher = ControlNodeSwitcher::new(nodes, self.speaker.clone(), 0);
+        match deadman_timeout {
+            Some(deadman_timeout) => switcher.with_deadman_timeout(deadman_timeout),
+            None => switcher,
+        }
+    }
+
+    /// Runs the switcher with the script until the end of it.
+    async fn run(
+        &self,
+        script: GamepadScript,
+        deadman_timeout: Option<Duration>,
+    ) -> Arc<ScriptedGamepad> {
+        let gamepad = Arc::new(ScriptedGamepad::new(script));
+        self.switcher(deadman_timeout).main(gamepad.clone()).await;
+        gamepad
+    }
+
+    /// Base velocity after `delay`.
+    async fn base_velocity_after(&self, delay: Duration) -> BaseVelocity {
+        tokio::time::sleep(delay).await;
+        self.base.current_velocity().unwrap()
+    }
+}
+
+async fn run(script: GamepadScript) -> Robot {
+    let robot = Robot::new();
+    robot.run(script, None).await;
+    robot
+}
+
+#[tokio::test]
+async fn test_move_base() {
+    let robot = Robot::new();
+    let (_, vel) = tokio::join!(
+        robot.run(
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .axis(Axis::LeftStickY, 1.0)
+                .axis(Axis::RightStickX, -0.5)
+                .wait(Duration::from_millis(300)),
+            None,
+        ),
+        robot.base_velocity_after(Duration::from_millis(200)),
+    );
+    assert_approx_eq!(vel.x, 0.5);
+    assert_approx_eq!(vel.y, 0.0);
+    assert_approx_eq!(vel.theta, -0.75);
+    assert_eq!(robot.speaker.messages(), vec!["base"]);
+    // The base is stopped on shutdown.
+    let vel = robot.base.current_velocity().unwrap();
+    assert_approx_eq!(vel.x, 0.0);
+    assert_approx_eq!(vel.theta, 0.0);
+}
+
+#[tokio::test]
+async fn test_move_base_deadman() {
+    // Without the deadman button, nothing is sent.
+    let robot = run(GamepadScript::new()
+        .axis(Axis::LeftStickY, 1.0)
+        .wait(Duration::from_millis(300)))
+    .await;
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0);
+
+    // Releasing the deadman button stops the base immediately.
+    let robot = run(GamepadScript::new()
+        .press(Button::RightTrigger2)
+        .axis(Axis::LeftStickY, 1.0)
+        .wait(Duration::from_millis(300))
+        .release(Button::RightTrigger2)
+        .wait(Duration::from_millis(200)))
+    .await;
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0);
+}
+
+#[tokio::test]
+async fn test_switch_mode_and_move_joint() {
+    let robot = run(GamepadScript::new()
+        .press(Button::North)
+        .release(Button::North)
+        .wait(Duration::from_millis(100))
+        .press(Button::East)
+        .release(Button::East)
+        .press(Button::RightTrigger2)
+        .press(Button::West)
+        .wait(Duration::from_millis(300))
+        .release(Button::West)
+        .release(Button::RightTrigger2)
+        .wait(Duration::from_millis(100)))
+    .await;
+    assert_eq!(robot.speaker.messages(), vec!["base", "arm0", "arm1"]);
+    let positions = robot.arm.positions.lock().unwrap().clone();
+    assert_approx_eq!(positions[0], 0.0);
+    assert!(positions[1] > 0.1, "{:?}", positions);
+    // The base is not moved in the arm mode.
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0);
+}
+
+#[tokio::test]
+async fn test_stop_on_mode_switch() {
+    let robot = Robot::new();
+    let (gamepad, vel) = tokio::join!(
+        robot.run(
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .axis(Axis::LeftStickY, 1.0)
+                .wait(Duration::from_millis(200))
+                .press(Button::North)
+                .release(Button::North)
+                .wait(Duration::from_millis(300)),
+            None,
+        ),
+        robot.base_velocity_after(Duration::from_millis(400)),
+    );
+    assert_approx_eq!(vel.x, 0.0);
+    assert_eq!(robot.speaker.messages(), vec!["base", "arm0"]);
+    let mode_switch = Rumble::mode_switch();
+    assert_eq!(
+        gamepad.rumbles(),
+        vec![(mode_switch.intensity, mode_switch.duration)]
+    );
+}
+
+#[tokio::test]
+async fn test_stop_on_disconnect() {
+    let robot = Robot::new();
+    let (_, vel) = tokio::join!(
+        robot.run(
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .axis(Axis::LeftStickY, 1.0)
+                .wait(Duration::from_millis(200))
+                .event(GamepadEvent::Disconnected)
+                .wait(Duration::from_millis(300))
+                .event(GamepadEvent::Connected)
+                // The deadman button must be pressed again after the reconnection.
+                .axis(Axis::LeftStickY, 1.0)
+                .wait(Duration::from_millis(200)),
+            None,
+        ),
+        async {
+            (
+                robot.base_velocity_after(Duration::from_millis(400)).await,
+                robot.base_velocity_after(Duration::from_millis(250)).await,
+            )
+        },
+    );
+    assert_approx_eq!(vel.0.x, 0.0);
+    assert_approx_eq!(vel.1.x, 0.0);
+    assert_eq!(
+        robot.speaker.messages(),
+        vec!["base", "gamepad disconnected", "gamepad connected"]
+    );
+}
+
+#[tokio::test]
+async fn test_deadman_timeout() {
+    let robot = Robot::new();
+    let (_, vel) = tokio::join!(
+        robot.run(
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .axis(Axis::LeftStickY, 1.0)
+                .wait(Duration::from_millis(600)),
+            Some(Duration::from_millis(300)),
+        ),
+        async {
+            (
+                robot.base_velocity_after(Duration::from_millis(150)).await,
+                robot.base_velocity_after(Duration::from_millis(300)).await,
+            )
+        },
+    );
+    assert_approx_eq!(vel.0.x, 0.5);
+    assert_approx_eq!(vel.1.x, 0.0);
+}
+
+#[tokio::test]
+async fn test_status() {
+    let robot = Robot::new();
+    let switcher = robot.switcher(None);
+    let status = switcher.status().await;
+    assert_eq!(status.modes, vec!["base", "arm"]);
+    assert_eq!(status.current_index, 0);
+    assert!(status.node.joints.is_empty());
+    assert_approx_eq!(status.node.base_velocity.unwrap().x, 0.0);
+    assert!(status
+        .node
+        .legend
+        .contains(&("RightTrigger2".to_owned(), "enable".to_owned())));
+    let text = status.to_string();
+    assert!(text.contains("Mode: base (1/2)"), "{}", text);
+    assert!(text.contains("Base velocity"), "{}", text);
+
+    switcher.increment_mode().await;
+    let status = switcher.status().await;
+    assert_eq!(status.current_index, 1);
+    assert_eq!(
+        status.node.joints,
+        vec![("j0".to_owned(), 0.0), ("j1".to_owned(), 0.0)]
+    );
+    assert!(status.node.base_velocity.is_none());
+    let text = status.to_string();
+    assert!(text.contains("Mode: arm0 (2/2)"), "{}", text);
+    assert!(text.contains("North"), "{}", text);
+    assert!(text.contains("next_joint"), "{}", text);
+}
+
+/// Client which rejects all the commands, like `CollisionCheckClient` in collision.
+struct RejectingClient(DummyJointTrajectoryClient);
+
+#[async_trait]
+impl JointTrajectoryClient for RejectingClient {
+    fn joint_names(&self) -> &[String] {
+        self.0.joint_names()
+    }
+    fn current_joint_positions(&self) -> Result<Vec<f64>, arci::Error> {
+        self.0.current_joint_positions()
+    }
+    async fn send_joint_positions(
+        &self,
+        _positions: Vec<f64>,
+        _duration: Duration,
+    ) -> Result<(), arci::Error> {
+        Err(arci::Error::CollisionError(
+            "j0".to_owned(),
+            "j1".to_owned(),
+        ))
+    }
+    async fn send_joint_trajectory(
+        &self,
+        _trajectory: Vec<TrajectoryPoint>,
+    ) -> Result<(), arci::Error> {
+        Err(arci::Error::CollisionError(
+            "j0".to_owned(),
+            "j1".to_owned(),
+        ))
+    }
+}
+
+#[tokio::test]
+async fn test_rumble_on_rejected_command() {
+    let speaker = Arc::new(DummySpeaker::new());
+    let nodes: Vec<Box<dyn ControlNode>> = vec![Box::new(JoyJointTeleopNode::new(
+        "arm".to_owned(),
+        RejectingClient(DummyJointTrajectoryClient::new(vec!["j0".to_owned()])),
+        0.1,
+        Duration::from_millis(100),
+        speaker.clone(),
+    ))];
+    let gamepad = Arc::new(ScriptedGamepad::new(
+        GamepadScript::new()
+            .press(Button::RightTrigger2)
+            .press(Button::West)
+            .wait(Duration::from_millis(200)),
+    ));
+    ControlNodeSwitcher::new(nodes, speaker, 0)
+        .main(gamepad.clone())
+        .await;
+    let failure = Rumble::failure();
+    let rumbles = gamepad.rumbles();
+    assert!(!rumbles.is_empty());
+    assert!(rumbles
+        .iter()
+        .all(|rumble| *rumble == (failure.intensity, failure.duration)));
+}
@@ -0,0 +1,189 @@
+use arci::{
+    gamepad::{Axis, Button},
+    DummyJointTrajectoryClient, DummySpeaker, GamepadScript, JointTrajectoryClient,
+    ScriptedGamepad,
+};
+use assert_approx_eq::assert_approx_eq;
+use openrr_client::{RecordEvent, RecordedTrajectoryPoint, Recorder};
+use openrr_teleop::{
+    ControlNode, ControlNodeSwitcher, Error, TrajectoryPlaybackBindings, TrajectoryPlaybackNode,
+    TrajectoryPlaybackNodeConfig,
+};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+/// Writes the record of the trajectory of "arm" from [0, 0] to [0.2, 0.1] in 1 second.
+fn write_record(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("openrr_teleop_test_{}.jsonl", name));
+    // The recorder appends the records to the existing file.
+    let _ = std::fs::remove_file(&path);
+    let point = |positions: Vec<f64>, time_from_start_sec| RecordedTrajectoryPoint {
+        positions,
+        velocities: None,
+        time_from_start_sec,
+    };
+    Recorder::create(&path)
+        .unwrap()
+        .record(RecordEvent::SendJointTrajectory {
+            client: "arm".to_owned(),
+            trajectory: vec![
+                point(vec![0.0, 0.0], 0.0),
+                point(vec![0.1, 0.0], 0.5),
+                point(vec![0.2, 0.1], 1.0),
+            ],
+        });
+    path
+}
+
+fn config(record_path: PathBuf) -> TrajectoryPlaybackNodeConfig {
+    TrajectoryPlaybackNodeConfig {
+        mode: "playback".to_owned(),
+        record_path,
+        record_client_name: "arm".to_owned(),
+        speed: 2.0,
+        scrub_speed: 2.0,
+        max_joint_velocity: 0.5,
+        step_duration_secs: 0.1,
+        bindings: TrajectoryPlaybackBindings::default(),
+    }
+}
+
+struct Robot {
+    arm: Arc<DummyJointTrajectoryClient>,
+    speaker: Arc<DummySpeaker>,
+}
+
+impl Robot {
+    fn new() -> Self {
+        Self {
+            arm: Arc::new(DummyJointTrajectoryClient::new(vec![
+                "j0".to_owned(),
+                "j1".to_owned(),
+            ])),
+            speaker: Arc::new(DummySpeaker::new()),
+        }
+    }
+
+    async fn run(&self, name: &str, script: GamepadScript) {
+        let node = TrajectoryPlaybackNode::new_from_config(
+            config(write_record(name)),
+            self.arm.clone(),
+            self.speaker.clone(),
+        )
+        .unwrap();
+        let nodes: Vec<Box<dyn ControlNode>> = vec![Box::new(node)];
+        ControlNodeSwitcher::new(nodes, self.speaker.clone(), 0)
+            .main(Arc::new(ScriptedGamepad::new(script)))
+            .await;
+    }
+
+    /// Joint positions after `delay`.
+    async fn positions_after(&self, delay: Duration) -> Vec<f64> {
+        tokio::time::sleep(delay).await;
+        self.arm.current_joint_positions().unwrap()
+    }
+}
+
+#[tokio::test]
+async fn test_play() {
+    let robot = Robot::new();
+    robot
+        .run(
+            "play",
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .press(Button::South)
+                .release(Button::South)
+                .wait(Duration::from_millis(1000)),
+        )
+        .await;
+    let positions = robot.arm.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 0.2);
+    assert_approx_eq!(positions[1], 0.1);
+    assert_eq!(robot.speaker.messages(), vec!["playback", "play", "end"]);
+}
+
+#[tokio::test]
+async fn test_step() {
+    let robot = Robot::new();
+    robot
+        .run(
+            "step",
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .press(Button::DPadRight)
+                .release(Button::DPadRight)
+                .press(Button::DPadRight)
+                .release(Button::DPadRight)
+                .press(Button::DPadLeft)
+                .release(Button::DPadLeft)
+                .wait(Duration::from_millis(300)),
+        )
+        .await;
+    let positions = robot.arm.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 0.1);
+    assert_approx_eq!(positions[1], 0.0);
+}
+
+#[tokio::test]
+async fn test_scrub() {
+    let robot = Robot::new();
+    let (_, positions) = tokio::join!(
+        robot.run(
+            "scrub",
+            GamepadScript::new()
+                .press(Button::RightTrigger2)
+                .axis(Axis::RightStickX, 1.0)
+                .wait(Duration::from_millis(300))
+                .axis(Axis::RightStickX, -1.0)
+                .wait(Duration::from_millis(600)),
+        ),
+        robot.positions_after(Duration::from_millis(250)),
+    );
+    assert!(positions[0] > 0.0 && positions[0] < 0.2, "{:?}", positions);
+    // Scrubbed back to the start.
+    let positions = robot.arm.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 0.0);
+    assert_approx_eq!(positions[1], 0.0);
+}
+
+#[tokio::test]
+async fn test_deadman() {
+    let robot = Robot::new();
+    robot
+        .run(
+            "deadman",
+            GamepadScript::new()
+                .press(Button::DPadRight)
+                .release(Button::DPadRight)
+                .press(Button::South)
+                .release(Button::South)
+                .press(Button::RightTrigger2)
+                .wait(Duration::from_millis(300)),
+        )
+        .await;
+    // The buttons are ignored without the deadman button.
+    let positions = robot.arm.current_joint_positions().unwrap();
+    assert_approx_eq!(positions[0], 0.0);
+    assert_eq!(robot.speaker.messages(), vec!["playback"]);
+}
+
+#[test]
+fn test_invalid_record() {
+    let record_path = write_record("invalid");
+    let result = TrajectoryPlaybackNode::new_from_config(
+        TrajectoryPlaybackNodeConfig {
+            record_client_name: "head".to_owned(),
+            ..config(record_path.clone())
+        },
+        DummyJointTrajectoryClient::new(vec!["j0".to_owned(), "j1".to_owned()]),
+        DummySpeaker::new(),
+    );
+    assert!(matches!(result, Err(Error::NoJointTrajectory(..))));
+
+    let result = TrajectoryPlaybackNode::new_from_config(
+        config(record_path),
+        DummyJointTrajectoryClient::new(vec!["j0".to_owned()]),
+        DummySpeaker::new(),
+    );
+    assert!(matches!(result, Err(Error::MismatchedLength(2, 1))));
+}
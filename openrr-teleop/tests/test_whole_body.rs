@@ -0,0 +1,150 @@
+use arci::{
+    gamepad::{Axis, Button, GamepadEvent},
+    DummyJointTrajectoryClient, DummyMoveBase, JointTrajectoryClient, MoveBase,
+};
+use assert_approx_eq::assert_approx_eq;
+use openrr_client::{create_jacobian_ik_solver, IkSolverParameters, IkSolverWithChain};
+use openrr_teleop::{ControlNode, WholeBodyBindings, WholeBodyNode, WholeBodyNodeConfig};
+use std::{sync::Arc, time::Duration};
+
+/// Bent left arm of the sample URDF, so the end effector can move in all directions.
+const INITIAL_POSITIONS: [f64; 6] = [0.0, -0.5, 0.0, -1.0, 0.0, 0.0];
+
+struct Robot {
+    arm: Arc<DummyJointTrajectoryClient>,
+    base: Arc<DummyMoveBase>,
+    ik_solver_with_chain: Arc<IkSolverWithChain>,
+}
+
+impl Robot {
+    async fn new() -> Self {
+        let chain = k::Chain::<f64>::from_urdf_file("../openrr-planner/sample.urdf").unwrap();
+        let arm_chain = k::SerialChain::from_end(chain.find("l_tool_fixed").unwrap());
+        let arm = Arc::new(DummyJointTrajectoryClient::new(
+            arm_chain
+                .iter_joints()
+                .map(|joint| joint.name.clone())
+                .collect(),
+        ));
+        arm.send_joint_positions(INITIAL_POSITIONS.to_vec(), Duration::default())
+            .await
+            .unwrap();
+        let ik_solver = create_jacobian_ik_solver(&IkSolverParameters {
+            allowable_position_error: 0.001,
+            allowable_angle_error: 0.005,
+            jacobian_multiplier: 0.1,
+            num_max_try: 100,
+        });
+        Self {
+            arm,
+            base: Arc::new(DummyMoveBase::new()),
+            ik_solver_with_chain: Arc::new(IkSolverWithChain::new(
+                arm_chain,
+                Arc::new(ik_solver),
+                k::Constraints::default(),
+            )),
+        }
+    }
+
+    fn node(&self) -> WholeBodyNode<Arc<DummyJointTrajectoryClient>, Arc<DummyMoveBase>> {
+        WholeBodyNode::new_from_config(
+            WholeBodyNodeConfig {
+                mode: "whole_body".to_owned(),
+                linear_velocity: [0.1, 0.1, 0.05],
+                angular_velocity: 0.5,
+                step_duration_secs: 0.1,
+                turbo_gain: 2.0,
+                damping: 0.05,
+                posture_gain: 1.0,
+                bindings: WholeBodyBindings::default(),
+            },
+            self.arm.clone(),
+            self.base.clone(),
+            self.ik_solver_with_chain.clone(),
+        )
+    }
+
+    /// Position of the end effector.
+    fn end_position(&self) -> k::Vector3<f64> {
+        self.ik_solver_with_chain
+            .set_joint_positions_clamped(&self.arm.current_joint_positions().unwrap());
+        self.ik_solver_with_chain.end_transform().translation.vector
+    }
+}
+
+async fn proc_n<N: ControlNode>(node: &N, n: usize) {
+    for _ in 0..n {
+        node.proc().await;
+    }
+}
+
+#[tokio::test]
+async fn test_arm_moves_inside_reach() {
+    let robot = Robot::new().await;
+    let mut node = robot.node();
+    let start = robot.end_position();
+
+    node.set_event(GamepadEvent::ButtonPressed(Button::RightTrigger2));
+    node.set_event(GamepadEvent::AxisChanged(Axis::LeftStickY, 1.0));
+    node.set_event(GamepadEvent::ButtonPressed(Button::West));
+    proc_n(&node, 3).await;
+
+    // 0.1 m/s in x and 0.05 m/s in z for 0.3 seconds by the arm.
+    let end = robot.end_position();
+    assert!((end.x - start.x - 0.03).abs() < 0.005, "{:?}", end - start);
+    assert!((end.y - start.y).abs() < 0.005, "{:?}", end - start);
+    assert!((end.z - start.z - 0.015).abs() < 0.005, "{:?}", end - start);
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0, 1e-3);
+}
+
+#[tokio::test]
+async fn test_base_takes_over_at_reach() {
+    let robot = Robot::new().await;
+    let mut node = robot.node();
+    let start = robot.end_position();
+
+    node.set_event(GamepadEvent::ButtonPressed(Button::RightTrigger2));
+    node.set_event(GamepadEvent::AxisChanged(Axis::LeftStickY, 1.0));
+    node.set_event(GamepadEvent::AxisChanged(Axis::RightStickX, 1.0));
+    proc_n(&node, 100).await;
+
+    // The arm is stretched and the base moves instead, keeping the height of the end effector.
+    let velocity = robot.base.current_velocity().unwrap();
+    assert!((velocity.x - 0.1).abs() < 0.01, "{:?}", velocity);
+    assert!(velocity.y.abs() < 0.02, "{:?}", velocity);
+    assert_approx_eq!(velocity.theta, 0.5);
+    let stretched = robot.end_position();
+    assert!((stretched.z - start.z).abs() < 0.005, "{:?}", stretched);
+
+    // The vertical velocity is still done by the arm at the reach limit.
+    node.set_event(GamepadEvent::ButtonPressed(Button::West));
+    proc_n(&node, 4).await;
+    let end = robot.end_position();
+    assert!(
+        (end.z - stretched.z - 0.02).abs() < 0.005,
+        "{:?}",
+        end - stretched
+    );
+    assert!(robot.base.current_velocity().unwrap().x > 0.05);
+
+    // Releasing the deadman button stops the base.
+    node.set_event(GamepadEvent::ButtonReleased(Button::RightTrigger2));
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0);
+    assert_approx_eq!(robot.base.current_velocity().unwrap().theta, 0.0);
+    node.proc().await;
+    assert_approx_eq!(robot.base.current_velocity().unwrap().x, 0.0);
+}
+
+#[tokio::test]
+async fn test_disabled() {
+    let robot = Robot::new().await;
+    let mut node = robot.node();
+    node.set_event(GamepadEvent::AxisChanged(Axis::LeftStickY, 1.0));
+    node.set_event(GamepadEvent::AxisChanged(Axis::RightStickX, 1.0));
+    proc_n(&node, 3).await;
+    assert_eq!(
+        robot.arm.current_joint_positions().unwrap(),
+        INITIAL_POSITIONS.to_vec()
+    );
+    assert_approx_eq!(robot.base.current_velocity().unwrap().theta, 0.0);
+}
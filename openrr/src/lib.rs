@@ -2,6 +2,10 @@ pub mod apps {
     pub use openrr_apps::*;
 }
 
+pub mod behavior_tree {
+    pub use openrr_behavior_tree::*;
+}
+
 pub mod client {
     pub use openrr_client::*;
 }